@@ -1,4 +1,110 @@
+use anyhow::{bail, Context};
+use clap::Parser;
+use log::info;
+use solana_lite_rpc_blockstore::block_importer::{BlockFetcher, BlockImporter};
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::{
+    BinaryCompression, PostgresSessionConfig,
+};
+use solana_lite_rpc_core::structures::epoch::EpochCache;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::slot_history::Slot;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// import a range of finalized blocks from a solana rpc endpoint into the postgres blockstore
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(
+        short = 'r',
+        long,
+        env = "RPC_ADDR",
+        default_value = "http://0.0.0.0:8899"
+    )]
+    rpc_url: String,
+    #[arg(long, conflicts_with_all = ["from_epoch", "to_epoch"], requires = "to_slot")]
+    from_slot: Option<Slot>,
+    #[arg(long, requires = "from_slot")]
+    to_slot: Option<Slot>,
+    #[arg(long, requires = "to_epoch")]
+    from_epoch: Option<u64>,
+    #[arg(long, requires = "from_epoch")]
+    to_epoch: Option<u64>,
+    /// progress is stored here; rerun with the same range to resume
+    #[arg(short = 'c', long, default_value = "blockstore-import.checkpoint")]
+    checkpoint_file: PathBuf,
+    /// number of blocks fetched in parallel
+    #[arg(short = 'p', long, default_value_t = 8)]
+    parallelism: usize,
+    /// number of slots between two checkpoints
+    #[arg(short = 'b', long, default_value_t = 100)]
+    batch_size: u64,
+    /// compression of newly created epoch schemas (none or zstd)
+    #[arg(long, env = "BLOCKSTORE_COMPRESSION", default_value = "none")]
+    compression: BinaryCompression,
+}
+
+impl Args {
+    fn slot_range(&self, epoch_cache: &EpochCache) -> anyhow::Result<RangeInclusive<Slot>> {
+        let range = match (self.from_slot, self.to_slot, self.from_epoch, self.to_epoch) {
+            (Some(from_slot), Some(to_slot), None, None) => from_slot..=to_slot,
+            (None, None, Some(from_epoch), Some(to_epoch)) => {
+                epoch_cache.get_first_slot_in_epoch(from_epoch)
+                    ..=epoch_cache.get_last_slot_in_epoch(to_epoch)
+            }
+            _ => bail!("Provide either --from-slot/--to-slot or --from-epoch/--to-epoch"),
+        };
+        if range.is_empty() {
+            bail!("Empty slot range {:?}", range);
+        }
+        Ok(range)
+    }
+}
+
 #[tokio::main(flavor = "multi_thread", worker_threads = 16)]
-async fn main() {
+async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
+
+    let args = Args::parse();
+
+    let pg_session_config = PostgresSessionConfig::new_from_env()?
+        .context("Postgres must be enabled (PG_ENABLED) to import blocks")?;
+
+    let rpc_client = Arc::new(RpcClient::new_with_timeout(
+        args.rpc_url.clone(),
+        Duration::from_secs(60),
+    ));
+    let (epoch_cache, _) = EpochCache::bootstrap_epoch(&rpc_client).await?;
+    let slot_range = args.slot_range(&epoch_cache)?;
+
+    let block_storage = PostgresBlockStore::new_with_compression(
+        epoch_cache.clone(),
+        pg_session_config.clone(),
+        args.compression,
+    )
+    .await;
+    let block_storage_query =
+        PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config).await;
+
+    let importer = BlockImporter::new(
+        BlockFetcher::new(rpc_client, args.parallelism),
+        block_storage,
+        block_storage_query,
+        epoch_cache,
+        args.checkpoint_file,
+        args.batch_size,
+    );
+
+    info!("Importing slots {:?} from {}", slot_range, args.rpc_url);
+    let stats = importer.import(slot_range.clone()).await?;
+    info!(
+        "Imported {} blocks with {} transactions in slots {:?}",
+        stats.blocks_imported, stats.transactions_imported, slot_range
+    );
+
+    Ok(())
 }
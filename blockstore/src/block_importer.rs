@@ -0,0 +1,284 @@
+use crate::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use crate::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use anyhow::{bail, Context};
+use futures::StreamExt;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use solana_lite_rpc_cluster_endpoints::rpc_polling::poll_blocks::from_ui_block;
+use solana_lite_rpc_core::structures::epoch::EpochCache;
+use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcBlockConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::slot_history::Slot;
+use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const MAX_FETCH_ATTEMPTS: usize = 3;
+const FETCH_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// progress of an import; all slots below next_slot are imported
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportCheckpoint {
+    pub from_slot: Slot,
+    pub to_slot: Slot,
+    pub next_slot: Slot,
+}
+
+impl ImportCheckpoint {
+    pub fn new(slot_range: &RangeInclusive<Slot>) -> Self {
+        Self {
+            from_slot: *slot_range.start(),
+            to_slot: *slot_range.end(),
+            next_slot: *slot_range.start(),
+        }
+    }
+
+    pub fn is_for_range(&self, slot_range: &RangeInclusive<Slot>) -> bool {
+        self.from_slot == *slot_range.start() && self.to_slot == *slot_range.end()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.next_slot > self.to_slot
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("read checkpoint file {}", path.display()))?;
+        let checkpoint = serde_json::from_str(&content)
+            .with_context(|| format!("parse checkpoint file {}", path.display()))?;
+        Ok(Some(checkpoint))
+    }
+
+    // write to temp file first to survive crashes while writing
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(self)?)
+            .with_context(|| format!("write checkpoint file {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, path)
+            .with_context(|| format!("move checkpoint file to {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// fetches finalized blocks from a solana rpc endpoint
+pub struct BlockFetcher {
+    rpc_client: Arc<RpcClient>,
+    parallelism: usize,
+}
+
+impl BlockFetcher {
+    pub fn new(rpc_client: Arc<RpcClient>, parallelism: usize) -> Self {
+        assert!(parallelism > 0, "need at least one fetcher");
+        Self {
+            rpc_client,
+            parallelism,
+        }
+    }
+
+    // slots which have a block (i.e. not skipped)
+    pub async fn get_block_slots(
+        &self,
+        slot_range: &RangeInclusive<Slot>,
+    ) -> anyhow::Result<Vec<Slot>> {
+        self.rpc_client
+            .get_blocks_with_commitment(
+                *slot_range.start(),
+                Some(*slot_range.end()),
+                CommitmentConfig::finalized(),
+            )
+            .await
+            .with_context(|| format!("get blocks in range {:?}", slot_range))
+    }
+
+    // fetch in parallel, result is ordered by slot
+    pub async fn fetch_blocks(&self, slots: &[Slot]) -> anyhow::Result<Vec<ProducedBlock>> {
+        futures::stream::iter(slots.iter().copied())
+            .map(|slot| self.fetch_block(slot))
+            .buffered(self.parallelism)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    pub async fn fetch_block(&self, slot: Slot) -> anyhow::Result<ProducedBlock> {
+        let config = RpcBlockConfig {
+            transaction_details: Some(TransactionDetails::Full),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
+            encoding: Some(UiTransactionEncoding::Base64),
+            rewards: Some(true),
+        };
+
+        let mut attempt = 1;
+        loop {
+            match self.rpc_client.get_block_with_config(slot, config).await {
+                Ok(block) => {
+                    return Ok(from_ui_block(block, slot, CommitmentConfig::finalized()));
+                }
+                Err(err) if attempt < MAX_FETCH_ATTEMPTS => {
+                    warn!(
+                        "Failed to fetch block {} (attempt {}) - retry: {}",
+                        slot, attempt, err
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(FETCH_RETRY_DELAY).await;
+                }
+                Err(err) => {
+                    bail!("Failed to fetch block {}: {}", slot, err);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportStats {
+    pub blocks_imported: u64,
+    pub transactions_imported: u64,
+}
+
+/// imports a range of finalized blocks into the postgres blockstore
+pub struct BlockImporter {
+    fetcher: BlockFetcher,
+    block_storage: PostgresBlockStore,
+    block_storage_query: PostgresQueryBlockStore,
+    epoch_cache: EpochCache,
+    checkpoint_path: PathBuf,
+    // number of slots processed between two checkpoints
+    batch_size: u64,
+}
+
+impl BlockImporter {
+    pub fn new(
+        fetcher: BlockFetcher,
+        block_storage: PostgresBlockStore,
+        block_storage_query: PostgresQueryBlockStore,
+        epoch_cache: EpochCache,
+        checkpoint_path: PathBuf,
+        batch_size: u64,
+    ) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        Self {
+            fetcher,
+            block_storage,
+            block_storage_query,
+            epoch_cache,
+            checkpoint_path,
+            batch_size,
+        }
+    }
+
+    pub async fn import(&self, slot_range: RangeInclusive<Slot>) -> anyhow::Result<ImportStats> {
+        let mut checkpoint = match ImportCheckpoint::load(&self.checkpoint_path)? {
+            Some(checkpoint) if checkpoint.is_for_range(&slot_range) => {
+                info!(
+                    "Resume import of slots {:?} from slot {}",
+                    slot_range, checkpoint.next_slot
+                );
+                checkpoint
+            }
+            Some(checkpoint) => {
+                bail!(
+                    "Checkpoint file {} belongs to import of slots {}..={} - remove it to start a new import",
+                    self.checkpoint_path.display(),
+                    checkpoint.from_slot,
+                    checkpoint.to_slot
+                );
+            }
+            None => ImportCheckpoint::new(&slot_range),
+        };
+
+        let mut stats = ImportStats::default();
+        let mut prepared_epoch: Option<u64> = None;
+        while !checkpoint.is_complete() {
+            let started = Instant::now();
+            let batch_end = checkpoint
+                .next_slot
+                .saturating_add(self.batch_size - 1)
+                .min(checkpoint.to_slot);
+            let batch_range = checkpoint.next_slot..=batch_end;
+
+            let slots = self.fetcher.get_block_slots(&batch_range).await?;
+            let blocks = self.fetcher.fetch_blocks(&slots).await?;
+
+            for block in blocks {
+                let epoch = self.epoch_cache.get_epoch_at_slot(block.slot).epoch;
+                if prepared_epoch != Some(epoch) {
+                    self.block_storage.prepare_epoch_schema(block.slot).await?;
+                    prepared_epoch = Some(epoch);
+                }
+                // noop if the block already exists
+                self.block_storage.save_block(&block).await?;
+                stats.blocks_imported += 1;
+                stats.transactions_imported += block.transactions.len() as u64;
+            }
+
+            checkpoint.next_slot = batch_end + 1;
+            checkpoint.save(&self.checkpoint_path)?;
+            info!(
+                "Imported {} blocks in slots {:?} in {:.2}s - {} slots left",
+                slots.len(),
+                batch_range,
+                started.elapsed().as_secs_f64(),
+                checkpoint.to_slot.saturating_sub(batch_end)
+            );
+        }
+
+        self.verify(&slot_range).await?;
+        Ok(stats)
+    }
+
+    // compare the blocks in the blockstore with the blocks available from rpc
+    pub async fn verify(&self, slot_range: &RangeInclusive<Slot>) -> anyhow::Result<()> {
+        let expected_slots = self.fetcher.get_block_slots(slot_range).await?;
+        let (Some(first_slot), Some(last_slot)) = (expected_slots.first(), expected_slots.last())
+        else {
+            info!(
+                "No blocks in slot range {:?} - nothing to verify",
+                slot_range
+            );
+            return Ok(());
+        };
+
+        let stored_range = self.block_storage_query.get_slot_range().await;
+        if !stored_range.contains(first_slot) || !stored_range.contains(last_slot) {
+            bail!(
+                "Blockstore slot range {:?} does not cover imported blocks {}..={}",
+                stored_range,
+                first_slot,
+                last_slot
+            );
+        }
+
+        let stored_slots = self
+            .block_storage_query
+            .query_slots(slot_range.clone(), None)
+            .await?;
+        let missing_slots = expected_slots
+            .iter()
+            .filter(|slot| stored_slots.binary_search(slot).is_err())
+            .collect::<Vec<_>>();
+        if !missing_slots.is_empty() {
+            bail!(
+                "{} blocks missing in blockstore, e.g. {:?}",
+                missing_slots.len(),
+                &missing_slots[..missing_slots.len().min(10)]
+            );
+        }
+
+        debug!(
+            "Verified {} blocks in slot range {:?}",
+            expected_slots.len(),
+            slot_range
+        );
+        Ok(())
+    }
+}
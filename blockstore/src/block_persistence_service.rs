@@ -0,0 +1,367 @@
+use crate::block_stores::block_storage_interface::BlockStorageWriter;
+use anyhow::bail;
+use itertools::Itertools;
+use log::{debug, error, info, warn};
+use prometheus::core::GenericGauge;
+use prometheus::{opts, register_int_counter, register_int_gauge, IntCounter};
+use solana_lite_rpc_core::structures::epoch::EpochCache;
+use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
+use solana_lite_rpc_core::types::BlockStream;
+use solana_lite_rpc_core::AnyhowJoinHandle;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::slot_history::Slot;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+lazy_static::lazy_static! {
+    static ref BLOCKSTORE_PERSISTED_SLOT: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_persisted_slot", "Highest slot written to the blockstore")).unwrap();
+    static ref BLOCKSTORE_LAG_SLOTS: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_lag_slots", "Number of slots the blockstore writer is behind the block stream")).unwrap();
+    static ref BLOCKSTORE_QUEUE_LENGTH: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_queue_length", "Number of blocks waiting to be written to the blockstore")).unwrap();
+    static ref BLOCKSTORE_BLOCKS_SAVED: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_blocks_saved", "Number of blocks written to the blockstore")).unwrap();
+    static ref BLOCKSTORE_BLOCKS_FAILED: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_blocks_failed", "Number of blocks which could not be written to the blockstore")).unwrap();
+    static ref BLOCKSTORE_BLOCK_WRITE_RETRIES: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_block_write_retries", "Number of failed block writes which are retried")).unwrap();
+    static ref BLOCKSTORE_BLOCKS_MISSED: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_blocks_missed", "Number of blocks missed by the blockstore writer because it could not keep up")).unwrap();
+}
+
+// max number of blocks taken from the stream in one go
+const MAX_BATCH_SIZE: usize = 32;
+// number of recently written slots to remember to avoid writing a block twice
+const NB_SAVED_SLOTS_TO_REMEMBER: usize = 1024;
+// a failed block is written again with the next batches before it is given up
+const MAX_WRITE_ATTEMPTS: usize = 3;
+const SLOW_WRITE_WARNING_THRESHOLD: Duration = Duration::from_millis(400);
+
+struct PendingBlock {
+    block: ProducedBlock,
+    attempts: usize,
+}
+
+/// writes confirmed and finalized blocks from the block stream to the blockstore
+pub struct BlockPersistenceService {
+    block_storage: Arc<dyn BlockStorageWriter>,
+    epoch_cache: EpochCache,
+}
+
+impl BlockPersistenceService {
+    pub fn new(block_storage: impl BlockStorageWriter + 'static, epoch_cache: EpochCache) -> Self {
+        Self {
+            block_storage: Arc::new(block_storage),
+            epoch_cache,
+        }
+    }
+
+    pub fn start(self, blocks_notifier: BlockStream) -> AnyhowJoinHandle {
+        tokio::spawn(async move {
+            let mut blocks_notifier = blocks_notifier;
+            let mut saved_slots: BTreeSet<Slot> = BTreeSet::new();
+            let mut prepared_epoch: Option<u64> = None;
+            // highest slot of the stream including processed blocks
+            let mut stream_head_slot: Slot = 0;
+            let mut retries: Vec<PendingBlock> = vec![];
+            let mut stream_closed = false;
+
+            info!("Starting blockstore writer");
+            while !stream_closed {
+                let mut batch = match blocks_notifier.recv().await {
+                    Ok(block) => vec![block],
+                    Err(RecvError::Lagged(missed_blocks)) => {
+                        warn!(
+                            "Blockstore writer could not keep up with block stream - missed {} blocks",
+                            missed_blocks
+                        );
+                        BLOCKSTORE_BLOCKS_MISSED.inc_by(missed_blocks);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => {
+                        bail!("Block stream has been closed - abort");
+                    }
+                };
+
+                // take what is already waiting in the channel
+                while batch.len() < MAX_BATCH_SIZE {
+                    match blocks_notifier.try_recv() {
+                        Ok(block) => batch.push(block),
+                        Err(TryRecvError::Lagged(missed_blocks)) => {
+                            BLOCKSTORE_BLOCKS_MISSED.inc_by(missed_blocks);
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Closed) => {
+                            stream_closed = true;
+                            break;
+                        }
+                    }
+                }
+
+                if let Some(slot) = batch.iter().map(|block| block.slot).max() {
+                    stream_head_slot = stream_head_slot.max(slot);
+                }
+                batch.retain(|block| {
+                    block.commitment_config.commitment != CommitmentLevel::Processed
+                });
+
+                let mut pending = std::mem::take(&mut retries);
+                pending.extend(
+                    batch
+                        .into_iter()
+                        .map(|block| PendingBlock { block, attempts: 0 }),
+                );
+                pending.sort_by_key(|pending| pending.block.slot);
+
+                for epoch_first_block in
+                    pending
+                        .iter()
+                        .map(|pending| &pending.block)
+                        .dedup_by(|a, b| {
+                            self.epoch_cache.get_epoch_at_slot(a.slot).epoch
+                                == self.epoch_cache.get_epoch_at_slot(b.slot).epoch
+                        })
+                {
+                    let slot = epoch_first_block.slot;
+                    let epoch = self.epoch_cache.get_epoch_at_slot(slot).epoch;
+                    if prepared_epoch == Some(epoch) {
+                        continue;
+                    }
+                    // creates the schemas of the current and the next epoch
+                    match self.block_storage.prepare_epoch_schema(slot).await {
+                        Ok(created) => {
+                            info!(
+                                "Prepared blockstore schema for epoch {} at slot {} (created: {})",
+                                epoch, slot, created
+                            );
+                            prepared_epoch = Some(epoch);
+                        }
+                        Err(err) => {
+                            error!(
+                                "Failed to prepare blockstore schema for epoch {}: {:?}",
+                                epoch, err
+                            );
+                        }
+                    }
+                }
+
+                retries = self.persist_blocks(pending, &mut saved_slots).await;
+
+                if let Some(persisted_slot) = saved_slots.last() {
+                    BLOCKSTORE_LAG_SLOTS
+                        .set(stream_head_slot.saturating_sub(*persisted_slot) as i64);
+                }
+                BLOCKSTORE_QUEUE_LENGTH.set(blocks_notifier.len() as i64);
+            }
+
+            bail!("Block stream has been closed - abort");
+        })
+    }
+
+    // blocks must be sorted by slot; returns the failed blocks which should be written again
+    async fn persist_blocks(
+        &self,
+        blocks: Vec<PendingBlock>,
+        saved_slots: &mut BTreeSet<Slot>,
+    ) -> Vec<PendingBlock> {
+        let started = Instant::now();
+        let nb_blocks = blocks.len();
+
+        // the first block of a slot not written yet is saved, everything else progresses the commitment
+        let mut new_slots = BTreeSet::new();
+        let (new_blocks, progressed_blocks): (Vec<_>, Vec<_>) =
+            blocks.into_iter().partition(|pending| {
+                !saved_slots.contains(&pending.block.slot) && new_slots.insert(pending.block.slot)
+            });
+
+        let mut failed = vec![];
+        let blocks_to_save = new_blocks
+            .iter()
+            .map(|pending| pending.block.clone())
+            .collect_vec();
+        let results = self.block_storage.save_blocks(&blocks_to_save).await;
+        for (pending, result) in new_blocks.into_iter().zip(results) {
+            match result {
+                Ok(()) => {
+                    BLOCKSTORE_BLOCKS_SAVED.inc();
+                    saved_slots.insert(pending.block.slot);
+                    if saved_slots.len() > NB_SAVED_SLOTS_TO_REMEMBER {
+                        saved_slots.pop_first();
+                    }
+                }
+                Err(err) => failed.push((pending, err)),
+            }
+        }
+        if let Some(persisted_slot) = saved_slots.last() {
+            BLOCKSTORE_PERSISTED_SLOT.set(*persisted_slot as i64);
+        }
+
+        for pending in progressed_blocks {
+            if let Err(err) = self
+                .block_storage
+                .progress_block_commitment_level(&pending.block)
+                .await
+            {
+                failed.push((pending, err));
+            }
+        }
+
+        let mut retries = vec![];
+        for (mut pending, err) in failed {
+            pending.attempts += 1;
+            if pending.attempts < MAX_WRITE_ATTEMPTS {
+                BLOCKSTORE_BLOCK_WRITE_RETRIES.inc();
+                warn!(
+                    "Failed to write block {}@{} to blockstore (attempt {}) - will retry: {:?}",
+                    pending.block.slot,
+                    pending.block.commitment_config.commitment,
+                    pending.attempts,
+                    err
+                );
+                retries.push(pending);
+            } else {
+                BLOCKSTORE_BLOCKS_FAILED.inc();
+                error!(
+                    "Failed to write block {}@{} to blockstore after {} attempts - give up: {:?}",
+                    pending.block.slot,
+                    pending.block.commitment_config.commitment,
+                    pending.attempts,
+                    err
+                );
+            }
+        }
+
+        let elapsed = started.elapsed();
+        debug!(
+            "Blockstore write of {} blocks took {:.2}ms",
+            nb_blocks,
+            elapsed.as_secs_f64() * 1000.0
+        );
+        if elapsed > SLOW_WRITE_WARNING_THRESHOLD {
+            warn!(
+                "Slow blockstore write of {} blocks - took {:.2}ms",
+                nb_blocks,
+                elapsed.as_secs_f64() * 1000.0
+            );
+        }
+
+        retries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use solana_lite_rpc_core::test_utils::create_test_block;
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    // fails the first writes of each slot
+    struct FlakyBlockStorage {
+        failures_per_slot: usize,
+        attempts: Mutex<BTreeMap<Slot, usize>>,
+        saved: Mutex<Vec<Slot>>,
+    }
+
+    #[async_trait]
+    impl BlockStorageWriter for FlakyBlockStorage {
+        async fn prepare_epoch_schema(&self, _slot: Slot) -> anyhow::Result<bool> {
+            Ok(false)
+        }
+
+        async fn save_block(&self, block: &ProducedBlock) -> anyhow::Result<()> {
+            let mut attempts = self.attempts.lock().unwrap();
+            let attempts = attempts.entry(block.slot).or_default();
+            *attempts += 1;
+            if *attempts <= self.failures_per_slot {
+                bail!("write failed");
+            }
+            self.saved.lock().unwrap().push(block.slot);
+            Ok(())
+        }
+
+        async fn progress_block_commitment_level(
+            &self,
+            _block: &ProducedBlock,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn create_pending_block(slot: Slot) -> PendingBlock {
+        PendingBlock {
+            block: create_test_block(slot, CommitmentConfig::confirmed()),
+            attempts: 0,
+        }
+    }
+
+    fn create_service(
+        failures_per_slot: usize,
+    ) -> (BlockPersistenceService, Arc<FlakyBlockStorage>) {
+        let storage = Arc::new(FlakyBlockStorage {
+            failures_per_slot,
+            attempts: Mutex::new(BTreeMap::new()),
+            saved: Mutex::new(vec![]),
+        });
+        let service = BlockPersistenceService {
+            block_storage: storage.clone(),
+            epoch_cache: EpochCache::new_for_tests(),
+        };
+        (service, storage)
+    }
+
+    #[tokio::test]
+    async fn retries_failed_blocks() {
+        let (service, storage) = create_service(1);
+        let mut saved_slots = BTreeSet::new();
+
+        let retries = service
+            .persist_blocks(
+                vec![create_pending_block(10), create_pending_block(11)],
+                &mut saved_slots,
+            )
+            .await;
+        assert_eq!(retries.len(), 2);
+        assert!(saved_slots.is_empty());
+
+        let retries = service.persist_blocks(retries, &mut saved_slots).await;
+        assert!(retries.is_empty());
+        assert_eq!(*storage.saved.lock().unwrap(), vec![10, 11]);
+        assert_eq!(saved_slots, BTreeSet::from([10, 11]));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (service, storage) = create_service(usize::MAX);
+        let mut saved_slots = BTreeSet::new();
+
+        let mut pending = vec![create_pending_block(10)];
+        for _ in 1..MAX_WRITE_ATTEMPTS {
+            pending = service.persist_blocks(pending, &mut saved_slots).await;
+            assert_eq!(pending.len(), 1);
+        }
+        let retries = service.persist_blocks(pending, &mut saved_slots).await;
+        assert!(retries.is_empty());
+        assert_eq!(storage.attempts.lock().unwrap()[&10], MAX_WRITE_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn saves_a_slot_once_per_batch() {
+        let (service, storage) = create_service(0);
+        let mut saved_slots = BTreeSet::new();
+
+        let retries = service
+            .persist_blocks(
+                vec![create_pending_block(10), create_pending_block(10)],
+                &mut saved_slots,
+            )
+            .await;
+        assert!(retries.is_empty());
+        assert_eq!(*storage.saved.lock().unwrap(), vec![10]);
+    }
+}
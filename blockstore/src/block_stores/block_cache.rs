@@ -0,0 +1,439 @@
+use crate::block_stores::block_storage_interface::BlockStorageReader;
+use anyhow::Result;
+use async_trait::async_trait;
+use prometheus::core::GenericGauge;
+use prometheus::{opts, register_int_counter, register_int_gauge, IntCounter};
+use solana_lite_rpc_core::commitment_utils::Commitment;
+use solana_lite_rpc_core::structures::produced_block::{ConfirmedTransactionInfo, ProducedBlock};
+use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+lazy_static::lazy_static! {
+    static ref BLOCK_CACHE_HITS: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_cache_hits", "Number of blocks served from the blockstore read cache")).unwrap();
+    static ref BLOCK_CACHE_MISSES: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_cache_misses", "Number of block lookups which missed the blockstore read cache")).unwrap();
+    static ref BLOCK_CACHE_EVICTIONS: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_cache_evictions", "Number of least recently used blocks evicted from the blockstore read cache")).unwrap();
+    static ref BLOCK_CACHE_INVALIDATIONS: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_cache_invalidations", "Number of cached blocks dropped because their commitment level progressed")).unwrap();
+    static ref BLOCK_CACHE_STALE_INSERTS: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_cache_stale_inserts", "Number of blocks not cached because their slot progressed to a higher commitment level meanwhile")).unwrap();
+    static ref BLOCK_CACHE_SIZE: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_cache_size", "Number of blocks in the blockstore read cache")).unwrap();
+}
+
+// enough for dashboards polling the last few hundred slots
+pub const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 500;
+
+struct CacheEntry {
+    block: ProducedBlock,
+    last_access: u64,
+}
+
+#[derive(Default)]
+struct LruState {
+    entries: HashMap<Slot, CacheEntry>,
+    // last access tick -> slot; first entry is the least recently used
+    access_order: BTreeMap<u64, Slot>,
+    // highest commitment level seen per slot; reads which started before the slot
+    // progressed must not put the outdated block back into the cache
+    commitment_watermarks: BTreeMap<Slot, Commitment>,
+    tick: u64,
+}
+
+impl LruState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, slot: Slot) -> Option<CacheEntry> {
+        let entry = self.entries.remove(&slot)?;
+        self.access_order.remove(&entry.last_access);
+        Some(entry)
+    }
+
+    fn raise_watermark(&mut self, slot: Slot, commitment: Commitment, capacity: usize) {
+        let watermark = self.commitment_watermarks.entry(slot).or_insert(commitment);
+        *watermark = (*watermark).max(commitment);
+        // keep the watermarks of the most recent slots only
+        while self.commitment_watermarks.len() > capacity {
+            self.commitment_watermarks.pop_first();
+        }
+    }
+
+    fn is_below_watermark(&self, block: &ProducedBlock) -> bool {
+        self.commitment_watermarks
+            .get(&block.slot)
+            .is_some_and(|watermark| Commitment::from(block.commitment_config) < *watermark)
+    }
+}
+
+/// size-bounded LRU cache of blocks read from the blockstore
+#[derive(Clone)]
+pub struct BlockCache {
+    capacity: usize,
+    state: Arc<Mutex<LruState>>,
+}
+
+impl BlockCache {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "block cache capacity must be positive");
+        Self {
+            capacity,
+            state: Arc::new(Mutex::new(LruState::default())),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, slot: Slot) -> Option<ProducedBlock> {
+        let mut state = self.state.lock().unwrap();
+        let tick = state.next_tick();
+        let LruState {
+            entries,
+            access_order,
+            ..
+        } = &mut *state;
+
+        let Some(entry) = entries.get_mut(&slot) else {
+            BLOCK_CACHE_MISSES.inc();
+            return None;
+        };
+        access_order.remove(&entry.last_access);
+        access_order.insert(tick, slot);
+        entry.last_access = tick;
+
+        BLOCK_CACHE_HITS.inc();
+        Some(entry.block.clone())
+    }
+
+    pub fn insert(&self, block: ProducedBlock) {
+        let mut state = self.state.lock().unwrap();
+        if state.is_below_watermark(&block) {
+            BLOCK_CACHE_STALE_INSERTS.inc();
+            return;
+        }
+        let tick = state.next_tick();
+        let slot = block.slot;
+
+        state.remove(slot);
+        state.entries.insert(
+            slot,
+            CacheEntry {
+                block,
+                last_access: tick,
+            },
+        );
+        state.access_order.insert(tick, slot);
+
+        while state.entries.len() > self.capacity {
+            let Some((_, lru_slot)) = state.access_order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&lru_slot);
+            BLOCK_CACHE_EVICTIONS.inc();
+        }
+        BLOCK_CACHE_SIZE.set(state.entries.len() as i64);
+    }
+
+    // drop the cached block if it has a lower commitment level than the progressed block
+    // a finalized block also drops the blocks of abandoned forks between its parent and itself
+    pub fn on_block_commitment(&self, block: &ProducedBlock) {
+        let commitment = Commitment::from(block.commitment_config);
+        let mut state = self.state.lock().unwrap();
+        state.raise_watermark(block.slot, commitment, self.capacity);
+        let mut outdated_slots = state
+            .entries
+            .get(&block.slot)
+            .filter(|entry| Commitment::from(entry.block.commitment_config) < commitment)
+            .map(|entry| vec![entry.block.slot])
+            .unwrap_or_default();
+        if commitment == Commitment::Finalized {
+            outdated_slots.extend(
+                state
+                    .entries
+                    .keys()
+                    .filter(|slot| block.parent_slot < **slot && **slot < block.slot),
+            );
+            // blocks of abandoned forks will never be finalized
+            for slot in (block.parent_slot + 1..block.slot)
+                .rev()
+                .take(self.capacity)
+            {
+                state.raise_watermark(slot, Commitment::Finalized, self.capacity);
+            }
+        }
+
+        for slot in &outdated_slots {
+            state.remove(*slot);
+            BLOCK_CACHE_INVALIDATIONS.inc();
+        }
+        if !outdated_slots.is_empty() {
+            BLOCK_CACHE_SIZE.set(state.entries.len() as i64);
+        }
+    }
+}
+
+/// read-through cache for the blocks of a block storage
+pub struct CachedBlockStorageReader {
+    block_storage: Arc<dyn BlockStorageReader>,
+    block_cache: BlockCache,
+}
+
+impl CachedBlockStorageReader {
+    pub fn new(block_storage: Arc<dyn BlockStorageReader>, block_cache: BlockCache) -> Self {
+        Self {
+            block_storage,
+            block_cache,
+        }
+    }
+
+    pub fn block_cache(&self) -> &BlockCache {
+        &self.block_cache
+    }
+}
+
+#[async_trait]
+impl BlockStorageReader for CachedBlockStorageReader {
+    async fn get_slot_range(&self) -> RangeInclusive<Slot> {
+        self.block_storage.get_slot_range().await
+    }
+
+    async fn is_block_in_range(&self, slot: Slot) -> bool {
+        self.block_storage.is_block_in_range(slot).await
+    }
+
+    async fn query_block(&self, slot: Slot) -> Result<ProducedBlock> {
+        if let Some(block) = self.block_cache.get(slot) {
+            return Ok(block);
+        }
+
+        let block = self.block_storage.query_block(slot).await?;
+        self.block_cache.insert(block.clone());
+        Ok(block)
+    }
+
+    async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>> {
+        self.block_storage.query_slots(slot_range, limit).await
+    }
+
+    async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>> {
+        self.block_storage.query_transaction(signature).await
+    }
+
+    async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.block_storage
+            .query_signatures_for_address(account, before, until, max_slot, limit)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_lite_rpc_core::test_utils::{create_fork_block, create_test_block};
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingBlockStorage {
+        queries: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl BlockStorageReader for CountingBlockStorage {
+        async fn get_slot_range(&self) -> RangeInclusive<Slot> {
+            0..=u64::MAX
+        }
+
+        async fn is_block_in_range(&self, _slot: Slot) -> bool {
+            true
+        }
+
+        async fn query_block(&self, slot: Slot) -> Result<ProducedBlock> {
+            self.queries.fetch_add(1, Ordering::Relaxed);
+            Ok(create_test_block(slot, CommitmentConfig::confirmed()))
+        }
+
+        async fn query_slots(
+            &self,
+            _slot_range: RangeInclusive<Slot>,
+            _limit: Option<usize>,
+        ) -> Result<Vec<Slot>> {
+            Ok(vec![])
+        }
+
+        async fn query_transaction(
+            &self,
+            _signature: &Signature,
+        ) -> Result<Option<ConfirmedTransactionInfo>> {
+            Ok(None)
+        }
+
+        async fn query_signatures_for_address(
+            &self,
+            _account: Pubkey,
+            _before: Option<Signature>,
+            _until: Option<Signature>,
+            _max_slot: Option<Slot>,
+            _limit: usize,
+        ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+            Ok(vec![])
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = BlockCache::new(2);
+        cache.insert(create_test_block(100, CommitmentConfig::confirmed()));
+        cache.insert(create_test_block(101, CommitmentConfig::confirmed()));
+        // 100 becomes the most recently used
+        assert!(cache.get(100).is_some());
+
+        cache.insert(create_test_block(102, CommitmentConfig::confirmed()));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(101).is_none());
+        assert!(cache.get(100).is_some());
+        assert!(cache.get(102).is_some());
+    }
+
+    #[test]
+    fn invalidates_on_commitment_progress() {
+        let cache = BlockCache::new(10);
+        cache.insert(create_test_block(100, CommitmentConfig::confirmed()));
+        cache.insert(create_test_block(101, CommitmentConfig::finalized()));
+
+        // same level - keep
+        cache.on_block_commitment(&create_test_block(100, CommitmentConfig::confirmed()));
+        assert!(cache.get(100).is_some());
+
+        cache.on_block_commitment(&create_test_block(100, CommitmentConfig::finalized()));
+        cache.on_block_commitment(&create_test_block(101, CommitmentConfig::finalized()));
+        assert!(cache.get(100).is_none());
+        assert!(cache.get(101).is_some());
+    }
+
+    #[test]
+    fn invalidates_abandoned_forks() {
+        let cache = BlockCache::new(10);
+        // 101 and 103 are on a fork which is abandoned by the finalized 104
+        cache.insert(create_fork_block(
+            101,
+            100,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+        cache.insert(create_fork_block(
+            102,
+            100,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+        cache.insert(create_fork_block(
+            103,
+            101,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+        cache.insert(create_fork_block(
+            105,
+            104,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+
+        cache.on_block_commitment(&create_fork_block(
+            104,
+            102,
+            CommitmentConfig::finalized(),
+            vec![],
+        ));
+        assert!(cache.get(103).is_none());
+        assert!(cache.get(101).is_some());
+        assert!(cache.get(102).is_some());
+        assert!(cache.get(105).is_some());
+
+        cache.on_block_commitment(&create_fork_block(
+            102,
+            100,
+            CommitmentConfig::finalized(),
+            vec![],
+        ));
+        assert!(cache.get(101).is_none());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn skips_blocks_below_commitment_watermark() {
+        let cache = BlockCache::new(10);
+        // a read of the confirmed block is in flight while the slot gets finalized
+        cache.on_block_commitment(&create_test_block(100, CommitmentConfig::finalized()));
+        cache.insert(create_test_block(100, CommitmentConfig::confirmed()));
+        assert!(cache.get(100).is_none());
+
+        cache.insert(create_test_block(100, CommitmentConfig::finalized()));
+        assert!(cache.get(100).is_some());
+
+        // same for a block of a fork abandoned by the finalized block
+        cache.on_block_commitment(&create_fork_block(
+            104,
+            102,
+            CommitmentConfig::finalized(),
+            vec![],
+        ));
+        cache.insert(create_fork_block(
+            103,
+            101,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+        assert!(cache.get(103).is_none());
+
+        // slots which did not progress are cached
+        cache.insert(create_test_block(105, CommitmentConfig::confirmed()));
+        assert!(cache.get(105).is_some());
+    }
+
+    #[tokio::test]
+    async fn reads_through_to_block_storage() {
+        let block_storage = Arc::new(CountingBlockStorage::default());
+        let cached = CachedBlockStorageReader::new(block_storage.clone(), BlockCache::new(10));
+
+        assert_eq!(cached.query_block(100).await.unwrap().slot, 100);
+        assert_eq!(cached.query_block(100).await.unwrap().slot, 100);
+        assert_eq!(block_storage.queries.load(Ordering::Relaxed), 1);
+
+        cached
+            .block_cache()
+            .on_block_commitment(&create_test_block(100, CommitmentConfig::finalized()));
+        cached.query_block(100).await.unwrap();
+        assert_eq!(block_storage.queries.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_lite_rpc_core::structures::produced_block::{ConfirmedTransactionInfo, ProducedBlock};
+use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// the block is not stored, e.g. a skipped slot or a slot outside of the stored range
+/// any other error of query_block is a failure of the storage
+#[derive(Debug, thiserror::Error)]
+#[error("Block {slot} not found")]
+pub struct BlockNotFound {
+    pub slot: Slot,
+}
+
+impl BlockNotFound {
+    pub fn is_block_not_found(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<BlockNotFound>().is_some()
+    }
+}
+
+/// read path of a persistent block storage (confirmed and finalized blocks)
+#[async_trait]
+pub trait BlockStorageReader: Send + Sync {
+    // min and max slot of all stored blocks; empty range if there are no blocks
+    async fn get_slot_range(&self) -> RangeInclusive<Slot>;
+
+    // true if the slot is within the stored slot range of its epoch
+    async fn is_block_in_range(&self, slot: Slot) -> bool;
+
+    // BlockNotFound if the block is not stored
+    async fn query_block(&self, slot: Slot) -> Result<ProducedBlock>;
+
+    // slots of stored blocks in range (ascending), at most limit slots
+    async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>>;
+
+    async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>>;
+
+    // newest first; before and until are exclusive; confirmation_status is not set
+    async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+}
+
+/// write path of a persistent block storage
+#[async_trait]
+pub trait BlockStorageWriter: Send + Sync {
+    // prepare storage for the epoch of the slot and the next epoch; true if anything was created
+    async fn prepare_epoch_schema(&self, slot: Slot) -> Result<bool>;
+
+    // noop if the block already exists
+    async fn save_block(&self, block: &ProducedBlock) -> Result<()>;
+
+    // one result per block in the order of blocks; storages which can write several blocks at once override this
+    async fn save_blocks(&self, blocks: &[ProducedBlock]) -> Vec<Result<()>> {
+        let mut results = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            results.push(self.save_block(block).await);
+        }
+        results
+    }
+
+    async fn progress_block_commitment_level(&self, block: &ProducedBlock) -> Result<()>;
+}
+
+/// block storage serving both the read and the write path from one instance
+pub trait BlockStorage: BlockStorageReader + BlockStorageWriter {}
+
+impl<T: BlockStorageReader + BlockStorageWriter> BlockStorage for T {}
+
+// allows sharing one storage between history and the block writer
+#[async_trait]
+impl<T: BlockStorageReader + ?Sized> BlockStorageReader for Arc<T> {
+    async fn get_slot_range(&self) -> RangeInclusive<Slot> {
+        self.as_ref().get_slot_range().await
+    }
+
+    async fn is_block_in_range(&self, slot: Slot) -> bool {
+        self.as_ref().is_block_in_range(slot).await
+    }
+
+    async fn query_block(&self, slot: Slot) -> Result<ProducedBlock> {
+        self.as_ref().query_block(slot).await
+    }
+
+    async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>> {
+        self.as_ref().query_slots(slot_range, limit).await
+    }
+
+    async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>> {
+        self.as_ref().query_transaction(signature).await
+    }
+
+    async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.as_ref()
+            .query_signatures_for_address(account, before, until, max_slot, limit)
+            .await
+    }
+}
+
+#[async_trait]
+impl<T: BlockStorageWriter + ?Sized> BlockStorageWriter for Arc<T> {
+    async fn prepare_epoch_schema(&self, slot: Slot) -> Result<bool> {
+        self.as_ref().prepare_epoch_schema(slot).await
+    }
+
+    async fn save_block(&self, block: &ProducedBlock) -> Result<()> {
+        self.as_ref().save_block(block).await
+    }
+
+    async fn save_blocks(&self, blocks: &[ProducedBlock]) -> Vec<Result<()>> {
+        self.as_ref().save_blocks(blocks).await
+    }
+
+    async fn progress_block_commitment_level(&self, block: &ProducedBlock) -> Result<()> {
+        self.as_ref().progress_block_commitment_level(block).await
+    }
+}
@@ -1,40 +1,80 @@
+use crate::block_stores::block_storage_interface::BlockNotFound;
 use anyhow::bail;
 use log::warn;
 use solana_lite_rpc_cluster_endpoints::rpc_polling;
 use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::{Error as ClientError, ErrorKind as ClientErrorKind};
 use solana_rpc_client_api::config::RpcBlockConfig;
+use solana_rpc_client_api::custom_error::{
+    JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP, JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+    JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+};
+use solana_rpc_client_api::request::RpcError;
 use solana_sdk::clock::Slot;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
 use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+const FIRST_SLOT_UNKNOWN: u64 = u64::MAX;
+
 pub struct FaithfulBlockStore {
     faithful_rpc_client: Arc<RpcClient>, // to fetch legacy blocks from faithful_history
+    // the archive only grows to the right, so the first slot is fetched once
+    first_available_slot: AtomicU64,
 }
 
 impl FaithfulBlockStore {
     pub fn new(faithful_rpc_client: Arc<RpcClient>) -> Self {
         Self {
             faithful_rpc_client,
+            first_available_slot: AtomicU64::new(FIRST_SLOT_UNKNOWN),
         }
     }
 
-    pub fn get_slot_range(&self) -> RangeInclusive<Slot> {
-        // TODO
-        warn!("slot_range not implemented for FaithfulBlockStore");
-        RangeInclusive::new(1, 0) // empty
+    // lowest slot served by the archive; the archive is assumed to reach up to the slots in postgres
+    pub async fn get_first_available_slot(&self) -> Option<Slot> {
+        let cached = self.first_available_slot.load(Ordering::Relaxed);
+        if cached != FIRST_SLOT_UNKNOWN {
+            return Some(cached);
+        }
+
+        match self.faithful_rpc_client.get_first_available_block().await {
+            Ok(first_slot) => {
+                self.first_available_slot
+                    .store(first_slot, Ordering::Relaxed);
+                Some(first_slot)
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to get first available block from faithful_history: {}",
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    // true if the archive serves the slot range, checked by fetching its last block
+    pub async fn covers_slot_range(&self, slot_range: &RangeInclusive<Slot>) -> bool {
+        match self.get_first_available_slot().await {
+            Some(first_slot) if first_slot <= *slot_range.start() => {
+                self.get_block(*slot_range.end()).await.is_ok()
+            }
+            _ => false,
+        }
     }
 
     pub async fn get_block(&self, slot: Slot) -> anyhow::Result<ProducedBlock> {
-        // TODO check what parameters we want
+        // same parameters as in rpc_polling::poll_blocks
         let faithful_config = RpcBlockConfig {
-            encoding: Some(UiTransactionEncoding::Base58),
+            encoding: Some(UiTransactionEncoding::Base64),
             transaction_details: Some(TransactionDetails::Full),
-            rewards: None,
-            commitment: None,
-            max_supported_transaction_version: None,
+            rewards: Some(true),
+            commitment: Some(CommitmentConfig::finalized()),
+            max_supported_transaction_version: Some(0),
         };
 
         match self
@@ -47,12 +87,28 @@ impl FaithfulBlockStore {
                 slot,
                 CommitmentConfig::finalized(),
             )),
+            Err(err) if is_block_not_available(&err) => Err(BlockNotFound { slot }.into()),
             Err(err) => {
                 bail!(format!(
-                    "Block {} not found in faithful_history: {}",
+                    "Failed to get block {} from faithful_history: {}",
                     slot, err
                 ));
             }
         }
     }
 }
+
+// same error codes as solana rpc getBlock for blocks which are not stored
+fn is_block_not_available(err: &ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. })
+            if [
+                JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+                JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED,
+                JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP,
+            ]
+            .contains(code)
+    )
+}
@@ -0,0 +1,294 @@
+use dashmap::DashMap;
+use solana_lite_rpc_core::commitment_utils::Commitment;
+use solana_lite_rpc_core::structures::produced_block::{ConfirmedTransactionInfo, ProducedBlock};
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// keeps the blocks of the most recent slots in memory
+/// blocks are replaced when their commitment level progresses
+#[derive(Clone)]
+pub struct InmemoryBlockStore {
+    blocks: Arc<DashMap<Slot, ProducedBlock>>,
+    // index of the transactions in blocks
+    signatures: Arc<DashMap<Signature, Slot>>,
+    highest_slot: Arc<AtomicU64>,
+    number_of_slots_to_keep: u64,
+}
+
+impl InmemoryBlockStore {
+    pub fn new(number_of_slots_to_keep: u64) -> Self {
+        Self {
+            blocks: Arc::new(DashMap::new()),
+            signatures: Arc::new(DashMap::new()),
+            highest_slot: Arc::new(AtomicU64::new(0)),
+            number_of_slots_to_keep,
+        }
+    }
+
+    // true if the block was added or its commitment level was updated
+    pub fn save(&self, block: &ProducedBlock) -> bool {
+        let slot = block.slot;
+        let highest_slot = self
+            .highest_slot
+            .fetch_max(slot, Ordering::Relaxed)
+            .max(slot);
+        if highest_slot.saturating_sub(slot) > self.number_of_slots_to_keep {
+            // too old
+            return false;
+        }
+
+        if Commitment::from(block.commitment_config) == Commitment::Finalized {
+            self.remove_abandoned_forks(block);
+        }
+
+        let updated = match self.blocks.entry(slot) {
+            dashmap::mapref::entry::Entry::Occupied(mut entry) => {
+                if Commitment::from(block.commitment_config)
+                    > Commitment::from(entry.get().commitment_config)
+                {
+                    entry.insert(block.clone());
+                    self.index_transactions(block);
+                    true
+                } else {
+                    false
+                }
+            }
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                entry.insert(block.clone());
+                self.index_transactions(block);
+                true
+            }
+        };
+
+        if self.blocks.len() as u64 > self.number_of_slots_to_keep {
+            let cleanup_slot = highest_slot.saturating_sub(self.number_of_slots_to_keep);
+            self.blocks.retain(|slot, block| {
+                if *slot >= cleanup_slot {
+                    return true;
+                }
+                for tx in &block.transactions {
+                    self.signatures
+                        .remove_if(&tx.signature, |_, indexed_slot| indexed_slot == slot);
+                }
+                false
+            });
+        }
+
+        updated
+    }
+
+    // a finalized block orphans all blocks between its parent and itself
+    fn remove_abandoned_forks(&self, finalized_block: &ProducedBlock) {
+        let abandoned_slots: Vec<Slot> = self
+            .blocks
+            .iter()
+            .map(|entry| *entry.key())
+            .filter(|slot| finalized_block.parent_slot < *slot && *slot < finalized_block.slot)
+            .collect();
+        for slot in abandoned_slots {
+            let Some((slot, block)) = self.blocks.remove(&slot) else {
+                continue;
+            };
+            for tx in &block.transactions {
+                self.signatures
+                    .remove_if(&tx.signature, |_, indexed_slot| *indexed_slot == slot);
+            }
+        }
+    }
+
+    fn index_transactions(&self, block: &ProducedBlock) {
+        for tx in &block.transactions {
+            self.signatures.insert(tx.signature, block.slot);
+        }
+    }
+
+    pub fn get_transaction(&self, signature: &Signature) -> Option<ConfirmedTransactionInfo> {
+        let slot = *self.signatures.get(signature)?;
+        let block = self.get(slot)?;
+        let transaction = block
+            .transactions
+            .iter()
+            .find(|tx| tx.signature == *signature)?
+            .clone();
+        Some(ConfirmedTransactionInfo {
+            slot,
+            block_time: block.block_time,
+            commitment_config: block.commitment_config,
+            transaction,
+        })
+    }
+
+    pub fn get(&self, slot: Slot) -> Option<ProducedBlock> {
+        self.blocks.get(&slot).map(|block| block.value().clone())
+    }
+
+    // slots in range with at least the given commitment level; unordered
+    pub fn get_slots(
+        &self,
+        slot_range: &RangeInclusive<Slot>,
+        commitment: Commitment,
+    ) -> Vec<Slot> {
+        self.blocks
+            .iter()
+            .filter(|entry| slot_range.contains(entry.key()))
+            .filter(|entry| Commitment::from(entry.value().commitment_config) >= commitment)
+            .map(|entry| *entry.key())
+            .collect()
+    }
+
+    pub fn get_slot_range(&self) -> Option<(Slot, Slot)> {
+        let min = self.blocks.iter().map(|entry| *entry.key()).min()?;
+        let max = self.blocks.iter().map(|entry| *entry.key()).max()?;
+        Some((min, max))
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_lite_rpc_core::test_utils::{create_fork_block, create_test_block, create_test_tx};
+    use solana_sdk::commitment_config::CommitmentConfig;
+
+    #[test]
+    fn test_commitment_level_progression() {
+        let store = InmemoryBlockStore::new(100);
+        let block = create_test_block(1000, CommitmentConfig::confirmed());
+
+        assert!(store.save(&block));
+        assert!(!store.save(&block.to_confirmed_block()));
+        assert!(store.save(&block.to_finalized_block()));
+        assert!(!store.save(&block.to_confirmed_block()));
+
+        assert_eq!(
+            store.get(1000).unwrap().commitment_config,
+            CommitmentConfig::finalized()
+        );
+    }
+
+    #[test]
+    fn test_old_slots_are_removed() {
+        let store = InmemoryBlockStore::new(10);
+        for slot in 1000..1100 {
+            store.save(&create_test_block(slot, CommitmentConfig::confirmed()));
+        }
+
+        assert!(store.len() <= 11);
+        assert!(store.get(1050).is_none());
+        assert!(store.get(1099).is_some());
+        assert!(!store.save(&create_test_block(1050, CommitmentConfig::finalized())));
+        assert_eq!(store.get_slot_range().map(|(_, max)| max), Some(1099));
+    }
+
+    #[test]
+    fn test_get_slots_by_commitment() {
+        let store = InmemoryBlockStore::new(100);
+        store.save(&create_test_block(1000, CommitmentConfig::finalized()));
+        store.save(&create_test_block(1001, CommitmentConfig::confirmed()));
+        store.save(&create_test_block(1002, CommitmentConfig::processed()));
+
+        let mut confirmed = store.get_slots(&(1000..=1002), Commitment::Confirmed);
+        confirmed.sort();
+        assert_eq!(confirmed, vec![1000, 1001]);
+        assert_eq!(
+            store.get_slots(&(1000..=1002), Commitment::Finalized),
+            vec![1000]
+        );
+        assert!(store
+            .get_slots(&(1003..=1010), Commitment::Processed)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_transaction() {
+        let store = InmemoryBlockStore::new(10);
+        let tx = create_test_tx();
+        let signature = tx.signature;
+        store.save(&create_fork_block(
+            1000,
+            999,
+            CommitmentConfig::confirmed(),
+            vec![create_test_tx(), tx],
+        ));
+
+        let confirmed_tx = store.get_transaction(&signature).unwrap();
+        assert_eq!(confirmed_tx.slot, 1000);
+        assert_eq!(confirmed_tx.transaction.signature, signature);
+        assert_eq!(
+            confirmed_tx.commitment_config,
+            CommitmentConfig::confirmed()
+        );
+        assert!(store.get_transaction(&Signature::new_unique()).is_none());
+
+        // evict slot 1000
+        for slot in 1001..1020 {
+            store.save(&create_test_block(slot, CommitmentConfig::confirmed()));
+        }
+        assert!(store.get_transaction(&signature).is_none());
+    }
+
+    #[test]
+    fn test_abandoned_forks_are_removed() {
+        let store = InmemoryBlockStore::new(100);
+        let abandoned_tx = create_test_tx();
+        let abandoned_signature = abandoned_tx.signature;
+        //        / 1001 - 1003          (abandoned)
+        // 1000 --
+        //        \ 1002 - 1004          (finalized)
+        store.save(&create_fork_block(
+            1000,
+            999,
+            CommitmentConfig::finalized(),
+            vec![],
+        ));
+        store.save(&create_fork_block(
+            1001,
+            1000,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+        store.save(&create_fork_block(
+            1002,
+            1000,
+            CommitmentConfig::confirmed(),
+            vec![],
+        ));
+        store.save(&create_fork_block(
+            1003,
+            1001,
+            CommitmentConfig::processed(),
+            vec![abandoned_tx],
+        ));
+        assert!(store.get_transaction(&abandoned_signature).is_some());
+
+        store.save(&create_fork_block(
+            1004,
+            1002,
+            CommitmentConfig::finalized(),
+            vec![],
+        ));
+        assert!(store.get(1003).is_none());
+        assert!(store.get_transaction(&abandoned_signature).is_none());
+        // not between 1002 and 1004 - removed once 1002 is finalized
+        assert!(store.get(1001).is_some());
+
+        store.save(&create_fork_block(
+            1002,
+            1000,
+            CommitmentConfig::finalized(),
+            vec![],
+        ));
+        assert!(store.get(1001).is_none());
+        assert_eq!(store.len(), 3);
+    }
+}
@@ -0,0 +1,883 @@
+use crate::block_stores::block_storage_interface::{
+    BlockNotFound, BlockStorageReader, BlockStorageWriter,
+};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use solana_lite_rpc_core::commitment_utils::Commitment;
+use solana_lite_rpc_core::structures::epoch::{EpochCache, EpochRef};
+use solana_lite_rpc_core::structures::produced_block::{
+    ConfirmedTransactionInfo, ProducedBlock, ProducedBlockInner, TransactionInfo,
+};
+use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::hash::Hash;
+use solana_sdk::message::v0::MessageAddressTableLookup;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status::Reward;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+// layout: <base_dir>/epoch_<N>/blocks.seg - append-only segment of bincode encoded blocks
+//         <base_dir>/epoch_<N>/slots.idx  - append-only slot index; a later entry for a slot wins
+const EPOCH_DIR_PREFIX: &str = "epoch_";
+const SEGMENT_FILE: &str = "blocks.seg";
+const INDEX_FILE: &str = "slots.idx";
+// slot (u64) + offset (u64) + length (u32) + commitment (u8)
+const INDEX_ENTRY_SIZE: usize = 21;
+
+#[derive(Serialize, Deserialize)]
+struct LocalTransaction {
+    signature: Signature,
+    is_vote: bool,
+    err: Option<TransactionError>,
+    cu_requested: Option<u32>,
+    prioritization_fees: Option<u64>,
+    cu_consumed: Option<u64>,
+    recent_blockhash: Hash,
+    message: VersionedMessage,
+    writable_accounts: Vec<Pubkey>,
+    readable_accounts: Vec<Pubkey>,
+    address_lookup_tables: Vec<MessageAddressTableLookup>,
+}
+
+impl LocalTransaction {
+    fn new(value: &TransactionInfo) -> Self {
+        Self {
+            signature: value.signature,
+            is_vote: value.is_vote,
+            err: value.err.clone(),
+            cu_requested: value.cu_requested,
+            prioritization_fees: value.prioritization_fees,
+            cu_consumed: value.cu_consumed,
+            recent_blockhash: value.recent_blockhash,
+            message: value.message.clone(),
+            writable_accounts: value.writable_accounts.clone(),
+            readable_accounts: value.readable_accounts.clone(),
+            address_lookup_tables: value.address_lookup_tables.clone(),
+        }
+    }
+
+    fn into_transaction_info(self) -> TransactionInfo {
+        TransactionInfo {
+            signature: self.signature,
+            is_vote: self.is_vote,
+            err: self.err,
+            cu_requested: self.cu_requested,
+            prioritization_fees: self.prioritization_fees,
+            cu_consumed: self.cu_consumed,
+            recent_blockhash: self.recent_blockhash,
+            message: self.message,
+            writable_accounts: self.writable_accounts,
+            readable_accounts: self.readable_accounts,
+            address_lookup_tables: self.address_lookup_tables,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LocalBlock {
+    slot: Slot,
+    block_height: u64,
+    blockhash: Hash,
+    previous_blockhash: Hash,
+    parent_slot: Slot,
+    block_time: u64,
+    leader_id: Option<String>,
+    rewards: Option<Vec<Reward>>,
+    transactions: Vec<LocalTransaction>,
+}
+
+impl LocalBlock {
+    fn new(value: &ProducedBlock) -> Self {
+        Self {
+            slot: value.slot,
+            block_height: value.block_height,
+            blockhash: value.blockhash,
+            previous_blockhash: value.previous_blockhash,
+            parent_slot: value.parent_slot,
+            block_time: value.block_time,
+            leader_id: value.leader_id.clone(),
+            rewards: value.rewards.clone(),
+            transactions: value
+                .transactions
+                .iter()
+                .map(LocalTransaction::new)
+                .collect(),
+        }
+    }
+
+    fn into_produced_block(self, commitment: Commitment) -> ProducedBlock {
+        let inner = ProducedBlockInner {
+            transactions: self
+                .transactions
+                .into_iter()
+                .map(LocalTransaction::into_transaction_info)
+                .collect(),
+            leader_id: self.leader_id,
+            blockhash: self.blockhash,
+            block_height: self.block_height,
+            slot: self.slot,
+            parent_slot: self.parent_slot,
+            block_time: self.block_time,
+            previous_blockhash: self.previous_blockhash,
+            rewards: self.rewards,
+        };
+        ProducedBlock::new(inner, commitment.into_commiment_config())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlotLocation {
+    offset: u64,
+    length: u32,
+    commitment: Commitment,
+}
+
+fn encode_index_entry(slot: Slot, location: &SlotLocation) -> [u8; INDEX_ENTRY_SIZE] {
+    let mut entry = [0u8; INDEX_ENTRY_SIZE];
+    entry[0..8].copy_from_slice(&slot.to_le_bytes());
+    entry[8..16].copy_from_slice(&location.offset.to_le_bytes());
+    entry[16..20].copy_from_slice(&location.length.to_le_bytes());
+    entry[20] = location.commitment as u8;
+    entry
+}
+
+fn decode_index_entry(entry: &[u8]) -> Option<(Slot, SlotLocation)> {
+    let commitment = match entry[20] {
+        1 => Commitment::Confirmed,
+        2 => Commitment::Finalized,
+        _ => return None,
+    };
+    Some((
+        Slot::from_le_bytes(entry[0..8].try_into().ok()?),
+        SlotLocation {
+            offset: u64::from_le_bytes(entry[8..16].try_into().ok()?),
+            length: u32::from_le_bytes(entry[16..20].try_into().ok()?),
+            commitment,
+        },
+    ))
+}
+
+struct EpochSegment {
+    dir: PathBuf,
+    segment: File,
+    segment_length: u64,
+    index: File,
+    slots: BTreeMap<Slot, SlotLocation>,
+}
+
+impl EpochSegment {
+    fn build_dir_name(epoch: EpochRef) -> String {
+        format!("{}{}", EPOCH_DIR_PREFIX, epoch.get_epoch())
+    }
+
+    fn parse_epoch_from_dir_name(dir_name: &str) -> Option<EpochRef> {
+        dir_name
+            .strip_prefix(EPOCH_DIR_PREFIX)
+            .and_then(|epoch| epoch.parse::<u64>().ok())
+            .map(EpochRef::new)
+    }
+
+    fn open(dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("create epoch dir {}", dir.display()))?;
+        let segment = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(dir.join(SEGMENT_FILE))
+            .context("open block segment")?;
+        let segment_length = segment.metadata()?.len();
+        let mut index = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .read(true)
+            .open(dir.join(INDEX_FILE))
+            .context("open slot index")?;
+
+        let mut index_bytes = Vec::new();
+        index.read_to_end(&mut index_bytes)?;
+        let valid_index_length = index_bytes.len() - index_bytes.len() % INDEX_ENTRY_SIZE;
+        if valid_index_length != index_bytes.len() {
+            // torn write of the last index entry
+            warn!("Truncate incomplete slot index entry in {}", dir.display());
+            index.set_len(valid_index_length as u64)?;
+        }
+
+        let mut slots = BTreeMap::new();
+        for entry in index_bytes[..valid_index_length].chunks_exact(INDEX_ENTRY_SIZE) {
+            match decode_index_entry(entry) {
+                Some((slot, location))
+                    if location
+                        .offset
+                        .checked_add(location.length as u64)
+                        .map(|end| end <= segment_length)
+                        .unwrap_or(false) =>
+                {
+                    slots.insert(slot, location);
+                }
+                _ => warn!("Ignore invalid slot index entry in {}", dir.display()),
+            }
+        }
+
+        Ok(Self {
+            dir,
+            segment,
+            segment_length,
+            index,
+            slots,
+        })
+    }
+
+    // note: no fsync; after a crash a torn tail of the segment is ignored as it is not indexed
+    fn append_block(&mut self, slot: Slot, data: &[u8], commitment: Commitment) -> Result<()> {
+        let location = SlotLocation {
+            offset: self.segment_length,
+            length: u32::try_from(data.len()).context("block too large")?,
+            commitment,
+        };
+        self.segment.write_all(data)?;
+        self.segment_length += data.len() as u64;
+        self.write_index_entry(slot, location)
+    }
+
+    fn write_index_entry(&mut self, slot: Slot, location: SlotLocation) -> Result<()> {
+        self.index.write_all(&encode_index_entry(slot, &location))?;
+        self.slots.insert(slot, location);
+        Ok(())
+    }
+
+    fn read_block(&self, location: &SlotLocation) -> Result<LocalBlock> {
+        // separate handle as readers run concurrently
+        let mut segment = File::open(self.dir.join(SEGMENT_FILE))?;
+        segment.seek(SeekFrom::Start(location.offset))?;
+        let mut data = vec![0u8; location.length as usize];
+        segment.read_exact(&mut data)?;
+        bincode::deserialize(&data).context("decode block from segment")
+    }
+}
+
+#[derive(Default)]
+struct LocalState {
+    epochs: BTreeMap<EpochRef, EpochSegment>,
+    // signature -> (slot, position of the transaction in the block)
+    signatures: HashMap<Signature, (Slot, u32)>,
+    // account -> (slot, position) of the transactions touching the account
+    account_transactions: HashMap<Pubkey, BTreeSet<(Slot, u32)>>,
+}
+
+impl LocalState {
+    fn get_location(&self, slot: Slot) -> Option<(&EpochSegment, SlotLocation)> {
+        self.epochs.values().find_map(|epoch_segment| {
+            epoch_segment
+                .slots
+                .get(&slot)
+                .map(|location| (epoch_segment, *location))
+        })
+    }
+
+    // true if the epoch was created
+    fn open_epoch_if_necessary(&mut self, base_dir: &Path, epoch: EpochRef) -> Result<bool> {
+        if self.epochs.contains_key(&epoch) {
+            return Ok(false);
+        }
+        let dir = base_dir.join(EpochSegment::build_dir_name(epoch));
+        self.epochs.insert(epoch, EpochSegment::open(dir)?);
+        info!("Start new epoch {} in local blockstore", epoch);
+        Ok(true)
+    }
+
+    fn index_transactions(&mut self, block: &LocalBlock) {
+        for (position, tx) in block.transactions.iter().enumerate() {
+            let key = (block.slot, position as u32);
+            self.signatures.insert(tx.signature, key);
+            for account in tx.writable_accounts.iter().chain(&tx.readable_accounts) {
+                self.account_transactions
+                    .entry(*account)
+                    .or_default()
+                    .insert(key);
+            }
+        }
+    }
+}
+
+/// embedded block storage keeping one append-only segment file per epoch, no postgres needed
+/// note: the signature and account indexes are kept in memory and rebuilt on startup
+pub struct LocalBlockStore {
+    base_dir: PathBuf,
+    epoch_schedule: EpochCache,
+    state: Arc<RwLock<LocalState>>,
+}
+
+impl LocalBlockStore {
+    pub fn open(base_dir: impl AsRef<Path>, epoch_schedule: EpochCache) -> Result<Self> {
+        let started = Instant::now();
+        let base_dir = base_dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&base_dir)
+            .with_context(|| format!("create blockstore dir {}", base_dir.display()))?;
+
+        let mut state = LocalState::default();
+        for dir_entry in std::fs::read_dir(&base_dir)? {
+            let dir_entry = dir_entry?;
+            let Some(epoch) = dir_entry
+                .file_name()
+                .to_str()
+                .and_then(EpochSegment::parse_epoch_from_dir_name)
+            else {
+                continue;
+            };
+            let epoch_segment = EpochSegment::open(dir_entry.path())?;
+            for location in epoch_segment.slots.values() {
+                let block = epoch_segment.read_block(location)?;
+                state.index_transactions(&block);
+            }
+            state.epochs.insert(epoch, epoch_segment);
+        }
+
+        info!(
+            "Opened local blockstore {} with {} epochs and {} transactions in {:.2}s",
+            base_dir.display(),
+            state.epochs.len(),
+            state.signatures.len(),
+            started.elapsed().as_secs_f64()
+        );
+
+        Ok(Self {
+            base_dir,
+            epoch_schedule,
+            state: Arc::new(RwLock::new(state)),
+        })
+    }
+
+    // segment I/O runs on the blocking pool so that it does not stall the async runtime
+    async fn with_state<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&LocalState) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let state = self.state.clone();
+        tokio::task::spawn_blocking(move || f(&state.read().unwrap()))
+            .await
+            .context("local blockstore read task")?
+    }
+
+    async fn with_state_mut<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut LocalState) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let state = self.state.clone();
+        tokio::task::spawn_blocking(move || f(&mut state.write().unwrap()))
+            .await
+            .context("local blockstore write task")?
+    }
+}
+
+#[async_trait]
+impl BlockStorageReader for LocalBlockStore {
+    async fn get_slot_range(&self) -> RangeInclusive<Slot> {
+        let state = self.state.read().unwrap();
+        let slot_min = state
+            .epochs
+            .values()
+            .filter_map(|epoch_segment| epoch_segment.slots.keys().next())
+            .min();
+        let slot_max = state
+            .epochs
+            .values()
+            .filter_map(|epoch_segment| epoch_segment.slots.keys().next_back())
+            .max();
+        match (slot_min, slot_max) {
+            (Some(slot_min), Some(slot_max)) => RangeInclusive::new(*slot_min, *slot_max),
+            // no blocks yet
+            _ => RangeInclusive::new(1, 0),
+        }
+    }
+
+    async fn is_block_in_range(&self, slot: Slot) -> bool {
+        let epoch: EpochRef = self.epoch_schedule.get_epoch_at_slot(slot).into();
+        let state = self.state.read().unwrap();
+        let Some(epoch_segment) = state.epochs.get(&epoch) else {
+            return false;
+        };
+        match (
+            epoch_segment.slots.keys().next(),
+            epoch_segment.slots.keys().next_back(),
+        ) {
+            (Some(first_slot), Some(last_slot)) => (*first_slot..=*last_slot).contains(&slot),
+            _ => false,
+        }
+    }
+
+    async fn query_block(&self, slot: Slot) -> Result<ProducedBlock> {
+        self.with_state(move |state| {
+            let Some((epoch_segment, location)) = state.get_location(slot) else {
+                return Err(BlockNotFound { slot }.into());
+            };
+            let block = epoch_segment.read_block(&location)?;
+            Ok(block.into_produced_block(location.commitment))
+        })
+        .await
+    }
+
+    async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>> {
+        if slot_range.is_empty() {
+            return Ok(vec![]);
+        }
+        let state = self.state.read().unwrap();
+        // epochs are ordered, so are the slots
+        Ok(state
+            .epochs
+            .values()
+            .flat_map(|epoch_segment| epoch_segment.slots.range(slot_range.clone()))
+            .map(|(slot, _)| *slot)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>> {
+        let signature = *signature;
+        self.with_state(move |state| {
+            let Some((slot, position)) = state.signatures.get(&signature).copied() else {
+                return Ok(None);
+            };
+            let Some((epoch_segment, location)) = state.get_location(slot) else {
+                return Ok(None);
+            };
+            let mut block = epoch_segment.read_block(&location)?;
+            if position as usize >= block.transactions.len() {
+                bail!("Transaction {} missing in block {}", signature, slot);
+            }
+            let transaction = block.transactions.swap_remove(position as usize);
+
+            Ok(Some(ConfirmedTransactionInfo {
+                slot,
+                block_time: block.block_time,
+                commitment_config: location.commitment.into_commiment_config(),
+                transaction: transaction.into_transaction_info(),
+            }))
+        })
+        .await
+    }
+
+    async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.with_state(move |state| {
+            let Some(account_transactions) = state.account_transactions.get(&account) else {
+                return Ok(vec![]);
+            };
+
+            let before = match before {
+                Some(signature) => match state.signatures.get(&signature) {
+                    Some(key) => Some(*key),
+                    None => {
+                        debug!(
+                            "Signature {} (before) not found - return empty result",
+                            signature
+                        );
+                        return Ok(vec![]);
+                    }
+                },
+                None => None,
+            };
+            let until = until.and_then(|signature| state.signatures.get(&signature).copied());
+
+            let newest_first: Box<dyn Iterator<Item = &(Slot, u32)>> = match before {
+                Some(before) => Box::new(account_transactions.range(..before).rev()),
+                None => Box::new(account_transactions.iter().rev()),
+            };
+
+            let mut result = Vec::with_capacity(limit.min(account_transactions.len()));
+            let mut cached_block: Option<LocalBlock> = None;
+            for (slot, position) in newest_first {
+                if result.len() >= limit {
+                    break;
+                }
+                if until
+                    .map(|until| (*slot, *position) <= until)
+                    .unwrap_or(false)
+                {
+                    break;
+                }
+                if max_slot.map(|max_slot| *slot > max_slot).unwrap_or(false) {
+                    continue;
+                }
+
+                if cached_block.as_ref().map(|block| block.slot) != Some(*slot) {
+                    let Some((epoch_segment, location)) = state.get_location(*slot) else {
+                        continue;
+                    };
+                    cached_block = Some(epoch_segment.read_block(&location)?);
+                }
+                let block = cached_block.as_ref().expect("block was loaded");
+                let Some(tx) = block.transactions.get(*position as usize) else {
+                    continue;
+                };
+
+                result.push(RpcConfirmedTransactionStatusWithSignature {
+                    signature: tx.signature.to_string(),
+                    slot: *slot,
+                    err: tx.err.clone(),
+                    memo: None,
+                    block_time: Some(block.block_time as i64),
+                    confirmation_status: None,
+                });
+            }
+
+            Ok(result)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl BlockStorageWriter for LocalBlockStore {
+    async fn prepare_epoch_schema(&self, slot: Slot) -> Result<bool> {
+        let current_epoch: EpochRef = self.epoch_schedule.get_epoch_at_slot(slot).into();
+        let base_dir = self.base_dir.clone();
+        self.with_state_mut(move |state| {
+            let created_current = state.open_epoch_if_necessary(&base_dir, current_epoch)?;
+            let created_next =
+                state.open_epoch_if_necessary(&base_dir, current_epoch.get_next_epoch())?;
+            Ok(created_current || created_next)
+        })
+        .await
+    }
+
+    async fn save_block(&self, block: &ProducedBlock) -> Result<()> {
+        let commitment = Commitment::from(block.commitment_config);
+        if commitment == Commitment::Processed {
+            bail!("Block {} must be confirmed to be saved", block.slot);
+        }
+
+        let epoch: EpochRef = self.epoch_schedule.get_epoch_at_slot(block.slot).into();
+        let local_block = LocalBlock::new(block);
+        let data = bincode::serialize(&local_block)?;
+
+        let slot = block.slot;
+        let base_dir = self.base_dir.clone();
+        let already_saved = self
+            .with_state_mut(move |state| {
+                if state.get_location(slot).is_some() {
+                    return Ok(true);
+                }
+                state.open_epoch_if_necessary(&base_dir, epoch)?;
+                state
+                    .epochs
+                    .get_mut(&epoch)
+                    .expect("epoch was opened")
+                    .append_block(slot, &data, commitment)?;
+                state.index_transactions(&local_block);
+                Ok(false)
+            })
+            .await?;
+
+        if already_saved {
+            debug!("Block {} already exists - skip update", block.slot);
+            return self.progress_block_commitment_level(block).await;
+        }
+        Ok(())
+    }
+
+    async fn progress_block_commitment_level(&self, block: &ProducedBlock) -> Result<()> {
+        let commitment = Commitment::from(block.commitment_config);
+        let epoch: EpochRef = self.epoch_schedule.get_epoch_at_slot(block.slot).into();
+
+        let slot = block.slot;
+        self.with_state_mut(move |state| {
+            let Some(epoch_segment) = state.epochs.get_mut(&epoch) else {
+                return Ok(());
+            };
+            let Some(location) = epoch_segment.slots.get(&slot).copied() else {
+                return Ok(());
+            };
+            if commitment > location.commitment {
+                epoch_segment.write_index_entry(
+                    slot,
+                    SlotLocation {
+                        commitment,
+                        ..location
+                    },
+                )?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_lite_rpc_core::test_utils::{
+        create_test_block_inner, create_test_rewards, create_test_tx_with_accounts,
+    };
+    use solana_sdk::commitment_config::CommitmentConfig;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("local-blockstore-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // failed transaction with compute units so that every field is stored
+    fn create_test_tx(writable: Pubkey, readable: Pubkey) -> TransactionInfo {
+        TransactionInfo {
+            err: Some(TransactionError::AccountInUse),
+            cu_requested: Some(40000),
+            prioritization_fees: Some(5000),
+            cu_consumed: Some(32000),
+            ..create_test_tx_with_accounts(vec![writable], vec![readable])
+        }
+    }
+
+    fn create_test_block(
+        slot: Slot,
+        commitment_config: CommitmentConfig,
+        transactions: Vec<TransactionInfo>,
+    ) -> ProducedBlock {
+        let inner = ProducedBlockInner {
+            leader_id: Some(Pubkey::new_unique().to_string()),
+            rewards: Some(create_test_rewards()),
+            ..create_test_block_inner(slot, slot - 1, transactions)
+        };
+        ProducedBlock::new(inner, commitment_config)
+    }
+
+    #[tokio::test]
+    async fn test_save_and_query_block() {
+        let dir = test_dir("save-and-query");
+        let store = LocalBlockStore::open(&dir, EpochCache::new_for_tests()).unwrap();
+        assert!(store.get_slot_range().await.is_empty());
+
+        let account = Pubkey::new_unique();
+        let block = create_test_block(
+            1200,
+            CommitmentConfig::confirmed(),
+            vec![create_test_tx(account, Pubkey::new_unique())],
+        );
+        assert!(store.prepare_epoch_schema(1200).await.unwrap());
+        assert!(!store.prepare_epoch_schema(1201).await.unwrap());
+        store.save_block(&block).await.unwrap();
+
+        let stored = store.query_block(1200).await.unwrap();
+        assert_eq!(stored.blockhash, block.blockhash);
+        assert_eq!(stored.previous_blockhash, block.previous_blockhash);
+        assert_eq!(stored.parent_slot, block.parent_slot);
+        assert_eq!(stored.block_height, block.block_height);
+        assert_eq!(stored.block_time, block.block_time);
+        assert_eq!(stored.leader_id, block.leader_id);
+        assert_eq!(stored.rewards, block.rewards);
+        assert_eq!(stored.commitment_config, CommitmentConfig::confirmed());
+        assert_eq!(stored.transactions.len(), 1);
+        assert_eq!(
+            stored.transactions[0].signature,
+            block.transactions[0].signature
+        );
+        assert_eq!(stored.transactions[0].err, block.transactions[0].err);
+        assert_eq!(
+            stored.transactions[0].message,
+            block.transactions[0].message
+        );
+
+        let err = store.query_block(1201).await.unwrap_err();
+        assert!(BlockNotFound::is_block_not_found(&err));
+        assert!(store.is_block_in_range(1200).await);
+        assert!(!store.is_block_in_range(1201).await);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reopen_and_commitment_progression() {
+        let dir = test_dir("reopen");
+        let signature;
+        {
+            let store = LocalBlockStore::open(&dir, EpochCache::new_for_tests()).unwrap();
+            for slot in [1200, 1201, 2100] {
+                let tx = create_test_tx(Pubkey::new_unique(), Pubkey::new_unique());
+                store
+                    .save_block(&create_test_block(
+                        slot,
+                        CommitmentConfig::confirmed(),
+                        vec![tx],
+                    ))
+                    .await
+                    .unwrap();
+            }
+            let block = store.query_block(1201).await.unwrap();
+            signature = block.transactions[0].signature;
+            store.save_block(&block.to_finalized_block()).await.unwrap();
+            // no downgrade
+            store
+                .progress_block_commitment_level(&block.to_confirmed_block())
+                .await
+                .unwrap();
+        }
+
+        let store = LocalBlockStore::open(&dir, EpochCache::new_for_tests()).unwrap();
+        assert_eq!(store.get_slot_range().await, 1200..=2100);
+        assert_eq!(
+            store.query_slots(1200..=2200, None).await.unwrap(),
+            vec![1200, 1201, 2100]
+        );
+        assert_eq!(
+            store.query_slots(1201..=2200, Some(1)).await.unwrap(),
+            vec![1201]
+        );
+        assert_eq!(
+            store.query_block(1201).await.unwrap().commitment_config,
+            CommitmentConfig::finalized()
+        );
+        assert_eq!(
+            store.query_block(1200).await.unwrap().commitment_config,
+            CommitmentConfig::confirmed()
+        );
+
+        let tx = store.query_transaction(&signature).await.unwrap().unwrap();
+        assert_eq!(tx.slot, 1201);
+        assert_eq!(tx.block_time, 1_700_000_000 + 1201);
+        assert_eq!(tx.commitment_config, CommitmentConfig::finalized());
+        assert!(store
+            .query_transaction(&Signature::new_unique())
+            .await
+            .unwrap()
+            .is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_signatures_for_address() {
+        let dir = test_dir("signatures");
+        let store = LocalBlockStore::open(&dir, EpochCache::new_for_tests()).unwrap();
+        let account = Pubkey::new_unique();
+
+        let mut signatures = vec![];
+        for slot in [1200, 1201, 1202] {
+            let txs = vec![
+                create_test_tx(account, Pubkey::new_unique()),
+                create_test_tx(Pubkey::new_unique(), account),
+            ];
+            signatures.extend(txs.iter().map(|tx| tx.signature.to_string()));
+            store
+                .save_block(&create_test_block(slot, CommitmentConfig::confirmed(), txs))
+                .await
+                .unwrap();
+        }
+        // newest first
+        signatures.reverse();
+
+        let all = store
+            .query_signatures_for_address(account, None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            all.iter().map(|s| s.signature.clone()).collect::<Vec<_>>(),
+            signatures
+        );
+        assert_eq!(all[0].slot, 1202);
+        assert_eq!(all[0].err, Some(TransactionError::AccountInUse));
+        assert_eq!(all[0].block_time, Some(1_700_000_000 + 1202));
+
+        let before: Signature = signatures[1].parse().unwrap();
+        let until: Signature = signatures[4].parse().unwrap();
+        let page = store
+            .query_signatures_for_address(account, Some(before), Some(until), None, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            page.iter().map(|s| s.signature.clone()).collect::<Vec<_>>(),
+            signatures[2..4].to_vec()
+        );
+
+        let limited = store
+            .query_signatures_for_address(account, None, None, Some(1201), 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            limited
+                .iter()
+                .map(|s| s.signature.clone())
+                .collect::<Vec<_>>(),
+            signatures[2..5].to_vec()
+        );
+
+        assert!(store
+            .query_signatures_for_address(account, Some(Signature::new_unique()), None, None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recover_from_torn_writes() {
+        let dir = test_dir("torn-writes");
+        {
+            let store = LocalBlockStore::open(&dir, EpochCache::new_for_tests()).unwrap();
+            store
+                .save_block(&create_test_block(
+                    1200,
+                    CommitmentConfig::confirmed(),
+                    vec![],
+                ))
+                .await
+                .unwrap();
+        }
+
+        // partial index entry and a block that was never indexed
+        let epoch_dir = dir.join("epoch_1");
+        let mut index = OpenOptions::new()
+            .append(true)
+            .open(epoch_dir.join(INDEX_FILE))
+            .unwrap();
+        index.write_all(&[1, 2, 3]).unwrap();
+        let mut segment = OpenOptions::new()
+            .append(true)
+            .open(epoch_dir.join(SEGMENT_FILE))
+            .unwrap();
+        segment.write_all(&[0xff; 64]).unwrap();
+
+        let store = LocalBlockStore::open(&dir, EpochCache::new_for_tests()).unwrap();
+        assert_eq!(store.query_slots(0..=5000, None).await.unwrap(), vec![1200]);
+        store
+            .save_block(&create_test_block(
+                1201,
+                CommitmentConfig::confirmed(),
+                vec![],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(store.query_block(1201).await.unwrap().slot, 1201);
+        assert_eq!(
+            std::fs::metadata(epoch_dir.join(INDEX_FILE)).unwrap().len() as usize,
+            2 * INDEX_ENTRY_SIZE
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
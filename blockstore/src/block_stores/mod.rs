@@ -1,3 +1,7 @@
+pub mod block_cache;
+pub mod block_storage_interface;
 pub mod faithful_history;
+pub mod inmemory_block_store;
+pub mod local_block_store;
 pub mod multiple_strategy_block_store;
 pub mod postgres;
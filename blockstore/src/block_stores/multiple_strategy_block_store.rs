@@ -1,9 +1,12 @@
+use crate::block_stores::block_storage_interface::{BlockNotFound, BlockStorageReader};
 use crate::block_stores::faithful_history::faithful_block_store::FaithfulBlockStore;
-use crate::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
 use log::{debug, trace};
-use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
+use solana_lite_rpc_core::structures::produced_block::{ConfirmedTransactionInfo, ProducedBlock};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use solana_sdk::slot_history::Slot;
 use std::ops::{Deref, RangeInclusive};
 use std::sync::Arc;
@@ -33,22 +36,20 @@ impl Deref for BlockStorageData {
 
 // you might need to add a read-cache instead
 pub struct MultipleStrategyBlockStorage {
-    block_storage_query: PostgresQueryBlockStore,
-    // note supported ATM
+    // postgres or local block storage
+    block_storage_query: Arc<dyn BlockStorageReader>,
+    // serves blocks older than the epochs in postgres
     faithful_block_storage: Option<FaithfulBlockStore>, // to fetch legacy blocks from faithful_history
-                                                        // last_confirmed_slot: Arc<AtomicU64>,
 }
 
 impl MultipleStrategyBlockStorage {
     pub fn new(
-        block_storage_query: PostgresQueryBlockStore,
-        _faithful_rpc_client: Option<Arc<RpcClient>>,
+        block_storage_query: impl BlockStorageReader + 'static,
+        faithful_rpc_client: Option<Arc<RpcClient>>,
     ) -> Self {
         Self {
-            block_storage_query,
-            // faithful_history not used ATM
-            faithful_block_storage: None,
-            // faithful_block_storage: faithful_rpc_client.map(|rpc| FaithfulBlockStore::new(rpc)),
+            block_storage_query: Arc::new(block_storage_query),
+            faithful_block_storage: faithful_rpc_client.map(FaithfulBlockStore::new),
         }
     }
 
@@ -61,11 +62,11 @@ impl MultipleStrategyBlockStorage {
         let mut lower = *persistent_storage_range.start();
 
         if let Some(faithful_block_storage) = &self.faithful_block_storage {
-            let faithful_storage_range = faithful_block_storage.get_slot_range();
-            trace!("Faithful storage range: {:?}", faithful_storage_range);
-            if lower.saturating_sub(*faithful_storage_range.end()) <= 1 {
+            // the archive is assumed to cover all slots up to the postgres range
+            if let Some(first_slot) = faithful_block_storage.get_first_available_slot().await {
+                trace!("Faithful storage starts at slot {}", first_slot);
                 // move the lower bound to the left
-                lower = lower.min(*faithful_storage_range.start());
+                lower = lower.min(first_slot);
             }
         }
 
@@ -78,7 +79,42 @@ impl MultipleStrategyBlockStorage {
         merged
     }
 
+    // slots of confirmed or finalized blocks in range from our blockstore
+    // note: faithful_history does not support range queries
+    pub async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>> {
+        self.block_storage_query
+            .query_slots(slot_range, limit)
+            .await
+    }
+
+    // lookup confirmed or finalized transaction from our blockstore
+    pub async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>> {
+        self.block_storage_query.query_transaction(signature).await
+    }
+
+    // signatures of confirmed or finalized transactions touching the account from our blockstore (newest first)
+    pub async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.block_storage_query
+            .query_signatures_for_address(account, before, until, max_slot, limit)
+            .await
+    }
+
     // lookup confirmed or finalized block from either our blockstore or faithful_history
+    // BlockNotFound if neither has the block
     // TODO find better method name
     pub async fn query_block(
         &self,
@@ -96,7 +132,7 @@ impl MultipleStrategyBlockStorage {
         // current strategy:
         // 1. check if requested slot is in min-max range served from Postgres
         // 2.1. if yes; fetch from Postgres
-        // 2.2. if not: try to fetch from faithful_history
+        // 2.2. if not: try to fetch from faithful_history (e.g. blocks older than the epochs in postgres)
 
         match self.block_storage_query.is_block_in_range(slot).await {
             true => {
@@ -124,31 +160,18 @@ impl MultipleStrategyBlockStorage {
         }
 
         if let Some(faithful_block_storage) = &self.faithful_block_storage {
-            match faithful_block_storage.get_block(slot).await {
-                Ok(block) => {
-                    debug!(
-                        "Lookup for block {} successful in faithful_history block-storage",
-                        slot
-                    );
-
-                    Ok(BlockStorageData {
-                        block,
-                        result_source: BlockSource::FaithfulArchive,
-                    })
-                }
-                Err(_) => {
-                    debug!(
-                        "Block {} not found in faithful_history storage - giving up",
-                        slot
-                    );
-                    bail!(format!("Block {} not found in faithful_history", slot));
-                }
-            }
-        } else {
-            bail!(format!(
-                "Block {} not found - faithful_history not available",
+            let block = faithful_block_storage.get_block(slot).await?;
+            debug!(
+                "Lookup for block {} successful in faithful_history block-storage",
                 slot
-            ));
+            );
+            Ok(BlockStorageData {
+                block,
+                result_source: BlockSource::FaithfulArchive,
+            })
+        } else {
+            debug!("Block {} not found - faithful_history not available", slot);
+            Err(BlockNotFound { slot }.into())
         }
     }
 }
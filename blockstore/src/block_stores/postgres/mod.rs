@@ -1,12 +1,15 @@
 pub mod postgres_block_store_query;
 pub mod postgres_block_store_writer;
 pub use postgres_config::PostgresSessionConfig;
+pub use postgres_schema_version::BinaryCompression;
 pub use postgres_session::PostgresSession;
 pub use postgres_session::PostgresWriteSession;
 
+mod postgres_account_transaction;
 mod postgres_block;
 mod postgres_config;
 mod postgres_epoch;
+mod postgres_schema_version;
 mod postgres_session;
 mod postgres_transaction;
 
@@ -0,0 +1,430 @@
+use anyhow::Context;
+use futures_util::pin_mut;
+use itertools::Itertools;
+use log::debug;
+use solana_lite_rpc_core::structures::epoch::EpochRef;
+use solana_lite_rpc_core::structures::produced_block::TransactionInfo;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use tokio::time::Instant;
+use tokio_postgres::binary_copy::BinaryCopyInWriter;
+use tokio_postgres::types::Type;
+use tokio_postgres::CopyInSink;
+
+use super::postgres_epoch::*;
+use super::postgres_schema_version::BinaryCompression;
+use super::postgres_session::*;
+
+/// one row per account touched by a transaction; used to lookup signatures by address
+#[derive(Debug)]
+pub struct PostgresAccountTransaction {
+    pub signature: String,
+    pub account_key: String,
+    pub slot: i64,
+    pub is_writable: bool,
+}
+
+/// position of a transaction in the account index of one epoch schema; used for pagination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountTransactionCursor {
+    pub epoch: EpochRef,
+    pub slot: Slot,
+    pub transaction_id: i64,
+}
+
+impl PostgresAccountTransaction {
+    pub fn from_transaction_info(value: &TransactionInfo, slot: Slot) -> Vec<Self> {
+        let signature = value.signature.to_string();
+        let writable = value.writable_accounts.iter().map(|pk| (pk, true));
+        let readable = value.readable_accounts.iter().map(|pk| (pk, false));
+        writable
+            .chain(readable)
+            .map(|(account_key, is_writable)| Self {
+                signature: signature.clone(),
+                account_key: account_key.to_string(),
+                slot: slot as i64,
+                is_writable,
+            })
+            .collect()
+    }
+
+    // the addresses loaded from lookup tables are not part of the message; only the static keys are indexed
+    pub fn from_message(signature: &str, message: &VersionedMessage, slot: Slot) -> Vec<Self> {
+        message
+            .static_account_keys()
+            .iter()
+            .enumerate()
+            .map(|(index, account_key)| Self {
+                signature: signature.to_string(),
+                account_key: account_key.to_string(),
+                slot: slot as i64,
+                is_writable: message.is_maybe_writable(index),
+            })
+            .collect()
+    }
+
+    // indexes the transactions of the epoch from their stored messages; existing index rows are kept
+    // reads the transactions in chunks of slots so that the table is not loaded at once
+    pub async fn backfill_from_messages(
+        postgres_session: &PostgresSession,
+        epoch: EpochRef,
+        compression: BinaryCompression,
+    ) -> anyhow::Result<usize> {
+        const BACKFILL_CHUNK_SLOTS: i64 = 100;
+        let schema = PostgresEpoch::build_schema_name(epoch);
+
+        let statement = format!(
+            r#"
+                SELECT DISTINCT slot FROM {schema}.transaction_blockdata
+                WHERE slot > $1
+                ORDER BY slot
+                LIMIT {BACKFILL_CHUNK_SLOTS}
+            "#,
+        );
+        let transactions_statement = format!(
+            r#"
+                SELECT tx_ids.signature, tx.slot, tx.message
+                FROM {schema}.transaction_blockdata tx
+                INNER JOIN {schema}.transaction_ids tx_ids ON tx_ids.transaction_id = tx.transaction_id
+                WHERE tx.slot = ANY($1)
+            "#,
+        );
+
+        let mut nb_transactions = 0;
+        let mut last_slot: i64 = -1;
+        loop {
+            let slots = postgres_session
+                .query_list(&statement, &[&last_slot])
+                .await?
+                .iter()
+                .map(|row| row.get::<&str, i64>("slot"))
+                .collect_vec();
+            let Some(chunk_last_slot) = slots.last().copied() else {
+                break;
+            };
+
+            let rows = postgres_session
+                .query_list(&transactions_statement, &[&slots])
+                .await?;
+            let mut account_transactions = vec![];
+            for row in &rows {
+                let signature: String = row.get("signature");
+                let slot: i64 = row.get("slot");
+                let message: Vec<u8> = row.get("message");
+                let message: VersionedMessage =
+                    bincode::deserialize(&compression.decompress(&message)?)
+                        .with_context(|| format!("deserialize message of {}", signature))?;
+                account_transactions.extend(Self::from_message(&signature, &message, slot as Slot));
+            }
+            Self::save_account_transactions_from_block(
+                postgres_session.clone(),
+                epoch,
+                &account_transactions,
+            )
+            .await?;
+
+            nb_transactions += rows.len();
+            last_slot = chunk_last_slot;
+            debug!(
+                "Backfilled account index of {} transactions up to slot {} in schema {}",
+                nb_transactions, last_slot, schema
+            );
+        }
+
+        Ok(nb_transactions)
+    }
+
+    pub fn build_create_table_statement(epoch: EpochRef) -> String {
+        let schema = PostgresEpoch::build_schema_name(epoch);
+        format!(
+            r#"
+                -- lookup table; maps account keys to generated int8 account ids
+                -- no updates or deletes, only INSERTs
+                CREATE TABLE IF NOT EXISTS {schema}.account_ids(
+                    account_id bigserial PRIMARY KEY WITH (FILLFACTOR=90),
+                    account_key text STORAGE PLAIN NOT NULL,
+                    UNIQUE(account_key)
+                ) WITH (FILLFACTOR=100);
+
+                -- index account -> transaction; transaction_id must exist in the transaction_ids table
+                CREATE TABLE IF NOT EXISTS {schema}.account_transactions(
+                    account_id bigint NOT NULL,
+                    transaction_id bigint NOT NULL,
+                    slot bigint NOT NULL,
+                    is_writable bool NOT NULL,
+                    -- one row per block like transaction_blockdata
+                    CONSTRAINT pk_account_transactions PRIMARY KEY (account_id, transaction_id, slot) WITH (FILLFACTOR=90)
+                ) WITH (FILLFACTOR=90);
+                CREATE INDEX IF NOT EXISTS idx_account_transactions_slot ON {schema}.account_transactions USING btree (account_id, slot DESC, transaction_id DESC) WITH (FILLFACTOR=90);
+            "#,
+            schema = schema
+        )
+    }
+
+    // see PostgresTransaction::build_migrate_primary_key_statement
+    pub fn build_migrate_primary_key_statement(epoch: EpochRef) -> String {
+        let schema = PostgresEpoch::build_schema_name(epoch);
+        format!(
+            r#"
+                DO $$
+                BEGIN
+                    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conrelid = '{schema}.account_transactions'::regclass AND conname = 'pk_account_transactions') THEN
+                        ALTER TABLE {schema}.account_transactions DROP CONSTRAINT IF EXISTS account_transactions_pkey;
+                        ALTER TABLE {schema}.account_transactions ADD CONSTRAINT pk_account_transactions PRIMARY KEY (account_id, transaction_id, slot) WITH (FILLFACTOR=90);
+                    END IF;
+                END $$;
+            "#,
+            schema = schema
+        )
+    }
+
+    // requires that the transactions were saved before (see PostgresTransaction::save_transactions_from_block)
+    pub async fn save_account_transactions_from_block(
+        postgres_session: PostgresSession,
+        epoch: EpochRef,
+        account_transactions: &[Self],
+    ) -> anyhow::Result<()> {
+        let schema = PostgresEpoch::build_schema_name(epoch);
+
+        let statement = r#"
+            CREATE TEMP TABLE IF NOT EXISTS account_transaction_raw(
+                signature text,
+                account_key text,
+                slot bigint,
+                is_writable bool
+            );
+            TRUNCATE account_transaction_raw;
+        "#;
+        postgres_session.execute_multiple(statement).await?;
+
+        let statement = r#"
+            COPY account_transaction_raw(
+                signature,
+                account_key,
+                slot,
+                is_writable
+            ) FROM STDIN BINARY
+        "#;
+        let started_at = Instant::now();
+        let sink: CopyInSink<bytes::Bytes> = postgres_session.copy_in(statement).await?;
+        let writer =
+            BinaryCopyInWriter::new(sink, &[Type::TEXT, Type::TEXT, Type::INT8, Type::BOOL]);
+        pin_mut!(writer);
+
+        for row in account_transactions {
+            let PostgresAccountTransaction {
+                signature,
+                account_key,
+                slot,
+                is_writable,
+            } = row;
+
+            writer
+                .as_mut()
+                .write(&[&signature, &account_key, &slot, &is_writable])
+                .await?;
+        }
+
+        let num_rows = writer.finish().await?;
+        debug!(
+            "inserted {} raw account transaction rows into temp table in {}ms",
+            num_rows,
+            started_at.elapsed().as_millis()
+        );
+
+        let statement = format!(
+            r#"
+            INSERT INTO {schema}.account_ids(account_key)
+            SELECT DISTINCT account_key FROM account_transaction_raw
+            ORDER BY account_key
+            ON CONFLICT DO NOTHING
+            "#,
+        );
+        let started_at = Instant::now();
+        let num_rows = postgres_session.execute(statement.as_str(), &[]).await?;
+        debug!(
+            "inserted {} account keys into account_ids table in {}ms",
+            num_rows,
+            started_at.elapsed().as_millis()
+        );
+
+        let statement = format!(
+            r#"
+                INSERT INTO {schema}.account_transactions(account_id, transaction_id, slot, is_writable)
+                SELECT
+                    acc_ids.account_id,
+                    tx_ids.transaction_id,
+                    raw.slot,
+                    raw.is_writable
+                FROM account_transaction_raw raw
+                INNER JOIN {schema}.account_ids acc_ids ON acc_ids.account_key = raw.account_key
+                INNER JOIN {schema}.transaction_ids tx_ids ON tx_ids.signature = raw.signature
+                ON CONFLICT (account_id, transaction_id, slot) DO NOTHING
+        "#,
+            schema = schema,
+        );
+        let started_at = Instant::now();
+        let num_rows = postgres_session.execute(statement.as_str(), &[]).await?;
+        debug!(
+            "inserted {} rows into account transactions table in {}ms",
+            num_rows,
+            started_at.elapsed().as_millis()
+        );
+
+        Ok(())
+    }
+
+    pub fn build_query_cursor_statement(epoch: EpochRef, signature: &Signature) -> String {
+        format!(
+            r#"
+                SELECT
+                    tx_ids.transaction_id,
+                    tx.slot
+                FROM {schema}.transaction_ids tx_ids
+                INNER JOIN {schema}.transaction_blockdata tx ON tx.transaction_id = tx_ids.transaction_id
+                WHERE tx_ids.signature = '{signature}'
+                -- on more than one fork until finalized
+                ORDER BY tx.slot DESC
+                LIMIT 1
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+            signature = signature,
+        )
+    }
+
+    // newest first; before and until are exclusive
+    pub fn build_query_signatures_statement(
+        epoch: EpochRef,
+        account: &Pubkey,
+        before: Option<&AccountTransactionCursor>,
+        until: Option<&AccountTransactionCursor>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> String {
+        let before_condition = before
+            .map(|cursor| {
+                format!(
+                    "AND (acc_tx.slot, acc_tx.transaction_id) < ({}, {})",
+                    cursor.slot, cursor.transaction_id
+                )
+            })
+            .unwrap_or_default();
+        let until_condition = until
+            .map(|cursor| {
+                format!(
+                    "AND (acc_tx.slot, acc_tx.transaction_id) > ({}, {})",
+                    cursor.slot, cursor.transaction_id
+                )
+            })
+            .unwrap_or_default();
+        let max_slot_condition = max_slot
+            .map(|max_slot| format!("AND acc_tx.slot <= {}", max_slot))
+            .unwrap_or_default();
+
+        format!(
+            r#"
+                SELECT
+                    tx_ids.signature,
+                    acc_tx.slot,
+                    tx.err,
+                    blocks.block_time
+                FROM {schema}.account_transactions acc_tx
+                INNER JOIN {schema}.transaction_ids tx_ids ON tx_ids.transaction_id = acc_tx.transaction_id
+                INNER JOIN {schema}.transaction_blockdata tx ON tx.transaction_id = acc_tx.transaction_id AND tx.slot = acc_tx.slot
+                LEFT JOIN {schema}.blocks blocks ON blocks.slot = acc_tx.slot
+                WHERE acc_tx.account_id = (SELECT account_id FROM {schema}.account_ids WHERE account_key = '{account}')
+                {before_condition}
+                {until_condition}
+                {max_slot_condition}
+                ORDER BY acc_tx.slot DESC, acc_tx.transaction_id DESC
+                LIMIT {limit}
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+            account = account,
+            before_condition = before_condition,
+            until_condition = until_condition,
+            max_slot_condition = max_slot_condition,
+            limit = limit,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_lite_rpc_core::test_utils::create_test_tx_with_accounts;
+    use solana_sdk::message::{v0, MessageHeader};
+
+    #[test]
+    fn map_transaction_info_to_account_transactions() {
+        let writable = Pubkey::new_unique();
+        let readable = Pubkey::new_unique();
+        let tx_info = create_test_tx_with_accounts(vec![writable], vec![readable]);
+
+        let rows = PostgresAccountTransaction::from_transaction_info(&tx_info, 4242);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].account_key, writable.to_string());
+        assert!(rows[0].is_writable);
+        assert_eq!(rows[1].account_key, readable.to_string());
+        assert!(!rows[1].is_writable);
+        assert!(rows.iter().all(|row| row.slot == 4242));
+    }
+
+    #[test]
+    fn map_message_to_account_transactions() {
+        let payer = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer, program],
+            ..v0::Message::default()
+        });
+        let signature = Signature::new_unique().to_string();
+
+        let rows = PostgresAccountTransaction::from_message(&signature, &message, 4242);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].account_key, payer.to_string());
+        assert!(rows[0].is_writable);
+        assert_eq!(rows[1].account_key, program.to_string());
+        assert!(!rows[1].is_writable);
+        assert!(rows
+            .iter()
+            .all(|row| row.signature == signature && row.slot == 4242));
+    }
+
+    #[test]
+    fn query_signatures_statement_with_cursors() {
+        let epoch = EpochRef::new(644);
+        let before = AccountTransactionCursor {
+            epoch,
+            slot: 278_300_000,
+            transaction_id: 77,
+        };
+        let until = AccountTransactionCursor {
+            epoch,
+            slot: 278_200_000,
+            transaction_id: 11,
+        };
+        let statement = PostgresAccountTransaction::build_query_signatures_statement(
+            epoch,
+            &Pubkey::new_unique(),
+            Some(&before),
+            Some(&until),
+            None,
+            100,
+        );
+
+        assert!(statement.contains("(acc_tx.slot, acc_tx.transaction_id) < (278300000, 77)"));
+        assert!(statement.contains("(acc_tx.slot, acc_tx.transaction_id) > (278200000, 11)"));
+        assert!(!statement.contains("acc_tx.slot <="));
+        assert!(statement.contains("LIMIT 100"));
+    }
+}
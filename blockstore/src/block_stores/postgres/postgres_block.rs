@@ -1,6 +1,8 @@
 use super::postgres_epoch::PostgresEpoch;
 use super::postgres_session::PostgresSession;
-use log::{debug, warn};
+use anyhow::Context;
+use itertools::Itertools;
+use log::{debug, info, warn};
 use solana_lite_rpc_core::solana_utils::hash_from_str;
 use solana_lite_rpc_core::structures::epoch::EpochRef;
 use solana_lite_rpc_core::structures::produced_block::{ProducedBlockInner, TransactionInfo};
@@ -8,6 +10,7 @@ use solana_lite_rpc_core::{encoding::BASE64, structures::produced_block::Produce
 use solana_sdk::clock::Slot;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_transaction_status::Reward;
+use std::ops::RangeInclusive;
 use std::time::Instant;
 use tokio_postgres::types::ToSql;
 
@@ -19,29 +22,32 @@ pub struct PostgresBlock {
     pub parent_slot: i64,
     pub block_time: i64,
     pub previous_blockhash: String,
+    // json array of rewards
     pub rewards: Option<String>,
     pub leader_id: Option<String>,
 }
 
-impl From<&ProducedBlock> for PostgresBlock {
-    fn from(value: &ProducedBlock) -> Self {
+impl TryFrom<&ProducedBlock> for PostgresBlock {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &ProducedBlock) -> Result<Self, Self::Error> {
         let rewards = value
             .rewards
             .as_ref()
-            .map(|x| BASE64.serialize::<Vec<Reward>>(x).ok())
-            .unwrap_or(None);
+            .map(serde_json::to_string::<Vec<Reward>>)
+            .transpose()
+            .context("serialize rewards")?;
 
-        Self {
+        Ok(Self {
             blockhash: value.blockhash.to_string(),
             block_height: value.block_height as i64,
             slot: value.slot as i64,
             parent_slot: value.parent_slot as i64,
             block_time: value.block_time as i64,
             previous_blockhash: value.previous_blockhash.to_string(),
-            // TODO add leader_id, etc.
             rewards,
             leader_id: value.leader_id.clone(),
-        }
+        })
     }
 }
 
@@ -51,16 +57,24 @@ impl PostgresBlock {
         transaction_infos: Vec<TransactionInfo>,
         commitment_config: CommitmentConfig,
     ) -> ProducedBlock {
-        let rewards_vec: Option<Vec<Reward>> = self
-            .rewards
-            .as_ref()
-            .map(|x| BASE64.deserialize::<Vec<Reward>>(x).ok())
-            .unwrap_or(None);
+        // rows which were not migrated may still hold base64 encoded rewards
+        let rewards_vec: Option<Vec<Reward>> =
+            self.rewards
+                .as_ref()
+                .and_then(|x| match serde_json::from_str::<Vec<Reward>>(x) {
+                    Ok(rewards) => Some(rewards),
+                    Err(err) => {
+                        warn!(
+                            "Ignoring undecodable rewards of block {}: {}",
+                            self.slot, err
+                        );
+                        None
+                    }
+                });
 
         let inner = ProducedBlockInner {
-            // TODO implement
             transactions: transaction_infos,
-            leader_id: None,
+            leader_id: self.leader_id.clone(),
             blockhash: hash_from_str(&self.blockhash).expect("valid blockhash"),
             block_height: self.block_height as u64,
             slot: self.slot as Slot,
@@ -86,7 +100,7 @@ impl PostgresBlock {
                 parent_slot BIGINT NOT NULL,
                 block_time BIGINT NOT NULL,
                 previous_blockhash TEXT NOT NULL,
-                rewards TEXT,
+                rewards JSONB,
                 CONSTRAINT pk_block_slot PRIMARY KEY(slot)
             ) WITH (FILLFACTOR=90);
             CLUSTER {schema}.blocks USING pk_block_slot;
@@ -99,7 +113,7 @@ impl PostgresBlock {
         format!(
             r#"
                 SELECT
-                    slot, blockhash, block_height, parent_slot, block_time, previous_blockhash, rewards, leader_id,
+                    slot, blockhash, block_height, parent_slot, block_time, previous_blockhash, rewards::text AS rewards, leader_id,
                     {epoch}::bigint as _epoch, '{schema}'::text as _epoch_schema FROM {schema}.blocks
                 WHERE slot = {slot}
             "#,
@@ -109,91 +123,247 @@ impl PostgresBlock {
         )
     }
 
-    // true is actually inserted; false if operation was noop
-    pub async fn save(
-        &self,
+    // slots of all blocks in range, spanning the given epoch schemas; ordered by slot
+    pub fn build_query_slots_statement(
+        epochs: &[EpochRef],
+        slot_range: &RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> String {
+        let inner = epochs
+            .iter()
+            .map(|epoch| {
+                format!(
+                    "SELECT slot FROM {schema}.blocks WHERE slot BETWEEN {from} AND {to}",
+                    schema = PostgresEpoch::build_schema_name(*epoch),
+                    from = slot_range.start(),
+                    to = slot_range.end(),
+                )
+            })
+            .join(" UNION ALL ");
+        let limit = limit
+            .map(|limit| format!("LIMIT {}", limit))
+            .unwrap_or_default();
+
+        format!(
+            r#"
+                SELECT slot FROM (
+                    {inner}
+                ) AS all_slots
+                ORDER BY slot
+                {limit}
+            "#,
+            inner = inner,
+            limit = limit
+        )
+    }
+
+    pub fn build_query_blockhashes_statement(
+        epoch: EpochRef,
+        slot_range: &RangeInclusive<Slot>,
+    ) -> String {
+        format!(
+            r#"
+                SELECT slot, blockhash FROM {schema}.blocks
+                WHERE slot BETWEEN {from} AND {to}
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+            from = slot_range.start(),
+            to = slot_range.end(),
+        )
+    }
+
+    // removes the blocks together with their transactions and account index entries
+    pub fn build_delete_blocks_statement(epoch: EpochRef, slots: &[Slot]) -> String {
+        let slots = slots.iter().join(",");
+        format!(
+            r#"
+                DELETE FROM {schema}.account_transactions WHERE slot IN ({slots});
+                DELETE FROM {schema}.transaction_blockdata WHERE slot IN ({slots});
+                DELETE FROM {schema}.blocks WHERE slot IN ({slots});
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+            slots = slots,
+        )
+    }
+
+    // legacy epoch schemas stored the rewards as base64 bincode text; converts them to jsonb
+    // resumes an interrupted migration (column rewards_legacy still present)
+    // reads the blocks in chunks ordered by slot so that the table is not loaded at once
+    pub async fn migrate_legacy_rewards(
         postgres_session: &PostgresSession,
         epoch: EpochRef,
-    ) -> anyhow::Result<bool> {
-        const NB_ARGUMENTS: usize = 8;
+    ) -> anyhow::Result<()> {
+        const UPDATE_CHUNK_SIZE: usize = 1000;
+        let schema = PostgresEpoch::build_schema_name(epoch);
+
+        let statement = format!(
+            r#"
+                SELECT column_name, data_type FROM information_schema.columns
+                WHERE table_schema = '{schema}' AND table_name = 'blocks' AND column_name IN ('rewards', 'rewards_legacy')
+            "#,
+        );
+        let columns = postgres_session
+            .query_list(&statement, &[])
+            .await?
+            .iter()
+            .map(|row| {
+                (
+                    row.get::<&str, String>("column_name"),
+                    row.get::<&str, String>("data_type"),
+                )
+            })
+            .collect_vec();
+        let rewards_is_text = columns
+            .iter()
+            .any(|(name, data_type)| name == "rewards" && data_type == "text");
+        let has_legacy_column = columns.iter().any(|(name, _)| name == "rewards_legacy");
+
+        if rewards_is_text {
+            let statement = format!(
+                r#"
+                    ALTER TABLE {schema}.blocks RENAME COLUMN rewards TO rewards_legacy;
+                    ALTER TABLE {schema}.blocks ADD COLUMN rewards JSONB;
+                "#,
+            );
+            postgres_session.execute_multiple(&statement).await?;
+        } else if !has_legacy_column {
+            return Ok(());
+        }
+
+        let query_statement = format!(
+            r#"
+                SELECT slot, rewards_legacy FROM {schema}.blocks
+                WHERE rewards_legacy IS NOT NULL AND slot > $1
+                ORDER BY slot
+                LIMIT {UPDATE_CHUNK_SIZE}
+            "#
+        );
+        let update_statement = format!(
+            r#"
+                UPDATE {schema}.blocks SET rewards = data.rewards::jsonb
+                FROM (SELECT unnest($1::bigint[]) AS slot, unnest($2::text[]) AS rewards) AS data
+                WHERE blocks.slot = data.slot
+            "#,
+        );
+        let mut last_slot = -1_i64;
+        let mut nb_blocks = 0;
+        loop {
+            let chunk = postgres_session
+                .query_list(&query_statement, &[&last_slot])
+                .await?;
+            let Some(last_row) = chunk.last() else {
+                break;
+            };
+            last_slot = last_row.get("slot");
+            nb_blocks += chunk.len();
+
+            let mut slots: Vec<i64> = Vec::with_capacity(chunk.len());
+            let mut rewards: Vec<String> = Vec::with_capacity(chunk.len());
+            for row in &chunk {
+                let slot: i64 = row.get("slot");
+                let legacy_rewards: String = row.get("rewards_legacy");
+                match BASE64.deserialize::<Vec<Reward>>(&legacy_rewards) {
+                    Ok(decoded) => {
+                        slots.push(slot);
+                        rewards.push(serde_json::to_string(&decoded)?);
+                    }
+                    Err(err) => {
+                        warn!("Dropping undecodable rewards of block {}: {}", slot, err);
+                    }
+                }
+            }
+            postgres_session
+                .execute(&update_statement, &[&slots, &rewards])
+                .await?;
+        }
+
+        let statement = format!("ALTER TABLE {schema}.blocks DROP COLUMN rewards_legacy");
+        postgres_session.execute_multiple(&statement).await?;
+        info!(
+            "Migrated rewards of {} blocks in schema {} to jsonb",
+            nb_blocks, schema
+        );
+
+        Ok(())
+    }
 
+    // inserts the blocks in one statement; returns the slots actually inserted, existing blocks are not updated
+    pub async fn save_all(
+        postgres_session: &PostgresSession,
+        epoch: EpochRef,
+        blocks: &[PostgresBlock],
+    ) -> anyhow::Result<Vec<Slot>> {
         let started = Instant::now();
         let schema = PostgresEpoch::build_schema_name(epoch);
-        let values = PostgresSession::values_vec(NB_ARGUMENTS, &[]);
-
         let statement = format!(
             r#"
                 INSERT INTO {schema}.blocks (slot, blockhash, block_height, parent_slot, block_time, previous_blockhash, rewards, leader_id)
-                VALUES {}
+                SELECT * FROM unnest($1::bigint[], $2::text[], $3::bigint[], $4::bigint[], $5::bigint[], $6::text[], $7::text[]::jsonb[], $8::text[])
                 -- prevent updates
                 ON CONFLICT DO NOTHING
-                RETURNING (
-                    -- get previous max slot
-                    SELECT max(all_blocks.slot) as prev_max_slot
-                    FROM {schema}.blocks AS all_blocks
-                    WHERE all_blocks.slot!={schema}.blocks.slot
-                )
+                RETURNING slot
             "#,
-            values,
             schema = schema,
         );
 
-        let mut args: Vec<&(dyn ToSql + Sync)> = Vec::with_capacity(NB_ARGUMENTS);
-        args.push(&self.slot);
-        args.push(&self.blockhash);
-        args.push(&self.block_height);
-        args.push(&self.parent_slot);
-        args.push(&self.block_time);
-        args.push(&self.previous_blockhash);
-        args.push(&self.rewards);
-        args.push(&self.leader_id);
-
-        let returning = postgres_session
-            .execute_and_return(&statement, &args)
-            .await?;
-
-        // TODO: decide what to do if block already exists
-        match returning {
-            Some(row) => {
-                // check if monotonic
-                let prev_max_slot = row.get::<&str, Option<i64>>("prev_max_slot");
-                // None -> no previous rows
-                debug!("Inserted block {}", self.slot,);
-                if let Some(prev_max_slot) = prev_max_slot {
-                    if prev_max_slot > self.slot {
-                        // note: unclear if this is desired behavior!
-                        warn!(
-                            "Block {} was inserted behind tip of highest slot number {} (epoch {})",
-                            self.slot, prev_max_slot, epoch
-                        );
-                    }
-                }
-            }
-            None => {
-                // database detected conflict
-                warn!("Block {} already exists - not updated", self.slot);
-                return Ok(false);
-            }
+        let slots = blocks.iter().map(|block| block.slot).collect_vec();
+        let blockhashes = blocks.iter().map(|block| &block.blockhash).collect_vec();
+        let block_heights = blocks.iter().map(|block| block.block_height).collect_vec();
+        let parent_slots = blocks.iter().map(|block| block.parent_slot).collect_vec();
+        let block_times = blocks.iter().map(|block| block.block_time).collect_vec();
+        let previous_blockhashes = blocks
+            .iter()
+            .map(|block| &block.previous_blockhash)
+            .collect_vec();
+        let rewards = blocks.iter().map(|block| &block.rewards).collect_vec();
+        let leader_ids = blocks.iter().map(|block| &block.leader_id).collect_vec();
+        let args: [&(dyn ToSql + Sync); 8] = [
+            &slots,
+            &blockhashes,
+            &block_heights,
+            &parent_slots,
+            &block_times,
+            &previous_blockhashes,
+            &rewards,
+            &leader_ids,
+        ];
+
+        let inserted = postgres_session
+            .query_list(&statement, &args)
+            .await?
+            .iter()
+            .map(|row| row.get::<&str, i64>("slot") as Slot)
+            .sorted()
+            .collect_vec();
+        if inserted.len() < blocks.len() {
+            // database detected conflict
+            warn!(
+                "{} of {} blocks already exist - not updated",
+                blocks.len() - inserted.len(),
+                blocks.len()
+            );
         }
 
         debug!(
-            "Inserting block {} to schema {} postgres took {:.2}ms",
-            self.slot,
+            "Inserting {} blocks to schema {} postgres took {:.2}ms",
+            inserted.len(),
             schema,
             started.elapsed().as_secs_f64() * 1000.0
         );
 
-        Ok(true)
+        Ok(inserted)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::block_stores::postgres::postgres_transaction::PostgresTransaction;
     use solana_sdk::hash::Hash;
     use solana_sdk::message::{v0, MessageHeader, VersionedMessage};
     use solana_sdk::pubkey::Pubkey;
     use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+    use solana_transaction_status::RewardType;
 
     #[test]
     fn map_postgresblock_to_produced_block() {
@@ -217,6 +387,156 @@ mod tests {
         assert_eq!(produced_block.transactions.len(), 2);
     }
 
+    #[test]
+    fn round_trip_block_fields() {
+        let block = create_produced_block();
+        let restored = round_trip(&block);
+
+        assert_eq!(restored.slot, block.slot);
+        assert_eq!(restored.blockhash, block.blockhash);
+        assert_eq!(restored.block_height, block.block_height);
+        assert_eq!(restored.parent_slot, block.parent_slot);
+        assert_eq!(restored.block_time, block.block_time);
+        assert_eq!(restored.previous_blockhash, block.previous_blockhash);
+    }
+
+    #[test]
+    fn round_trip_leader_id() {
+        let block = create_produced_block();
+        assert_eq!(round_trip(&block).leader_id, block.leader_id);
+    }
+
+    #[test]
+    fn round_trip_rewards() {
+        let block = create_produced_block();
+        let postgres_block = PostgresBlock::try_from(&block).unwrap();
+        // stored as readable json
+        assert!(postgres_block
+            .rewards
+            .as_ref()
+            .unwrap()
+            .contains("\"postBalance\":18446744073709551615"));
+        assert_eq!(round_trip(&block).rewards, block.rewards);
+
+        let block = ProducedBlock::new(
+            ProducedBlockInner {
+                rewards: None,
+                ..create_produced_block_inner()
+            },
+            CommitmentConfig::confirmed(),
+        );
+        assert_eq!(round_trip(&block).rewards, None);
+    }
+
+    #[test]
+    fn undecodable_rewards_are_dropped() {
+        let block = PostgresBlock {
+            rewards: Some("AAECAw==".to_string()),
+            ..PostgresBlock::try_from(&create_produced_block()).unwrap()
+        };
+        let produced_block = block.to_produced_block(vec![], CommitmentConfig::confirmed());
+        assert_eq!(produced_block.rewards, None);
+    }
+
+    #[test]
+    fn round_trip_transaction_order() {
+        let block = create_produced_block();
+        let restored = round_trip(&block);
+
+        let signatures = |block: &ProducedBlock| {
+            block
+                .transactions
+                .iter()
+                .map(|tx| tx.signature)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(signatures(&restored), signatures(&block));
+    }
+
+    #[test]
+    fn query_slots_statement_spans_epochs() {
+        let statement = PostgresBlock::build_query_slots_statement(
+            &[EpochRef::new(644), EpochRef::new(645)],
+            &(278_200_000..=278_300_000),
+            Some(100),
+        );
+
+        assert!(statement.contains(
+            "SELECT slot FROM rpc2a_epoch_644.blocks WHERE slot BETWEEN 278200000 AND 278300000 UNION ALL SELECT slot FROM rpc2a_epoch_645.blocks"
+        ));
+        assert!(statement.contains("ORDER BY slot"));
+        assert!(statement.contains("LIMIT 100"));
+    }
+
+    #[test]
+    fn delete_blocks_statement_removes_transactions_first() {
+        let statement = PostgresBlock::build_delete_blocks_statement(
+            EpochRef::new(644),
+            &[278_200_001, 278_200_003],
+        );
+
+        let account_transactions = statement
+            .find("DELETE FROM rpc2a_epoch_644.account_transactions WHERE slot IN (278200001,278200003)")
+            .unwrap();
+        let transactions = statement
+            .find("DELETE FROM rpc2a_epoch_644.transaction_blockdata WHERE slot IN (278200001,278200003)")
+            .unwrap();
+        let blocks = statement
+            .find("DELETE FROM rpc2a_epoch_644.blocks WHERE slot IN (278200001,278200003)")
+            .unwrap();
+        // foreign key from transactions to blocks
+        assert!(account_transactions < transactions && transactions < blocks);
+    }
+
+    // same mapping as in save_block and query_block
+    fn round_trip(block: &ProducedBlock) -> ProducedBlock {
+        let transaction_infos = block
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(idx, tx)| PostgresTransaction::new(tx, block.slot, idx))
+            .sorted_by_key(|tx| tx.tx_index)
+            .map(|tx| tx.to_transaction_info().unwrap())
+            .collect();
+        PostgresBlock::try_from(block)
+            .unwrap()
+            .to_produced_block(transaction_infos, block.commitment_config)
+    }
+
+    fn create_produced_block_inner() -> ProducedBlockInner {
+        let leader = Pubkey::new_unique();
+        ProducedBlockInner {
+            transactions: vec![create_tx_info(), create_tx_info(), create_tx_info()],
+            leader_id: Some(leader.to_string()),
+            blockhash: Hash::new_unique(),
+            block_height: 4040404,
+            slot: 5050505,
+            parent_slot: 5050500,
+            block_time: 12121212,
+            previous_blockhash: Hash::new_unique(),
+            rewards: Some(vec![
+                Reward {
+                    pubkey: leader.to_string(),
+                    lamports: 5000,
+                    post_balance: u64::MAX,
+                    reward_type: Some(RewardType::Fee),
+                    commission: None,
+                },
+                Reward {
+                    pubkey: Pubkey::new_unique().to_string(),
+                    lamports: -42,
+                    post_balance: 0,
+                    reward_type: Some(RewardType::Voting),
+                    commission: Some(10),
+                },
+            ]),
+        }
+    }
+
+    fn create_produced_block() -> ProducedBlock {
+        ProducedBlock::new(create_produced_block_inner(), CommitmentConfig::confirmed())
+    }
+
     fn create_tx_info() -> TransactionInfo {
         TransactionInfo {
             signature: Signature::new_unique(),
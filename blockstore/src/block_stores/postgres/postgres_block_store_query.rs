@@ -2,18 +2,28 @@ use std::collections::HashMap;
 use std::ops::RangeInclusive;
 use std::time::Instant;
 
+use crate::block_stores::block_storage_interface::{BlockNotFound, BlockStorageReader};
 use crate::block_stores::postgres::LITERPC_QUERY_ROLE;
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
 use itertools::Itertools;
-use log::{debug, info, warn};
+use log::{debug, info};
 use solana_lite_rpc_core::structures::epoch::EpochRef;
+use solana_lite_rpc_core::structures::produced_block::ConfirmedTransactionInfo;
 use solana_lite_rpc_core::structures::{epoch::EpochCache, produced_block::ProducedBlock};
+use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use solana_sdk::slot_history::Slot;
+use solana_sdk::transaction::TransactionError;
+use tokio_postgres::error::SqlState;
 
+use super::postgres_account_transaction::*;
 use super::postgres_block::*;
 use super::postgres_config::*;
 use super::postgres_epoch::*;
+use super::postgres_schema_version::*;
 use super::postgres_session::*;
 use super::postgres_transaction::*;
 
@@ -21,6 +31,7 @@ use super::postgres_transaction::*;
 pub struct PostgresQueryBlockStore {
     session_cache: PostgresSessionCache,
     epoch_schedule: EpochCache,
+    schema_versions: SchemaVersionCache,
 }
 
 impl PostgresQueryBlockStore {
@@ -34,9 +45,26 @@ impl PostgresQueryBlockStore {
         Self {
             session_cache,
             epoch_schedule,
+            schema_versions: SchemaVersionCache::default(),
         }
     }
 
+    // storage format of the epoch schema; legacy schemas must be migrated by the writer first
+    async fn get_schema_version(&self, epoch: EpochRef) -> Result<PostgresSchemaVersion> {
+        let schema_version = self
+            .schema_versions
+            .get(&self.get_session().await, epoch)
+            .await?;
+        if schema_version.is_legacy() {
+            bail!(
+                "Epoch schema {} has not been migrated to schema version {} yet",
+                PostgresEpoch::build_schema_name(epoch),
+                CURRENT_SCHEMA_VERSION
+            );
+        }
+        Ok(schema_version)
+    }
+
     async fn get_session(&self) -> PostgresSession {
         self.session_cache
             .get_session()
@@ -51,7 +79,7 @@ impl PostgresQueryBlockStore {
 
         matching_range
             .map(|slot_range| slot_range.contains(&slot))
-            .is_some()
+            .unwrap_or(false)
     }
 
     pub async fn query_block(&self, slot: Slot) -> Result<ProducedBlock> {
@@ -59,49 +87,48 @@ impl PostgresQueryBlockStore {
         let epoch: EpochRef = self.epoch_schedule.get_epoch_at_slot(slot).into();
 
         let statement = PostgresBlock::build_query_statement(epoch, slot);
-        let block_row = self
-            .get_session()
-            .await
-            .query_opt(&statement, &[])
-            .await
-            .unwrap();
+        let block_row = match self.get_session().await.query_opt(&statement, &[]).await {
+            Ok(row) => row,
+            Err(err)
+                if err
+                    .code()
+                    .map(|sqlstate| sqlstate == &SqlState::UNDEFINED_TABLE)
+                    .unwrap_or(false) =>
+            {
+                // no schema for this epoch
+                None
+            }
+            Err(err) => {
+                return Err(err).context(format!("query block {} from postgres", slot));
+            }
+        };
 
-        if block_row.is_none() {
-            bail!("Block {} in epoch {} not found in postgres", slot, epoch);
-        }
+        let Some(row) = block_row else {
+            debug!("Block {} in epoch {} not found in postgres", slot, epoch);
+            return Err(BlockNotFound { slot }.into());
+        };
 
+        let compression = self.get_schema_version(epoch).await?.compression;
         let statement = PostgresTransaction::build_query_statement(epoch, slot);
         let transaction_rows = self
             .get_session()
             .await
             .query_list(&statement, &[])
             .await
-            .unwrap();
-
-        warn!(
-            "transaction_rows: {} - print first 10",
-            transaction_rows.len()
-        );
+            .context(format!(
+                "query transactions of block {} from postgres",
+                slot
+            ))?;
 
+        // ordered by position in block
         let tx_infos = transaction_rows
             .iter()
             .map(|tx_row| {
-                let postgres_transaction = PostgresTransaction {
-                    slot: slot as i64,
-                    signature: tx_row.get("signature"),
-                    err: tx_row.get("err"),
-                    cu_requested: tx_row.get("cu_requested"),
-                    prioritization_fees: tx_row.get("prioritization_fees"),
-                    cu_consumed: tx_row.get("cu_consumed"),
-                    recent_blockhash: tx_row.get("recent_blockhash"),
-                    message: tx_row.get("message"),
-                };
-
-                postgres_transaction.to_transaction_info()
+                PostgresTransaction::from_row(tx_row, compression)
+                    .and_then(|tx| tx.to_transaction_info())
             })
-            .collect_vec();
+            .collect::<Result<Vec<_>>>()?;
 
-        let row = block_row.unwrap();
         // meta data
         let _epoch: i64 = row.get("_epoch");
         let epoch_schema: String = row.get("_epoch_schema");
@@ -144,6 +171,202 @@ impl PostgresQueryBlockStore {
         Ok(produced_block)
     }
 
+    // slots of all blocks within the range (ascending), at most limit slots
+    pub async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>> {
+        let started_at = Instant::now();
+        let first_epoch = self
+            .epoch_schedule
+            .get_epoch_at_slot(*slot_range.start())
+            .epoch;
+        let last_epoch = self
+            .epoch_schedule
+            .get_epoch_at_slot(*slot_range.end())
+            .epoch;
+
+        // only query epoch schemas which exist
+        let existing_epochs = self.get_slot_range_by_epoch().await;
+        let epochs = (first_epoch..=last_epoch)
+            .map(EpochRef::new)
+            .filter(|epoch| existing_epochs.contains_key(epoch))
+            .collect_vec();
+
+        if epochs.is_empty() || limit == Some(0) {
+            return Ok(vec![]);
+        }
+
+        let statement = PostgresBlock::build_query_slots_statement(&epochs, &slot_range, limit);
+        let rows = self.get_session().await.query_list(&statement, &[]).await?;
+
+        let slots = rows
+            .iter()
+            .map(|row| row.get::<&str, i64>("slot") as Slot)
+            .collect_vec();
+
+        debug!(
+            "Querying {} slots in range {:?} from postgres epoch schemas {:?} took {:.2}ms",
+            slots.len(),
+            slot_range,
+            epochs,
+            started_at.elapsed().as_secs_f64() * 1000.0
+        );
+
+        Ok(slots)
+    }
+
+    // lookup the transaction in the epoch schemas (newest first)
+    pub async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>> {
+        let started_at = Instant::now();
+        let epochs = self
+            .get_slot_range_by_epoch()
+            .await
+            .into_keys()
+            .sorted()
+            .rev()
+            .collect_vec();
+
+        for epoch in epochs {
+            let statement =
+                PostgresTransaction::build_query_by_signature_statement(epoch, signature);
+            let Some(row) = self.get_session().await.query_opt(&statement, &[]).await? else {
+                continue;
+            };
+
+            let compression = self.get_schema_version(epoch).await?.compression;
+            let postgres_transaction = PostgresTransaction::from_row(&row, compression)?;
+            let block_time: i64 = row.get("block_time");
+
+            debug!(
+                "Querying transaction {} from postgres in epoch {} took {:.2}ms",
+                signature,
+                epoch,
+                started_at.elapsed().as_secs_f64() * 1000.0
+            );
+
+            return Ok(Some(ConfirmedTransactionInfo {
+                slot: postgres_transaction.slot as Slot,
+                block_time: block_time as u64,
+                // FIXME same as for blocks
+                commitment_config: CommitmentConfig::confirmed(),
+                transaction: postgres_transaction.to_transaction_info()?,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    // newest first, paginated across all epoch schemas; before and until are exclusive
+    // note: confirmation_status is not set
+    pub async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let started_at = Instant::now();
+        let epochs = self
+            .get_slot_range_by_epoch()
+            .await
+            .into_keys()
+            .sorted()
+            .rev()
+            .collect_vec();
+
+        let before = match before {
+            Some(signature) => match self.find_transaction_cursor(&epochs, &signature).await? {
+                Some(cursor) => Some(cursor),
+                None => {
+                    debug!(
+                        "Signature {} (before) not found - return empty result",
+                        signature
+                    );
+                    return Ok(vec![]);
+                }
+            },
+            None => None,
+        };
+        let until = match until {
+            Some(signature) => self.find_transaction_cursor(&epochs, &signature).await?,
+            None => None,
+        };
+
+        let mut result = Vec::with_capacity(limit);
+        for epoch in epochs {
+            if result.len() >= limit {
+                break;
+            }
+            if before.map(|cursor| epoch > cursor.epoch).unwrap_or(false) {
+                continue;
+            }
+            if until.map(|cursor| epoch < cursor.epoch).unwrap_or(false) {
+                break;
+            }
+
+            let statement = PostgresAccountTransaction::build_query_signatures_statement(
+                epoch,
+                &account,
+                before.as_ref().filter(|cursor| cursor.epoch == epoch),
+                until.as_ref().filter(|cursor| cursor.epoch == epoch),
+                max_slot,
+                limit - result.len(),
+            );
+            let rows = self.get_session().await.query_list(&statement, &[]).await?;
+            let compression = self.get_schema_version(epoch).await?.compression;
+
+            for row in rows {
+                let err: Option<Vec<u8>> = row.get("err");
+                let err = err.map(|err| compression.decompress(&err)).transpose()?;
+                let block_time: Option<i64> = row.get("block_time");
+                result.push(RpcConfirmedTransactionStatusWithSignature {
+                    signature: row.get("signature"),
+                    slot: row.get::<&str, i64>("slot") as Slot,
+                    err: err.and_then(|err| bincode::deserialize::<TransactionError>(&err).ok()),
+                    memo: None,
+                    block_time,
+                    confirmation_status: None,
+                });
+            }
+        }
+
+        debug!(
+            "Querying {} signatures for address {} from postgres took {:.2}ms",
+            result.len(),
+            account,
+            started_at.elapsed().as_secs_f64() * 1000.0
+        );
+
+        Ok(result)
+    }
+
+    // lookup the transaction in the epoch schemas (newest first)
+    async fn find_transaction_cursor(
+        &self,
+        epochs: &[EpochRef],
+        signature: &Signature,
+    ) -> Result<Option<AccountTransactionCursor>> {
+        for epoch in epochs {
+            let statement =
+                PostgresAccountTransaction::build_query_cursor_statement(*epoch, signature);
+            let row = self.get_session().await.query_opt(&statement, &[]).await?;
+            if let Some(row) = row {
+                return Ok(Some(AccountTransactionCursor {
+                    epoch: *epoch,
+                    slot: row.get::<&str, i64>("slot") as Slot,
+                    transaction_id: row.get("transaction_id"),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
     async fn check_query_role(session_cache: &PostgresSessionCache) {
         let role = LITERPC_QUERY_ROLE;
         let statement = format!("SELECT 1 FROM pg_roles WHERE rolname='{role}'");
@@ -173,19 +396,14 @@ impl PostgresQueryBlockStore {
         let rows_minmax: Vec<&RangeInclusive<Slot>> =
             map_epoch_to_slot_range.values().collect_vec();
 
-        let slot_min = rows_minmax
-            .iter()
-            .map(|range| range.start())
-            .min()
-            // TODO decide what todo
-            .expect("non-empty result - TODO");
-        let slot_max = rows_minmax
-            .iter()
-            .map(|range| range.end())
-            .max()
-            .expect("non-empty result - TODO");
+        let slot_min = rows_minmax.iter().map(|range| range.start()).min();
+        let slot_max = rows_minmax.iter().map(|range| range.end()).max();
 
-        RangeInclusive::new(*slot_min, *slot_max)
+        match (slot_min, slot_max) {
+            (Some(slot_min), Some(slot_max)) => RangeInclusive::new(*slot_min, *slot_max),
+            // no blocks yet
+            _ => RangeInclusive::new(1, 0),
+        }
     }
 
     pub async fn get_slot_range_by_epoch(&self) -> HashMap<EpochRef, RangeInclusive<Slot>> {
@@ -283,3 +501,47 @@ impl PostgresQueryBlockStore {
         final_range
     }
 }
+
+#[async_trait]
+impl BlockStorageReader for PostgresQueryBlockStore {
+    async fn get_slot_range(&self) -> RangeInclusive<Slot> {
+        PostgresQueryBlockStore::get_slot_range(self).await
+    }
+
+    async fn is_block_in_range(&self, slot: Slot) -> bool {
+        PostgresQueryBlockStore::is_block_in_range(self, slot).await
+    }
+
+    async fn query_block(&self, slot: Slot) -> Result<ProducedBlock> {
+        PostgresQueryBlockStore::query_block(self, slot).await
+    }
+
+    async fn query_slots(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Slot>> {
+        PostgresQueryBlockStore::query_slots(self, slot_range, limit).await
+    }
+
+    async fn query_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionInfo>> {
+        PostgresQueryBlockStore::query_transaction(self, signature).await
+    }
+
+    async fn query_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        max_slot: Option<Slot>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        PostgresQueryBlockStore::query_signatures_for_address(
+            self, account, before, until, max_slot, limit,
+        )
+        .await
+    }
+}
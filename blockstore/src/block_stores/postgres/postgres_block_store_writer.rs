@@ -1,21 +1,33 @@
 use std::time::{Duration, Instant};
 
+use crate::block_stores::block_storage_interface::BlockStorageWriter;
 use crate::block_stores::postgres::{LITERPC_QUERY_ROLE, LITERPC_ROLE};
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
 use itertools::Itertools;
-use log::{debug, info, trace, warn};
+use log::{debug, error, info, trace, warn};
+use prometheus::{opts, register_int_counter, IntCounter};
 use solana_lite_rpc_core::structures::epoch::EpochRef;
 use solana_lite_rpc_core::structures::{epoch::EpochCache, produced_block::ProducedBlock};
 use solana_sdk::commitment_config::CommitmentLevel;
 use solana_sdk::slot_history::Slot;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
 use tokio_postgres::error::SqlState;
 
+use super::postgres_account_transaction::*;
 use super::postgres_block::*;
 use super::postgres_config::*;
 use super::postgres_epoch::*;
+use super::postgres_schema_version::*;
 use super::postgres_session::*;
 use super::postgres_transaction::*;
 
+lazy_static::lazy_static! {
+    static ref BLOCKSTORE_ABANDONED_BLOCKS_REMOVED: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_abandoned_blocks_removed", "Number of blocks of abandoned forks removed from the blockstore")).unwrap();
+}
+
 const PARALLEL_WRITE_SESSIONS: usize = 4;
 const MIN_WRITE_CHUNK_SIZE: usize = 500;
 
@@ -25,10 +37,21 @@ pub struct PostgresBlockStore {
     // use this session only for the write path!
     write_sessions: Vec<PostgresWriteSession>,
     epoch_schedule: EpochCache,
+    // compression of new epoch schemas; existing schemas keep their own
+    compression: BinaryCompression,
+    schema_versions: SchemaVersionCache,
 }
 
 impl PostgresBlockStore {
     pub async fn new(epoch_schedule: EpochCache, pg_session_config: PostgresSessionConfig) -> Self {
+        Self::new_with_compression(epoch_schedule, pg_session_config, BinaryCompression::None).await
+    }
+
+    pub async fn new_with_compression(
+        epoch_schedule: EpochCache,
+        pg_session_config: PostgresSessionConfig,
+        compression: BinaryCompression,
+    ) -> Self {
         let session_cache = PostgresSessionCache::new(pg_session_config.clone())
             .await
             .unwrap();
@@ -51,6 +74,8 @@ impl PostgresBlockStore {
             session_cache,
             write_sessions,
             epoch_schedule,
+            compression,
+            schema_versions: SchemaVersionCache::default(),
         }
     }
 
@@ -98,6 +123,23 @@ impl PostgresBlockStore {
                     "Schema {} for epoch {} already exists - data will be appended",
                     schema_name, epoch
                 );
+                self.migrate_epoch_schema(epoch).await?;
+                // schemas created before the account index existed lack these tables
+                let statement = PostgresAccountTransaction::build_create_table_statement(epoch);
+                session
+                    .execute_multiple(&statement)
+                    .await
+                    .context("create account transactions table for existing epoch")?;
+                // schemas created before transactions were keyed by block
+                let statement = format!(
+                    "{}{}",
+                    PostgresTransaction::build_migrate_primary_key_statement(epoch),
+                    PostgresAccountTransaction::build_migrate_primary_key_statement(epoch)
+                );
+                session
+                    .execute_multiple(&statement)
+                    .await
+                    .context("migrate transaction primary keys for existing epoch")?;
                 return Ok(false);
             } else {
                 return Err(err).context("create schema for new epoch");
@@ -125,6 +167,13 @@ impl PostgresBlockStore {
             .await
             .context("create transaction table for new epoch")?;
 
+        // create account to transaction index table
+        let statement = PostgresAccountTransaction::build_create_table_statement(epoch);
+        session
+            .execute_multiple(&statement)
+            .await
+            .context("create account transactions table for new epoch")?;
+
         // add foreign key constraint between transactions and blocks
         let statement = PostgresTransaction::build_foreign_key_statement(epoch);
         session
@@ -132,10 +181,110 @@ impl PostgresBlockStore {
             .await
             .context("create foreign key constraint between transactions and blocks")?;
 
-        info!("Start new epoch in postgres schema {}", schema_name);
+        let schema_version = PostgresSchemaVersion::current(self.compression);
+        let statement = schema_version.build_create_table_statement(epoch);
+        session
+            .execute_multiple(&statement)
+            .await
+            .context("create schema version table for new epoch")?;
+        self.schema_versions.set(epoch, schema_version);
+
+        info!(
+            "Start new epoch in postgres schema {} ({:?})",
+            schema_name, schema_version
+        );
+        Ok(true)
+    }
+
+    // bring an epoch schema to the current schema version; true if migrated
+    pub async fn migrate_epoch_schema(&self, epoch: EpochRef) -> Result<bool> {
+        let session = self.get_session().await;
+        let schema_version = self.schema_versions.get(&session, epoch).await?;
+        if !schema_version.needs_migration() {
+            return Ok(false);
+        }
+
+        // the background migration and the writer of the current epoch may both get here
+        let schema = PostgresEpoch::build_schema_name(epoch);
+        session
+            .execute_multiple(&format!("SELECT pg_advisory_lock(hashtext('{schema}'))"))
+            .await
+            .context("lock epoch schema for migration")?;
+        let migrated = self.migrate_locked_epoch_schema(&session, epoch).await;
+        session
+            .execute_multiple(&format!("SELECT pg_advisory_unlock(hashtext('{schema}'))"))
+            .await
+            .context("unlock epoch schema after migration")?;
+        migrated
+    }
+
+    async fn migrate_locked_epoch_schema(
+        &self,
+        session: &PostgresSession,
+        epoch: EpochRef,
+    ) -> Result<bool> {
+        // migrated while waiting for the lock
+        let schema_version = PostgresSchemaVersion::load(session, epoch).await?;
+        if !schema_version.needs_migration() {
+            self.schema_versions.remove(epoch);
+            return Ok(false);
+        }
+
+        let started = Instant::now();
+        info!(
+            "Migrating schema {} from schema version {} to {} ...",
+            PostgresEpoch::build_schema_name(epoch),
+            schema_version.version,
+            CURRENT_SCHEMA_VERSION
+        );
+        if schema_version.is_legacy() {
+            PostgresBlock::migrate_legacy_rewards(session, epoch)
+                .await
+                .context("migrate legacy rewards")?;
+            // runs in one transaction including the creation of the schema version table
+            let statement = PostgresSchemaVersion::build_migrate_from_legacy_statement(epoch);
+            session
+                .execute_multiple(&statement)
+                .await
+                .context("migrate legacy transaction columns")?;
+        }
+
+        // the account index of a legacy schema starts empty; an interrupted backfill is resumed from here
+        let nb_transactions = PostgresAccountTransaction::backfill_from_messages(
+            session,
+            epoch,
+            schema_version.compression,
+        )
+        .await
+        .context("backfill account index")?;
+        let statement =
+            PostgresSchemaVersion::build_update_version_statement(epoch, CURRENT_SCHEMA_VERSION);
+        session
+            .execute_multiple(&statement)
+            .await
+            .context("update schema version")?;
+        self.schema_versions.remove(epoch);
+
+        info!(
+            "Migrated schema {} in {:.2}ms (indexed accounts of {} transactions)",
+            PostgresEpoch::build_schema_name(epoch),
+            started.elapsed().as_secs_f64() * 1000.0,
+            nb_transactions
+        );
         Ok(true)
     }
 
+    // migrates all epoch schemas which are not on the current schema version; returns the migrated epochs
+    pub async fn migrate_epoch_schemas(&self) -> Result<Vec<EpochRef>> {
+        let mut migrated = vec![];
+        for (epoch, _) in self.get_epoch_schema_sizes().await? {
+            if self.migrate_epoch_schema(epoch).await? {
+                migrated.push(epoch);
+            }
+        }
+        Ok(migrated)
+    }
+
     async fn get_session(&self) -> PostgresSession {
         self.session_cache
             .get_session()
@@ -153,45 +302,165 @@ impl PostgresBlockStore {
             );
 
             // TODO model commitment levels in new table
+            let replaced = self.remove_abandoned_forks(block).await?;
+            if replaced {
+                let epoch = self.epoch_schedule.get_epoch_at_slot(block.slot).into();
+                self.insert_blocks(epoch, &[block]).await?;
+            }
         }
         Ok(())
     }
 
+    // a finalized block orphans all blocks stored between its parent and itself
+    // returns true if a block of another fork was stored at the slot of the finalized block
+    async fn remove_abandoned_forks(&self, block: &ProducedBlock) -> Result<bool> {
+        let Some(slot_range) = fork_slot_range(block) else {
+            return Ok(false);
+        };
+        let first_epoch = self
+            .epoch_schedule
+            .get_epoch_at_slot(*slot_range.start())
+            .epoch;
+        let last_epoch = self.epoch_schedule.get_epoch_at_slot(block.slot).epoch;
+
+        let session = self.get_session().await;
+        let mut replaced = false;
+        for epoch in (first_epoch..=last_epoch).map(EpochRef::new) {
+            let statement = PostgresBlock::build_query_blockhashes_statement(epoch, &slot_range);
+            let rows = match session.query_list(&statement, &[]).await {
+                Ok(rows) => rows,
+                Err(err)
+                    if err
+                        .code()
+                        .map(|sqlstate| sqlstate == &SqlState::UNDEFINED_TABLE)
+                        .unwrap_or(false) =>
+                {
+                    // no schema for this epoch
+                    continue;
+                }
+                Err(err) => return Err(err).context("query blocks of abandoned forks"),
+            };
+            let stored_blocks = rows
+                .iter()
+                .map(|row| {
+                    (
+                        row.get::<&str, i64>("slot") as Slot,
+                        row.get::<&str, String>("blockhash"),
+                    )
+                })
+                .collect_vec();
+
+            let abandoned_slots = find_abandoned_slots(block, &stored_blocks);
+            if abandoned_slots.is_empty() {
+                continue;
+            }
+
+            let statement = PostgresBlock::build_delete_blocks_statement(epoch, &abandoned_slots);
+            session
+                .execute_multiple(&statement)
+                .await
+                .context("delete blocks of abandoned forks")?;
+            BLOCKSTORE_ABANDONED_BLOCKS_REMOVED.inc_by(abandoned_slots.len() as u64);
+            warn!(
+                "Removed blocks {:?} of abandoned forks (finalized block {} with parent {})",
+                abandoned_slots, block.slot, block.parent_slot
+            );
+            replaced |= abandoned_slots.contains(&block.slot);
+        }
+        Ok(replaced)
+    }
+
     pub async fn save_block(&self, block: &ProducedBlock) -> Result<()> {
-        self.progress_block_commitment_level(block).await?;
+        self.save_blocks(std::slice::from_ref(block))
+            .await
+            .pop()
+            .expect("a result per block")
+    }
+
+    // the blocks of an epoch are written together; one result per block in the order of blocks
+    pub async fn save_blocks(&self, blocks: &[ProducedBlock]) -> Vec<Result<()>> {
+        let mut results = blocks.iter().map(|_| None).collect_vec();
+        let mut blocks_by_epoch: BTreeMap<EpochRef, Vec<usize>> = BTreeMap::new();
+        for (i, block) in blocks.iter().enumerate() {
+            if block.commitment_config.commitment == CommitmentLevel::Finalized {
+                if let Err(err) = self.remove_abandoned_forks(block).await {
+                    results[i] = Some(Err(err));
+                    continue;
+                }
+            }
+            let epoch = self.epoch_schedule.get_epoch_at_slot(block.slot).into();
+            blocks_by_epoch.entry(epoch).or_default().push(i);
+        }
+
+        for (epoch, indexes) in blocks_by_epoch {
+            let epoch_blocks = indexes.iter().map(|i| &blocks[*i]).collect_vec();
+            let result = self.insert_blocks(epoch, &epoch_blocks).await;
+            for i in indexes {
+                results[i] = Some(match &result {
+                    Ok(()) => Ok(()),
+                    Err(err) => Err(anyhow!("{:#}", err)),
+                });
+            }
+        }
 
-        // let PostgresData { current_epoch, .. } = { *self.postgres_data.read().await };
+        results
+            .into_iter()
+            .map(|result| result.expect("a result per block"))
+            .collect()
+    }
 
+    // all blocks must belong to the epoch
+    async fn insert_blocks(&self, epoch: EpochRef, blocks: &[&ProducedBlock]) -> Result<()> {
         trace!(
-            "Saving block {}@{} to postgres storage...",
-            block.slot,
-            block.commitment_config.commitment
+            "Saving blocks {:?} to postgres storage...",
+            blocks.iter().map(|block| block.slot).collect_vec()
         );
-        let slot = block.slot;
-        let transactions = block
-            .transactions
+        let postgres_blocks = blocks
             .iter()
-            .map(|x| PostgresTransaction::new(x, slot))
-            .collect_vec();
-        let postgres_block = PostgresBlock::from(block);
-
-        let epoch = self.epoch_schedule.get_epoch_at_slot(slot);
+            .map(|block| PostgresBlock::try_from(*block))
+            .collect::<Result<Vec<_>>>()?;
 
         let write_session_single = self.write_sessions[0].get_write_session().await;
 
-        let started_block = Instant::now();
-        let inserted = postgres_block
-            .save(&write_session_single, epoch.into())
+        let schema_version = self
+            .schema_versions
+            .get(&write_session_single, epoch)
             .await?;
+        if schema_version.is_legacy() {
+            bail!(
+                "Cannot write blocks to legacy epoch schema - migrate epoch {} first",
+                epoch
+            );
+        }
+        let compression = schema_version.compression;
 
-        if !inserted {
-            debug!("Block {} already exists - skip update", slot);
+        let started_blocks = Instant::now();
+        let inserted_slots =
+            PostgresBlock::save_all(&write_session_single, epoch, &postgres_blocks).await?;
+        if inserted_slots.is_empty() {
+            debug!("Blocks already exist - skip update");
             return Ok(());
         }
-        let elapsed_block_insert = started_block.elapsed();
+        let elapsed_blocks_insert = started_blocks.elapsed();
 
         let started_txs = Instant::now();
 
+        let transaction_infos = blocks
+            .iter()
+            .filter(|block| inserted_slots.contains(&block.slot))
+            .flat_map(|block| {
+                block
+                    .transactions
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, tx)| (block.slot, idx, tx))
+            })
+            .collect_vec();
+        let transactions = transaction_infos
+            .iter()
+            .map(|(slot, idx, tx)| PostgresTransaction::new(tx, *slot, *idx))
+            .collect_vec();
+
         let mut queries_fut = Vec::new();
         let chunk_size =
             div_ceil(transactions.len(), self.write_sessions.len()).max(MIN_WRITE_CHUNK_SIZE);
@@ -200,23 +469,60 @@ impl PostgresBlockStore {
             chunks.len() <= self.write_sessions.len(),
             "cannot have more chunks than session"
         );
-        for (i, chunk) in chunks.iter().enumerate() {
+        // same chunking as transactions so that each chunk indexes its own transactions
+        let account_transaction_chunks = transaction_infos
+            .chunks(chunk_size)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .flat_map(|(slot, _, tx)| {
+                        PostgresAccountTransaction::from_transaction_info(tx, *slot)
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        for (i, (chunk, account_transactions)) in
+            chunks.iter().zip(account_transaction_chunks).enumerate()
+        {
             let session = self.write_sessions[i].get_write_session().await.clone();
-            let future =
-                PostgresTransaction::save_transactions_from_block(session, epoch.into(), chunk);
+            let future = async move {
+                PostgresTransaction::save_transactions_from_block(
+                    session.clone(),
+                    epoch,
+                    compression,
+                    chunk,
+                )
+                .await?;
+                PostgresAccountTransaction::save_account_transactions_from_block(
+                    session,
+                    epoch,
+                    &account_transactions,
+                )
+                .await
+            };
             queries_fut.push(future);
         }
         let all_results: Vec<Result<()>> = futures_util::future::join_all(queries_fut).await;
-        for result in all_results {
-            result.expect("Save query must succeed");
+        if let Err(err) = all_results.into_iter().collect::<Result<Vec<()>>>() {
+            // a block without its transactions would be skipped when written again
+            let statement = PostgresBlock::build_delete_blocks_statement(epoch, &inserted_slots);
+            if let Err(delete_err) = write_session_single.execute_multiple(&statement).await {
+                error!(
+                    "Failed to remove blocks {:?} after failed transaction insert: {:?}",
+                    inserted_slots, delete_err
+                );
+            }
+            return Err(err).context("save transactions of blocks");
         }
 
         let elapsed_txs_insert = started_txs.elapsed();
 
         info!(
-            "Saving block {}@{} to postgres took {:.2}ms for block and {:.2}ms for {} transactions ({}x{} chunks)",
-            slot, block.commitment_config.commitment,
-            elapsed_block_insert.as_secs_f64() * 1000.0,
+            "Saving {} blocks ({}..={}) to postgres took {:.2}ms for blocks and {:.2}ms for {} transactions ({}x{} chunks)",
+            inserted_slots.len(),
+            inserted_slots.first().expect("inserted blocks"),
+            inserted_slots.last().expect("inserted blocks"),
+            elapsed_blocks_insert.as_secs_f64() * 1000.0,
             elapsed_txs_insert.as_secs_f64() * 1000.0,
             transactions.len(),
             chunks.len(),
@@ -273,7 +579,31 @@ impl PostgresBlockStore {
         Ok(created_current || created_next)
     }
 
-    // used for testing only ATM
+    // epoch schemas with their on-disk size in bytes (ascending by epoch)
+    pub async fn get_epoch_schema_sizes(&self) -> anyhow::Result<Vec<(EpochRef, u64)>> {
+        let session = self.get_session().await;
+        let statement = PostgresEpoch::build_query_schema_sizes_statement();
+        let rows = session
+            .query_list(&statement, &[])
+            .await
+            .context("query epoch schema sizes")?;
+
+        let sizes = rows
+            .iter()
+            .map(|row| {
+                (
+                    PostgresEpoch::parse_epoch_from_schema_name(
+                        row.get::<&str, &str>("schema_name"),
+                    ),
+                    row.get::<&str, i64>("size_bytes") as u64,
+                )
+            })
+            .sorted_by_key(|(epoch, _)| *epoch)
+            .collect_vec();
+        Ok(sizes)
+    }
+
+    // used by epoch retention and tests
     pub async fn drop_epoch_schema(&self, epoch: EpochRef) -> anyhow::Result<()> {
         // create schema for new epoch
         let schema_name = PostgresEpoch::build_schema_name(epoch);
@@ -281,6 +611,7 @@ impl PostgresBlockStore {
 
         let statement = PostgresEpoch::build_drop_schema_statement(epoch);
         let result_drop_schema = session.execute_multiple(&statement).await;
+        self.schema_versions.remove(epoch);
         match result_drop_schema {
             Ok(_) => {
                 warn!("Dropped schema {}", schema_name);
@@ -293,6 +624,25 @@ impl PostgresBlockStore {
     }
 }
 
+#[async_trait]
+impl BlockStorageWriter for PostgresBlockStore {
+    async fn prepare_epoch_schema(&self, slot: Slot) -> Result<bool> {
+        PostgresBlockStore::prepare_epoch_schema(self, slot).await
+    }
+
+    async fn save_block(&self, block: &ProducedBlock) -> Result<()> {
+        PostgresBlockStore::save_block(self, block).await
+    }
+
+    async fn save_blocks(&self, blocks: &[ProducedBlock]) -> Vec<Result<()>> {
+        PostgresBlockStore::save_blocks(self, blocks).await
+    }
+
+    async fn progress_block_commitment_level(&self, block: &ProducedBlock) -> Result<()> {
+        PostgresBlockStore::progress_block_commitment_level(self, block).await
+    }
+}
+
 fn build_assign_permissions_statements(epoch: EpochRef) -> String {
     let schema = PostgresEpoch::build_schema_name(epoch);
     format!(
@@ -309,6 +659,34 @@ fn build_assign_permissions_statements(epoch: EpochRef) -> String {
     )
 }
 
+// slots which must not hold blocks of other forks once the block is finalized
+fn fork_slot_range(block: &ProducedBlock) -> Option<RangeInclusive<Slot>> {
+    if block.parent_slot >= block.slot {
+        return None;
+    }
+    Some(block.parent_slot + 1..=block.slot)
+}
+
+// stored blocks which are not on the chain of the finalized block
+fn find_abandoned_slots(
+    finalized_block: &ProducedBlock,
+    stored_blocks: &[(Slot, String)],
+) -> Vec<Slot> {
+    let Some(slot_range) = fork_slot_range(finalized_block) else {
+        return vec![];
+    };
+    let blockhash = finalized_block.blockhash.to_string();
+    stored_blocks
+        .iter()
+        .filter(|(slot, _)| slot_range.contains(slot))
+        .filter(|(slot, stored_blockhash)| {
+            *slot != finalized_block.slot || *stored_blockhash != blockhash
+        })
+        .map(|(slot, _)| *slot)
+        .sorted()
+        .collect_vec()
+}
+
 fn div_ceil(a: usize, b: usize) -> usize {
     (a.saturating_add(b).saturating_sub(1)).saturating_div(b)
 }
@@ -316,12 +694,59 @@ fn div_ceil(a: usize, b: usize) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_lite_rpc_core::structures::produced_block::{ProducedBlockInner, TransactionInfo};
+    use solana_lite_rpc_core::test_utils::{create_fork_block, create_test_tx};
     use solana_sdk::commitment_config::CommitmentConfig;
-    use solana_sdk::message::{v0, MessageHeader, VersionedMessage};
-    use solana_sdk::pubkey::Pubkey;
-    use solana_sdk::signature::Signature;
-    use std::str::FromStr;
+
+    fn stored(blocks: &[&ProducedBlock]) -> Vec<(Slot, String)> {
+        blocks
+            .iter()
+            .map(|block| (block.slot, block.blockhash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finalized_block_orphans_skipped_slots() {
+        //        / 101 - 103        (abandoned)
+        // 100 --
+        //        \ 102 - 104        (finalized)
+        let fork_a_101 = create_fork_block(101, 100, CommitmentConfig::confirmed(), vec![]);
+        let fork_a_103 = create_fork_block(103, 101, CommitmentConfig::confirmed(), vec![]);
+        let fork_b_102 = create_fork_block(102, 100, CommitmentConfig::confirmed(), vec![]);
+        let fork_b_104 = create_fork_block(104, 102, CommitmentConfig::confirmed(), vec![]);
+        let stored_blocks = stored(&[&fork_a_101, &fork_b_102, &fork_a_103, &fork_b_104]);
+
+        let abandoned = find_abandoned_slots(&fork_b_102.to_finalized_block(), &stored_blocks);
+        assert_eq!(abandoned, vec![101]);
+        let abandoned = find_abandoned_slots(&fork_b_104.to_finalized_block(), &stored_blocks);
+        assert_eq!(abandoned, vec![103]);
+    }
+
+    #[test]
+    fn finalized_block_keeps_its_own_chain() {
+        let parent = create_fork_block(100, 99, CommitmentConfig::confirmed(), vec![]);
+        let block = create_fork_block(101, 100, CommitmentConfig::confirmed(), vec![]);
+        let child = create_fork_block(102, 101, CommitmentConfig::confirmed(), vec![]);
+        let stored_blocks = stored(&[&parent, &block, &child]);
+
+        assert!(find_abandoned_slots(&block.to_finalized_block(), &stored_blocks).is_empty());
+    }
+
+    #[test]
+    fn finalized_block_replaces_other_block_at_same_slot() {
+        let stored_block = create_fork_block(101, 100, CommitmentConfig::confirmed(), vec![]);
+        let finalized_block = create_fork_block(101, 100, CommitmentConfig::finalized(), vec![]);
+
+        let abandoned = find_abandoned_slots(&finalized_block, &stored(&[&stored_block]));
+        assert_eq!(abandoned, vec![101]);
+    }
+
+    #[test]
+    fn fork_slot_range_ignores_invalid_parent() {
+        let block = create_fork_block(101, 101, CommitmentConfig::finalized(), vec![]);
+        assert_eq!(fork_slot_range(&block), None);
+        let block = create_fork_block(105, 101, CommitmentConfig::finalized(), vec![]);
+        assert_eq!(fork_slot_range(&block), Some(102..=105));
+    }
 
     #[tokio::test]
     #[ignore]
@@ -357,54 +782,13 @@ mod tests {
             PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
 
         postgres_block_store
-            .save_block(&create_test_block())
+            .save_block(&create_fork_block(
+                223555999,
+                666,
+                CommitmentConfig::finalized(),
+                vec![create_test_tx(), create_test_tx()],
+            ))
             .await
             .unwrap();
     }
-
-    fn create_test_block() -> ProducedBlock {
-        let sig1 = Signature::from_str("5VBroA4MxsbZdZmaSEb618WRRwhWYW9weKhh3md1asGRx7nXDVFLua9c98voeiWdBE7A9isEoLL7buKyaVRSK1pV").unwrap();
-        let sig2 = Signature::from_str("3d9x3rkVQEoza37MLJqXyadeTbEJGUB6unywK4pjeRLJc16wPsgw3dxPryRWw3UaLcRyuxEp1AXKGECvroYxAEf2").unwrap();
-
-        let inner = ProducedBlockInner {
-            block_height: 42,
-            blockhash: solana_sdk::hash::Hash::new_unique(),
-            previous_blockhash: solana_sdk::hash::Hash::new_unique(),
-            parent_slot: 666,
-            slot: 223555999,
-            transactions: vec![create_test_tx(sig1), create_test_tx(sig2)],
-            // TODO double if this is unix millis or seconds
-            block_time: 1699260872000,
-            leader_id: None,
-            rewards: None,
-        };
-        ProducedBlock::new(inner, CommitmentConfig::finalized())
-    }
-
-    fn create_test_tx(signature: Signature) -> TransactionInfo {
-        TransactionInfo {
-            signature,
-            is_vote: false,
-            err: None,
-            cu_requested: Some(40000),
-            prioritization_fees: Some(5000),
-            cu_consumed: Some(32000),
-            recent_blockhash: solana_sdk::hash::Hash::new_unique(),
-            message: create_test_message(),
-            writable_accounts: vec![],
-            readable_accounts: vec![],
-            address_lookup_tables: vec![],
-        }
-    }
-
-    fn create_test_message() -> VersionedMessage {
-        VersionedMessage::V0(v0::Message {
-            header: MessageHeader {
-                num_required_signatures: 1,
-                ..MessageHeader::default()
-            },
-            account_keys: vec![Pubkey::new_unique()],
-            ..v0::Message::default()
-        })
-    }
 }
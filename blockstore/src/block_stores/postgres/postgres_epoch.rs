@@ -30,6 +30,22 @@ impl PostgresEpoch {
         )
     }
 
+    // on-disk size of all epoch schemas including indexes and toast
+    pub fn build_query_schema_sizes_statement() -> String {
+        format!(
+            r#"
+                SELECT
+                    nsp.nspname AS schema_name,
+                    COALESCE(sum(pg_total_relation_size(cls.oid)), 0)::bigint AS size_bytes
+                FROM pg_namespace nsp
+                LEFT JOIN pg_class cls ON cls.relnamespace = nsp.oid AND cls.relkind = 'r'
+                WHERE nsp.nspname ~ '^{schema_prefix}[0-9]+$'
+                GROUP BY nsp.nspname
+            "#,
+            schema_prefix = EPOCH_SCHEMA_PREFIX
+        )
+    }
+
     pub fn parse_epoch_from_schema_name(schema_name: &str) -> EpochRef {
         let epoch_number_str = schema_name.trim_start_matches(EPOCH_SCHEMA_PREFIX);
         let epoch = epoch_number_str.parse::<u64>().unwrap();
@@ -54,4 +70,11 @@ mod tests {
         let epoch = PostgresEpoch::parse_epoch_from_schema_name(schema);
         assert_eq!(644, epoch.get_epoch());
     }
+
+    #[test]
+    fn test_build_query_schema_sizes_statement() {
+        let statement = PostgresEpoch::build_query_schema_sizes_statement();
+        assert!(statement.contains("'^rpc2a_epoch_[0-9]+$'"));
+        assert!(statement.contains("GROUP BY nsp.nspname"));
+    }
 }
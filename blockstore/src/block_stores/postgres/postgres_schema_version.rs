@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{bail, Context};
+use solana_lite_rpc_core::structures::epoch::EpochRef;
+use tokio_postgres::error::SqlState;
+
+use super::postgres_account_transaction::PostgresAccountTransaction;
+use super::postgres_epoch::PostgresEpoch;
+use super::postgres_session::PostgresSession;
+use super::postgres_transaction::PostgresTransaction;
+
+// message and err stored as base64 text; epoch schemas without schema_version table
+pub const SCHEMA_VERSION_LEGACY: i32 = 1;
+// message and err stored as bytea, optionally compressed
+pub const SCHEMA_VERSION_BINARY: i32 = 2;
+// like binary; the account index also covers the transactions written before it existed
+pub const SCHEMA_VERSION_ACCOUNT_INDEX: i32 = 3;
+pub const CURRENT_SCHEMA_VERSION: i32 = SCHEMA_VERSION_ACCOUNT_INDEX;
+
+const ZSTD_LEVEL: i32 = 3;
+// marker byte in front of each compressed value
+const VALUE_RAW: u8 = 0;
+const VALUE_ZSTD: u8 = 1;
+
+/// compression of the binary columns, fixed per epoch schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryCompression {
+    #[default]
+    None,
+    Zstd,
+}
+
+impl BinaryCompression {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryCompression::None => "none",
+            BinaryCompression::Zstd => "zstd",
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            BinaryCompression::None => data.to_vec(),
+            BinaryCompression::Zstd => {
+                // small values often do not compress - keep them as they are
+                match zstd::bulk::compress(data, ZSTD_LEVEL) {
+                    Ok(compressed) if compressed.len() < data.len() => {
+                        let mut value = Vec::with_capacity(compressed.len() + 1);
+                        value.push(VALUE_ZSTD);
+                        value.extend_from_slice(&compressed);
+                        value
+                    }
+                    _ => {
+                        let mut value = Vec::with_capacity(data.len() + 1);
+                        value.push(VALUE_RAW);
+                        value.extend_from_slice(data);
+                        value
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn decompress(&self, value: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            BinaryCompression::None => Ok(value.to_vec()),
+            BinaryCompression::Zstd => match value.split_first() {
+                Some((&VALUE_RAW, data)) => Ok(data.to_vec()),
+                Some((&VALUE_ZSTD, data)) => {
+                    zstd::stream::decode_all(data).context("decompress zstd value")
+                }
+                Some((marker, _)) => bail!("Unknown compression marker {}", marker),
+                None => bail!("Empty compressed value"),
+            },
+        }
+    }
+}
+
+impl FromStr for BinaryCompression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(BinaryCompression::None),
+            "zstd" => Ok(BinaryCompression::Zstd),
+            _ => bail!("Unknown compression '{}' - use 'none' or 'zstd'", s),
+        }
+    }
+}
+
+impl Display for BinaryCompression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// storage format of an epoch schema; readers and writers must use the format of the schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PostgresSchemaVersion {
+    pub version: i32,
+    pub compression: BinaryCompression,
+}
+
+impl PostgresSchemaVersion {
+    pub fn current(compression: BinaryCompression) -> Self {
+        Self {
+            version: CURRENT_SCHEMA_VERSION,
+            compression,
+        }
+    }
+
+    pub fn legacy() -> Self {
+        Self {
+            version: SCHEMA_VERSION_LEGACY,
+            compression: BinaryCompression::None,
+        }
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.version == SCHEMA_VERSION_LEGACY
+    }
+
+    // readable but not on the current schema version
+    pub fn needs_migration(&self) -> bool {
+        self.version < CURRENT_SCHEMA_VERSION
+    }
+
+    pub fn build_create_table_statement(&self, epoch: EpochRef) -> String {
+        let schema = PostgresEpoch::build_schema_name(epoch);
+        format!(
+            r#"
+                CREATE TABLE {schema}.schema_version(
+                    version int4 NOT NULL,
+                    compression text NOT NULL
+                );
+                INSERT INTO {schema}.schema_version(version, compression) VALUES ({version}, '{compression}');
+            "#,
+            schema = schema,
+            version = self.version,
+            compression = self.compression,
+        )
+    }
+
+    pub fn build_update_version_statement(epoch: EpochRef, version: i32) -> String {
+        format!(
+            r#"
+                UPDATE {schema}.schema_version SET version = {version};
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+            version = version,
+        )
+    }
+
+    pub fn build_query_statement(epoch: EpochRef) -> String {
+        format!(
+            r#"
+                SELECT version, compression FROM {schema}.schema_version
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+        )
+    }
+
+    // schema version of the epoch schema; legacy if the schema predates versioning
+    pub async fn load(session: &PostgresSession, epoch: EpochRef) -> anyhow::Result<Self> {
+        let statement = Self::build_query_statement(epoch);
+        match session.query_one(&statement, &[]).await {
+            Ok(row) => {
+                let compression: String = row.get("compression");
+                Ok(Self {
+                    version: row.get("version"),
+                    compression: compression.parse()?,
+                })
+            }
+            Err(err)
+                if err
+                    .code()
+                    .map(|sqlstate| sqlstate == &SqlState::UNDEFINED_TABLE)
+                    .unwrap_or(false) =>
+            {
+                Ok(Self::legacy())
+            }
+            Err(err) => Err(err).context(format!("query schema version of epoch {}", epoch)),
+        }
+    }
+
+    // converts the text columns of a legacy schema in place and adds the empty account index; existing data stays uncompressed
+    pub fn build_migrate_from_legacy_statement(epoch: EpochRef) -> String {
+        let schema = PostgresEpoch::build_schema_name(epoch);
+        format!(
+            r#"
+                ALTER TABLE {schema}.transaction_blockdata
+                    ALTER COLUMN err TYPE bytea USING decode(err, 'base64'),
+                    ALTER COLUMN message TYPE bytea USING decode(message, 'base64'),
+                    ADD COLUMN IF NOT EXISTS tx_index int4 NOT NULL DEFAULT 0,
+                    ADD COLUMN IF NOT EXISTS is_vote bool NOT NULL DEFAULT false,
+                    ADD COLUMN IF NOT EXISTS writable_accounts text[] NOT NULL DEFAULT '{{}}',
+                    ADD COLUMN IF NOT EXISTS readable_accounts text[] NOT NULL DEFAULT '{{}}';
+                {create_account_tables}
+                {migrate_transaction_key}
+                {migrate_account_transaction_key}
+                {create_version_table}
+            "#,
+            schema = schema,
+            create_account_tables = PostgresAccountTransaction::build_create_table_statement(epoch),
+            migrate_transaction_key =
+                PostgresTransaction::build_migrate_primary_key_statement(epoch),
+            migrate_account_transaction_key =
+                PostgresAccountTransaction::build_migrate_primary_key_statement(epoch),
+            create_version_table = Self {
+                version: SCHEMA_VERSION_BINARY,
+                compression: BinaryCompression::None,
+            }
+            .build_create_table_statement(epoch),
+        )
+    }
+}
+
+/// schema versions of the epoch schemas; legacy versions are not cached as they change on migration
+#[derive(Clone, Default)]
+pub struct SchemaVersionCache {
+    versions: Arc<RwLock<HashMap<EpochRef, PostgresSchemaVersion>>>,
+}
+
+impl SchemaVersionCache {
+    pub async fn get(
+        &self,
+        session: &PostgresSession,
+        epoch: EpochRef,
+    ) -> anyhow::Result<PostgresSchemaVersion> {
+        let cached = self.versions.read().unwrap().get(&epoch).copied();
+        if let Some(version) = cached {
+            return Ok(version);
+        }
+
+        let version = PostgresSchemaVersion::load(session, epoch).await?;
+        if !version.is_legacy() {
+            self.set(epoch, version);
+        }
+        Ok(version)
+    }
+
+    pub fn set(&self, epoch: EpochRef, version: PostgresSchemaVersion) {
+        self.versions.write().unwrap().insert(epoch, version);
+    }
+
+    pub fn remove(&self, epoch: EpochRef) {
+        self.versions.write().unwrap().remove(&epoch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_round_trip() {
+        let compressible = vec![7u8; 1000];
+        let incompressible = (0..=255u8).collect::<Vec<_>>();
+
+        for compression in [BinaryCompression::None, BinaryCompression::Zstd] {
+            for data in [&compressible, &incompressible] {
+                let value = compression.compress(data);
+                assert_eq!(&compression.decompress(&value).unwrap(), data);
+            }
+        }
+
+        assert!(BinaryCompression::Zstd.compress(&compressible).len() < 100);
+        // one byte overhead for values which do not compress
+        assert_eq!(
+            BinaryCompression::Zstd.compress(&incompressible).len(),
+            incompressible.len() + 1
+        );
+    }
+
+    #[test]
+    fn parse_compression() {
+        assert_eq!(
+            "zstd".parse::<BinaryCompression>().unwrap(),
+            BinaryCompression::Zstd
+        );
+        assert_eq!(
+            "None".parse::<BinaryCompression>().unwrap(),
+            BinaryCompression::None
+        );
+        assert!("lz4".parse::<BinaryCompression>().is_err());
+    }
+
+    #[test]
+    fn migrate_statement_converts_base64_columns() {
+        let statement =
+            PostgresSchemaVersion::build_migrate_from_legacy_statement(EpochRef::new(644));
+        assert!(
+            statement.contains("ALTER COLUMN message TYPE bytea USING decode(message, 'base64')")
+        );
+        assert!(statement.contains("DEFAULT '{}'"));
+        assert!(statement.contains("CREATE TABLE IF NOT EXISTS rpc2a_epoch_644.account_ids("));
+        assert!(
+            statement.contains("CREATE TABLE IF NOT EXISTS rpc2a_epoch_644.account_transactions(")
+        );
+        assert!(statement.contains(
+            "ADD CONSTRAINT pk_transaction_blockdata PRIMARY KEY (transaction_id, slot)"
+        ));
+        assert!(statement.contains(
+            "ADD CONSTRAINT pk_account_transactions PRIMARY KEY (account_id, transaction_id, slot)"
+        ));
+        assert!(statement.contains(
+            "INSERT INTO rpc2a_epoch_644.schema_version(version, compression) VALUES (2, 'none')"
+        ));
+    }
+
+    #[test]
+    fn binary_schema_needs_account_index_migration() {
+        assert!(PostgresSchemaVersion::legacy().needs_migration());
+        let binary = PostgresSchemaVersion {
+            version: SCHEMA_VERSION_BINARY,
+            compression: BinaryCompression::None,
+        };
+        assert!(binary.needs_migration());
+        assert!(!binary.is_legacy());
+        assert!(!PostgresSchemaVersion::current(BinaryCompression::Zstd).needs_migration());
+    }
+}
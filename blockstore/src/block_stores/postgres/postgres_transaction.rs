@@ -1,20 +1,23 @@
 use std::str::FromStr;
 
+use anyhow::Context;
 use futures_util::pin_mut;
 use log::debug;
-use solana_lite_rpc_core::encoding::BinaryEncoding;
 use solana_lite_rpc_core::solana_utils::hash_from_str;
 use solana_lite_rpc_core::structures::epoch::EpochRef;
-use solana_lite_rpc_core::{encoding::BASE64, structures::produced_block::TransactionInfo};
+use solana_lite_rpc_core::structures::produced_block::TransactionInfo;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_sdk::slot_history::Slot;
 use solana_sdk::transaction::TransactionError;
 use tokio::time::Instant;
 use tokio_postgres::binary_copy::BinaryCopyInWriter;
 use tokio_postgres::types::Type;
-use tokio_postgres::CopyInSink;
+use tokio_postgres::{CopyInSink, Row};
 
 use super::postgres_epoch::*;
+use super::postgres_schema_version::BinaryCompression;
 use super::postgres_session::*;
 
 #[derive(Debug)]
@@ -22,52 +25,102 @@ pub struct PostgresTransaction {
     pub signature: String,
     // TODO clarify
     pub slot: i64,
-    pub err: Option<String>,
+    // bincode serialized TransactionError
+    pub err: Option<Vec<u8>>,
     pub cu_requested: Option<i64>,
     pub prioritization_fees: Option<i64>,
     pub cu_consumed: Option<i64>,
     pub recent_blockhash: String,
-    pub message: String,
+    // bincode serialized VersionedMessage
+    pub message: Vec<u8>,
+    // position in the block
+    pub tx_index: i32,
+    pub is_vote: bool,
+    // including the accounts loaded from address lookup tables
+    pub writable_accounts: Vec<String>,
+    pub readable_accounts: Vec<String>,
 }
 
 impl PostgresTransaction {
-    pub fn new(value: &TransactionInfo, slot: Slot) -> Self {
+    pub fn new(value: &TransactionInfo, slot: Slot, tx_index: usize) -> Self {
         Self {
             signature: value.signature.to_string(),
             err: value
                 .err
-                .clone()
-                .map(|x| BASE64.serialize(&x).ok())
-                .unwrap_or(None),
+                .as_ref()
+                .map(|x| bincode::serialize(x).expect("serializable transaction error")),
             cu_requested: value.cu_requested.map(|x| x as i64),
             prioritization_fees: value.prioritization_fees.map(|x| x as i64),
             cu_consumed: value.cu_consumed.map(|x| x as i64),
             recent_blockhash: value.recent_blockhash.to_string(),
-            message: BinaryEncoding::Base64.encode(value.message.serialize()),
+            message: value.message.serialize(),
             slot: slot as i64,
+            tx_index: tx_index as i32,
+            is_vote: value.is_vote,
+            writable_accounts: value
+                .writable_accounts
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            readable_accounts: value
+                .readable_accounts
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
         }
     }
 
-    pub fn to_transaction_info(&self) -> TransactionInfo {
-        TransactionInfo {
-            signature: Signature::from_str(self.signature.as_str()).unwrap(),
+    // expects the columns of build_query_statement/build_query_by_signature_statement
+    pub fn from_row(row: &Row, compression: BinaryCompression) -> anyhow::Result<Self> {
+        let err: Option<Vec<u8>> = row.get("err");
+        let message: Vec<u8> = row.get("message");
+        Ok(Self {
+            signature: row.get("signature"),
+            slot: row.get("slot"),
+            err: err.map(|x| compression.decompress(&x)).transpose()?,
+            cu_requested: row.get("cu_requested"),
+            prioritization_fees: row.get("prioritization_fees"),
+            cu_consumed: row.get("cu_consumed"),
+            recent_blockhash: row.get("recent_blockhash"),
+            message: compression.decompress(&message)?,
+            tx_index: row.get("tx_index"),
+            is_vote: row.get("is_vote"),
+            writable_accounts: row.get("writable_accounts"),
+            readable_accounts: row.get("readable_accounts"),
+        })
+    }
+
+    // fails on corrupt rows instead of returning a partial transaction
+    pub fn to_transaction_info(&self) -> anyhow::Result<TransactionInfo> {
+        let message: VersionedMessage = bincode::deserialize(&self.message)
+            .with_context(|| format!("deserialize message of transaction {}", self.signature))?;
+        // the lookups are part of the stored message
+        let address_lookup_tables = message
+            .address_table_lookups()
+            .map(|x| x.to_vec())
+            .unwrap_or_default();
+
+        Ok(TransactionInfo {
+            signature: Signature::from_str(self.signature.as_str())
+                .with_context(|| format!("parse signature {}", self.signature))?,
             err: self
                 .err
                 .as_ref()
-                .and_then(|x| BASE64.deserialize::<TransactionError>(x).ok()),
+                .map(|x| bincode::deserialize::<TransactionError>(x))
+                .transpose()
+                .with_context(|| format!("deserialize error of transaction {}", self.signature))?,
             cu_requested: self.cu_requested.map(|x| x as u32),
             prioritization_fees: self.prioritization_fees.map(|x| x as u64),
             cu_consumed: self.cu_consumed.map(|x| x as u64),
-            recent_blockhash: hash_from_str(&self.recent_blockhash).expect("valid blockhash"),
-            message: BinaryEncoding::Base64
-                .deserialize(&self.message)
-                .expect("serialized message"),
-            // TODO readable_accounts etc.
-            readable_accounts: vec![],
-            writable_accounts: vec![],
-            is_vote: false,
-            address_lookup_tables: vec![],
-        }
+            recent_blockhash: hash_from_str(&self.recent_blockhash).with_context(|| {
+                format!("parse recent blockhash of transaction {}", self.signature)
+            })?,
+            readable_accounts: parse_pubkeys(&self.readable_accounts)?,
+            writable_accounts: parse_pubkeys(&self.writable_accounts)?,
+            message,
+            is_vote: self.is_vote,
+            address_lookup_tables,
+        })
     }
 
     pub fn build_create_table_statement(epoch: EpochRef) -> String {
@@ -86,14 +139,20 @@ impl PostgresTransaction {
                 -- parameter 'schema' is something like 'rpc2a_epoch_592'
                 CREATE TABLE IF NOT EXISTS {schema}.transaction_blockdata(
                     -- transaction_id must exist in the transaction_ids table
-                    transaction_id bigint PRIMARY KEY WITH (FILLFACTOR=90),
+                    transaction_id bigint NOT NULL,
                     slot bigint NOT NULL,
+                    -- one row per block; the same transaction can land on different forks
+                    CONSTRAINT pk_transaction_blockdata PRIMARY KEY (transaction_id, slot) WITH (FILLFACTOR=90),
                     cu_requested bigint,
                     prioritization_fees bigint,
                     cu_consumed bigint,
                     recent_blockhash text NOT NULL,
-                    err text,
-                    message text NOT NULL
+                    err bytea,
+                    message bytea NOT NULL,
+                    tx_index int4 NOT NULL,
+                    is_vote bool NOT NULL,
+                    writable_accounts text[] NOT NULL,
+                    readable_accounts text[] NOT NULL
                     -- model_transaction_blockdata
                 ) WITH (FILLFACTOR=90,TOAST_TUPLE_TARGET=128);
                 CREATE INDEX idx_slot ON {schema}.transaction_blockdata USING btree (slot) WITH (FILLFACTOR=90);
@@ -102,6 +161,23 @@ impl PostgresTransaction {
         )
     }
 
+    // schemas created before transactions were keyed by block have transaction_id as primary key
+    pub fn build_migrate_primary_key_statement(epoch: EpochRef) -> String {
+        let schema = PostgresEpoch::build_schema_name(epoch);
+        format!(
+            r#"
+                DO $$
+                BEGIN
+                    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conrelid = '{schema}.transaction_blockdata'::regclass AND conname = 'pk_transaction_blockdata') THEN
+                        ALTER TABLE {schema}.transaction_blockdata DROP CONSTRAINT IF EXISTS transaction_blockdata_pkey;
+                        ALTER TABLE {schema}.transaction_blockdata ADD CONSTRAINT pk_transaction_blockdata PRIMARY KEY (transaction_id, slot) WITH (FILLFACTOR=90);
+                    END IF;
+                END $$;
+            "#,
+            schema = schema
+        )
+    }
+
     // removed the foreign key as it slows down inserts
     pub fn build_foreign_key_statement(epoch: EpochRef) -> String {
         let schema = PostgresEpoch::build_schema_name(epoch);
@@ -117,6 +193,7 @@ impl PostgresTransaction {
     pub async fn save_transactions_from_block(
         postgres_session: PostgresSession,
         epoch: EpochRef,
+        compression: BinaryCompression,
         transactions: &[Self],
     ) -> anyhow::Result<()> {
         let schema = PostgresEpoch::build_schema_name(epoch);
@@ -129,8 +206,12 @@ impl PostgresTransaction {
                 prioritization_fees bigint,
                 cu_consumed bigint,
                 recent_blockhash text STORAGE PLAIN,
-                err text STORAGE PLAIN,
-                message text STORAGE PLAIN
+                err bytea STORAGE PLAIN,
+                message bytea STORAGE PLAIN,
+                tx_index int4,
+                is_vote bool,
+                writable_accounts text[],
+                readable_accounts text[]
                 -- model_transaction_blockdata
             );
             TRUNCATE transaction_raw_blockdata;
@@ -146,7 +227,11 @@ impl PostgresTransaction {
                 cu_consumed,
                 recent_blockhash,
                 err,
-                message
+                message,
+                tx_index,
+                is_vote,
+                writable_accounts,
+                readable_accounts
                 -- model_transaction_blockdata
             ) FROM STDIN BINARY
         "#;
@@ -161,8 +246,12 @@ impl PostgresTransaction {
                 Type::INT8,
                 Type::INT8,
                 Type::TEXT,
-                Type::TEXT,
-                Type::TEXT, // model_transaction_blockdata
+                Type::BYTEA,
+                Type::BYTEA,
+                Type::INT4,
+                Type::BOOL,
+                Type::TEXT_ARRAY,
+                Type::TEXT_ARRAY, // model_transaction_blockdata
             ],
         );
         pin_mut!(writer);
@@ -177,8 +266,14 @@ impl PostgresTransaction {
                 err,
                 recent_blockhash,
                 message,
+                tx_index,
+                is_vote,
+                writable_accounts,
+                readable_accounts,
                 // model_transaction_blockdata
             } = tx;
+            let err = err.as_ref().map(|x| compression.compress(x));
+            let message = compression.compress(message);
 
             writer
                 .as_mut()
@@ -188,9 +283,13 @@ impl PostgresTransaction {
                     &cu_requested,
                     &prioritization_fees,
                     &cu_consumed,
-                    &err,
                     &recent_blockhash,
+                    &err,
                     &message,
+                    &tx_index,
+                    &is_vote,
+                    &writable_accounts,
+                    &readable_accounts,
                     // model_transaction_blockdata
                 ])
                 .await?;
@@ -227,11 +326,17 @@ impl PostgresTransaction {
                     cu_requested,
                     prioritization_fees,
                     cu_consumed,
-                    err,
                     recent_blockhash,
-                    message
+                    err,
+                    message,
+                    tx_index,
+                    is_vote,
+                    writable_accounts,
+                    readable_accounts
                     -- model_transaction_blockdata
                 FROM transaction_raw_blockdata
+                -- same transaction in blocks of different forks: one row per block until the abandoned forks are removed
+                ON CONFLICT (transaction_id, slot) DO NOTHING
         "#,
             schema = schema,
         );
@@ -251,18 +356,328 @@ impl PostgresTransaction {
             r#"
                 SELECT
                     (SELECT signature FROM {schema}.transaction_ids tx_ids WHERE tx_ids.transaction_id = transaction_blockdata.transaction_id),
+                    slot,
                     cu_requested,
                     prioritization_fees,
                     cu_consumed,
                     err,
                     recent_blockhash,
-                    message
+                    message,
+                    tx_index,
+                    is_vote,
+                    writable_accounts,
+                    readable_accounts
                     -- model_transaction_blockdata
                 FROM {schema}.transaction_blockdata
                 WHERE slot = {}
+                -- migrated legacy schemas have no tx_index
+                ORDER BY tx_index, transaction_id
             "#,
             slot,
             schema = PostgresEpoch::build_schema_name(epoch),
         )
     }
+
+    pub fn build_query_by_signature_statement(epoch: EpochRef, signature: &Signature) -> String {
+        format!(
+            r#"
+                SELECT
+                    tx_ids.signature,
+                    tx.slot,
+                    tx.cu_requested,
+                    tx.prioritization_fees,
+                    tx.cu_consumed,
+                    tx.err,
+                    tx.recent_blockhash,
+                    tx.message,
+                    tx.tx_index,
+                    tx.is_vote,
+                    tx.writable_accounts,
+                    tx.readable_accounts,
+                    -- model_transaction_blockdata
+                    blocks.block_time
+                FROM {schema}.transaction_ids tx_ids
+                INNER JOIN {schema}.transaction_blockdata tx ON tx.transaction_id = tx_ids.transaction_id
+                INNER JOIN {schema}.blocks blocks ON blocks.slot = tx.slot
+                WHERE tx_ids.signature = '{signature}'
+                -- on more than one fork until finalized
+                ORDER BY tx.slot DESC
+                LIMIT 1
+            "#,
+            schema = PostgresEpoch::build_schema_name(epoch),
+            signature = signature,
+        )
+    }
+}
+
+fn parse_pubkeys(pubkeys: &[String]) -> anyhow::Result<Vec<Pubkey>> {
+    pubkeys
+        .iter()
+        .map(|x| Pubkey::from_str(x).with_context(|| format!("parse pubkey {}", x)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_lite_rpc_core::encoding::BASE64;
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+    use solana_sdk::message::v0::MessageAddressTableLookup;
+    use solana_sdk::message::{v0, Message, MessageHeader};
+    use solana_sdk::system_instruction;
+
+    fn create_tx_info() -> TransactionInfo {
+        let lookup = MessageAddressTableLookup {
+            account_key: Pubkey::new_unique(),
+            writable_indexes: vec![0, 3],
+            readonly_indexes: vec![1],
+        };
+        let message = VersionedMessage::V0(v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            recent_blockhash: Hash::new_unique(),
+            address_table_lookups: vec![lookup.clone()],
+            ..v0::Message::default()
+        });
+        TransactionInfo {
+            signature: Signature::new_unique(),
+            is_vote: true,
+            err: Some(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(42),
+            )),
+            cu_requested: Some(200_000),
+            prioritization_fees: Some(5000),
+            cu_consumed: Some(1234),
+            recent_blockhash: *message.recent_blockhash(),
+            message,
+            // static keys followed by the accounts loaded from the lookup table
+            writable_accounts: vec![
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            ],
+            readable_accounts: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            address_lookup_tables: vec![lookup],
+        }
+    }
+
+    fn round_trip(tx_info: &TransactionInfo) -> TransactionInfo {
+        PostgresTransaction::new(tx_info, 5050505, 7)
+            .to_transaction_info()
+            .unwrap()
+    }
+
+    #[test]
+    fn corrupt_transaction_is_an_error() {
+        let tx_info = create_tx_info();
+
+        let corrupt_message = PostgresTransaction {
+            message: vec![0xff; 3],
+            ..PostgresTransaction::new(&tx_info, 5050505, 7)
+        };
+        assert!(corrupt_message.to_transaction_info().is_err());
+
+        let corrupt_pubkey = PostgresTransaction {
+            writable_accounts: vec!["not a pubkey".to_string()],
+            ..PostgresTransaction::new(&tx_info, 5050505, 7)
+        };
+        assert!(corrupt_pubkey.to_transaction_info().is_err());
+
+        let corrupt_blockhash = PostgresTransaction {
+            recent_blockhash: "0".to_string(),
+            ..PostgresTransaction::new(&tx_info, 5050505, 7)
+        };
+        assert!(corrupt_blockhash.to_transaction_info().is_err());
+    }
+
+    #[test]
+    fn round_trip_signature_and_message() {
+        let tx_info = create_tx_info();
+        let restored = round_trip(&tx_info);
+        assert_eq!(restored.signature, tx_info.signature);
+        assert_eq!(restored.message, tx_info.message);
+        assert_eq!(restored.recent_blockhash, tx_info.recent_blockhash);
+    }
+
+    #[test]
+    fn round_trip_err() {
+        let tx_info = create_tx_info();
+        assert_eq!(round_trip(&tx_info).err, tx_info.err);
+
+        let tx_info = TransactionInfo {
+            err: None,
+            ..create_tx_info()
+        };
+        assert_eq!(round_trip(&tx_info).err, None);
+    }
+
+    #[test]
+    fn round_trip_compute_units() {
+        let tx_info = create_tx_info();
+        let restored = round_trip(&tx_info);
+        assert_eq!(restored.cu_requested, tx_info.cu_requested);
+        assert_eq!(restored.prioritization_fees, tx_info.prioritization_fees);
+        assert_eq!(restored.cu_consumed, tx_info.cu_consumed);
+    }
+
+    #[test]
+    fn round_trip_is_vote() {
+        let tx_info = create_tx_info();
+        assert!(round_trip(&tx_info).is_vote);
+
+        let tx_info = TransactionInfo {
+            is_vote: false,
+            ..create_tx_info()
+        };
+        assert!(!round_trip(&tx_info).is_vote);
+    }
+
+    #[test]
+    fn round_trip_accounts() {
+        let tx_info = create_tx_info();
+        let restored = round_trip(&tx_info);
+        // order matters as well
+        assert_eq!(restored.writable_accounts, tx_info.writable_accounts);
+        assert_eq!(restored.readable_accounts, tx_info.readable_accounts);
+    }
+
+    #[test]
+    fn round_trip_address_lookup_tables() {
+        let tx_info = create_tx_info();
+        assert_eq!(
+            round_trip(&tx_info).address_lookup_tables,
+            tx_info.address_lookup_tables
+        );
+    }
+
+    #[test]
+    fn keeps_position_in_block() {
+        let tx_info = create_tx_info();
+        let postgres_transaction = PostgresTransaction::new(&tx_info, 5050505, 7);
+        assert_eq!(postgres_transaction.slot, 5050505);
+        assert_eq!(postgres_transaction.tx_index, 7);
+    }
+
+    #[test]
+    fn round_trip_compressed() {
+        let tx_info = create_tx_info();
+        let postgres_transaction = PostgresTransaction::new(&tx_info, 5050505, 7);
+
+        for compression in [BinaryCompression::None, BinaryCompression::Zstd] {
+            let stored_message = compression.compress(&postgres_transaction.message);
+            let stored_err = compression.compress(postgres_transaction.err.as_ref().unwrap());
+            let restored = PostgresTransaction {
+                message: compression.decompress(&stored_message).unwrap(),
+                err: Some(compression.decompress(&stored_err).unwrap()),
+                ..PostgresTransaction::new(&tx_info, 5050505, 7)
+            }
+            .to_transaction_info()
+            .unwrap();
+            assert_eq!(restored.message, tx_info.message);
+            assert_eq!(restored.err, tx_info.err);
+        }
+    }
+
+    // compares the legacy base64 text columns with bytea and zstd compressed bytea
+    #[test]
+    fn compare_storage_formats() {
+        const NB_TRANSACTIONS: usize = 2000;
+        let transactions = (0..NB_TRANSACTIONS)
+            .map(|i| PostgresTransaction::new(&create_realistic_tx_info(i), 5050505, i))
+            .collect::<Vec<_>>();
+
+        let started = Instant::now();
+        let legacy_bytes: usize = transactions
+            .iter()
+            .map(|tx| {
+                BASE64.encode(&tx.message).len()
+                    + tx.err.as_ref().map(|x| BASE64.encode(x).len()).unwrap_or(0)
+            })
+            .sum();
+        let legacy_elapsed = started.elapsed();
+
+        let mut format_bytes = vec![];
+        for compression in [BinaryCompression::None, BinaryCompression::Zstd] {
+            let started = Instant::now();
+            let mut total_bytes = 0;
+            for tx in &transactions {
+                let message = compression.compress(&tx.message);
+                let err = tx.err.as_ref().map(|x| compression.compress(x));
+                total_bytes += message.len() + err.as_ref().map(|x| x.len()).unwrap_or(0);
+
+                assert_eq!(compression.decompress(&message).unwrap(), tx.message);
+                if let (Some(stored), Some(original)) = (&err, &tx.err) {
+                    assert_eq!(&compression.decompress(stored).unwrap(), original);
+                }
+            }
+            log::info!(
+                "{}: {} bytes for {} transactions, encoded and decoded in {:.2}ms",
+                compression,
+                total_bytes,
+                NB_TRANSACTIONS,
+                started.elapsed().as_secs_f64() * 1000.0
+            );
+            format_bytes.push(total_bytes);
+        }
+        log::info!(
+            "base64 text: {} bytes for {} transactions, encoded in {:.2}ms",
+            legacy_bytes,
+            NB_TRANSACTIONS,
+            legacy_elapsed.as_secs_f64() * 1000.0
+        );
+
+        let (raw_bytes, zstd_bytes) = (format_bytes[0], format_bytes[1]);
+        // base64 inflates by 4/3
+        assert!(raw_bytes * 4 <= legacy_bytes * 3 + 4 * NB_TRANSACTIONS);
+        assert!(raw_bytes < legacy_bytes);
+        // at most one marker byte per value
+        assert!(zstd_bytes <= raw_bytes + 2 * NB_TRANSACTIONS);
+    }
+
+    // transfer with compute budget and memo, every 10th transaction failed
+    fn create_realistic_tx_info(i: usize) -> TransactionInfo {
+        let payer = Pubkey::new_unique();
+        let memo_program = Pubkey::new_unique();
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(10_000),
+            system_instruction::transfer(&payer, &Pubkey::new_unique(), 1_000_000),
+            Instruction::new_with_bytes(
+                memo_program,
+                format!("lite-rpc memo number {:08}", i).as_bytes(),
+                vec![AccountMeta::new_readonly(payer, true)],
+            ),
+        ];
+        let message = VersionedMessage::Legacy(Message::new(&instructions, Some(&payer)));
+        TransactionInfo {
+            signature: Signature::new_unique(),
+            is_vote: false,
+            err: (i % 10 == 0).then_some(TransactionError::InstructionError(
+                2,
+                InstructionError::InsufficientFunds,
+            )),
+            cu_requested: Some(200_000),
+            prioritization_fees: Some(10_000),
+            cu_consumed: Some(450),
+            recent_blockhash: *message.recent_blockhash(),
+            writable_accounts: vec![],
+            readable_accounts: vec![],
+            message,
+            address_lookup_tables: vec![],
+        }
+    }
+
+    #[test]
+    fn query_statement_orders_by_position() {
+        let statement = PostgresTransaction::build_query_statement(EpochRef::new(644), 278_200_000);
+        assert!(statement.contains("WHERE slot = 278200000"));
+        assert!(statement.contains("ORDER BY tx_index"));
+    }
 }
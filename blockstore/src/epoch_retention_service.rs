@@ -0,0 +1,244 @@
+use crate::block_stores::faithful_history::faithful_block_store::FaithfulBlockStore;
+use crate::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use crate::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use log::{debug, error, info, warn};
+use prometheus::core::GenericGauge;
+use prometheus::{opts, register_int_counter, register_int_gauge, IntCounter};
+use solana_lite_rpc_core::structures::epoch::EpochRef;
+use solana_lite_rpc_core::AnyhowJoinHandle;
+use std::sync::Arc;
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref BLOCKSTORE_SIZE_BYTES: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_size_bytes", "On-disk size of all epoch schemas in the blockstore")).unwrap();
+    static ref BLOCKSTORE_EPOCHS: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_epochs", "Number of epoch schemas in the blockstore")).unwrap();
+    static ref BLOCKSTORE_LAST_PRUNED_EPOCH: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_blockstore_last_pruned_epoch", "Last epoch dropped from the blockstore")).unwrap();
+    static ref BLOCKSTORE_PRUNED_EPOCHS: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_pruned_epochs", "Number of epoch schemas dropped from the blockstore")).unwrap();
+    static ref BLOCKSTORE_PRUNED_BYTES: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_pruned_bytes", "Bytes freed by dropping epoch schemas from the blockstore")).unwrap();
+    static ref BLOCKSTORE_PRUNE_DEFERRED: IntCounter =
+    register_int_counter!(opts!("literpc_blockstore_prune_deferred", "Number of times pruning an epoch was deferred because the archive does not cover it")).unwrap();
+}
+
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpochRetentionPolicy {
+    // number of most recent epochs to keep
+    pub keep_epochs: Option<u64>,
+    // drop oldest epochs until all epoch schemas fit
+    pub max_disk_bytes: Option<u64>,
+}
+
+impl EpochRetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.keep_epochs.is_some() || self.max_disk_bytes.is_some()
+    }
+
+    /// epochs to drop (ascending); the newest epoch with data is never selected
+    pub fn select_epochs_to_prune(
+        &self,
+        schema_sizes: &[(EpochRef, u64)],
+        newest_epoch: EpochRef,
+    ) -> Vec<EpochRef> {
+        let mut candidates = schema_sizes
+            .iter()
+            .filter(|(epoch, _)| *epoch < newest_epoch)
+            .copied()
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(epoch, _)| *epoch);
+
+        let mut total_bytes: u64 = schema_sizes.iter().map(|(_, size)| size).sum();
+        let mut selected = vec![];
+        for (epoch, size) in candidates {
+            let outside_kept_epochs = self
+                .keep_epochs
+                .map(|keep| epoch.get_epoch() + keep.max(1) <= newest_epoch.get_epoch())
+                .unwrap_or(false);
+            let over_budget = self
+                .max_disk_bytes
+                .map(|max_bytes| total_bytes > max_bytes)
+                .unwrap_or(false);
+            if !outside_kept_epochs && !over_budget {
+                break;
+            }
+            selected.push(epoch);
+            total_bytes = total_bytes.saturating_sub(size);
+        }
+        selected
+    }
+}
+
+/// drops old epoch schemas from the blockstore once the archive serves them
+pub struct EpochRetentionService {
+    block_storage: PostgresBlockStore,
+    block_storage_query: PostgresQueryBlockStore,
+    faithful_block_storage: Option<Arc<FaithfulBlockStore>>,
+    policy: EpochRetentionPolicy,
+}
+
+impl EpochRetentionService {
+    pub fn new(
+        block_storage: PostgresBlockStore,
+        block_storage_query: PostgresQueryBlockStore,
+        faithful_block_storage: Option<Arc<FaithfulBlockStore>>,
+        policy: EpochRetentionPolicy,
+    ) -> Self {
+        Self {
+            block_storage,
+            block_storage_query,
+            faithful_block_storage,
+            policy,
+        }
+    }
+
+    pub fn start(self) -> AnyhowJoinHandle {
+        tokio::spawn(async move {
+            info!("Starting blockstore epoch retention with {:?}", self.policy);
+            if self.faithful_block_storage.is_none() {
+                warn!("No faithful_history archive configured - epoch schemas will not be pruned");
+            }
+
+            let mut interval = tokio::time::interval(RETENTION_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(err) = self.prune().await {
+                    error!("Failed to prune blockstore epochs: {:?}", err);
+                }
+            }
+        })
+    }
+
+    // returns the dropped epochs
+    pub async fn prune(&self) -> anyhow::Result<Vec<EpochRef>> {
+        let schema_sizes = self.block_storage.get_epoch_schema_sizes().await?;
+        BLOCKSTORE_EPOCHS.set(schema_sizes.len() as i64);
+        BLOCKSTORE_SIZE_BYTES.set(schema_sizes.iter().map(|(_, size)| *size as i64).sum());
+
+        let slot_range_by_epoch = self.block_storage_query.get_slot_range_by_epoch().await;
+        let Some(newest_epoch) = slot_range_by_epoch.keys().max().copied() else {
+            debug!("Blockstore is empty - nothing to prune");
+            return Ok(vec![]);
+        };
+
+        let to_prune = self
+            .policy
+            .select_epochs_to_prune(&schema_sizes, newest_epoch);
+        if to_prune.is_empty() {
+            return Ok(vec![]);
+        }
+        let Some(faithful_block_storage) = &self.faithful_block_storage else {
+            debug!(
+                "Retention policy selects epochs {:?} but there is no archive to serve them",
+                to_prune
+            );
+            BLOCKSTORE_PRUNE_DEFERRED.inc();
+            return Ok(vec![]);
+        };
+
+        let mut pruned = vec![];
+        for epoch in to_prune {
+            // empty schemas can always be dropped
+            if let Some(slot_range) = slot_range_by_epoch.get(&epoch) {
+                if !faithful_block_storage.covers_slot_range(slot_range).await {
+                    // keep the blockstore contiguous; newer epochs are kept as well
+                    warn!(
+                        "Archive does not cover epoch {} (slots {:?}) yet - defer pruning",
+                        epoch, slot_range
+                    );
+                    BLOCKSTORE_PRUNE_DEFERRED.inc();
+                    break;
+                }
+            }
+
+            let size = schema_sizes
+                .iter()
+                .find(|(schema_epoch, _)| *schema_epoch == epoch)
+                .map(|(_, size)| *size)
+                .unwrap_or_default();
+            self.block_storage.drop_epoch_schema(epoch).await?;
+            info!("Pruned epoch {} from blockstore ({} bytes)", epoch, size);
+
+            BLOCKSTORE_PRUNED_EPOCHS.inc();
+            BLOCKSTORE_PRUNED_BYTES.inc_by(size);
+            BLOCKSTORE_LAST_PRUNED_EPOCH.set(epoch.get_epoch() as i64);
+            BLOCKSTORE_EPOCHS.dec();
+            BLOCKSTORE_SIZE_BYTES.sub(size as i64);
+            pruned.push(epoch);
+        }
+
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema_sizes(epochs: &[u64], size: u64) -> Vec<(EpochRef, u64)> {
+        epochs
+            .iter()
+            .map(|epoch| (EpochRef::new(*epoch), size))
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_epochs() {
+        let policy = EpochRetentionPolicy {
+            keep_epochs: Some(2),
+            max_disk_bytes: None,
+        };
+        // 603 is the prepared next epoch
+        let sizes = schema_sizes(&[598, 599, 600, 601, 602, 603], 100);
+        assert_eq!(
+            policy.select_epochs_to_prune(&sizes, EpochRef::new(602)),
+            vec![EpochRef::new(598), EpochRef::new(599), EpochRef::new(600)]
+        );
+    }
+
+    #[test]
+    fn test_never_prune_newest_epoch() {
+        let policy = EpochRetentionPolicy {
+            keep_epochs: Some(0),
+            max_disk_bytes: Some(0),
+        };
+        let sizes = schema_sizes(&[600, 601, 602], 100);
+        assert_eq!(
+            policy.select_epochs_to_prune(&sizes, EpochRef::new(601)),
+            vec![EpochRef::new(600)]
+        );
+    }
+
+    #[test]
+    fn test_max_disk_bytes() {
+        let policy = EpochRetentionPolicy {
+            keep_epochs: None,
+            max_disk_bytes: Some(250),
+        };
+        let sizes = schema_sizes(&[600, 601, 602, 603], 100);
+        assert_eq!(
+            policy.select_epochs_to_prune(&sizes, EpochRef::new(603)),
+            vec![EpochRef::new(600), EpochRef::new(601)]
+        );
+
+        // within budget
+        let sizes = schema_sizes(&[602, 603], 100);
+        assert!(policy
+            .select_epochs_to_prune(&sizes, EpochRef::new(603))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_disabled_policy() {
+        let policy = EpochRetentionPolicy::default();
+        assert!(!policy.is_enabled());
+        let sizes = schema_sizes(&[600, 601, 602], u64::MAX / 4);
+        assert!(policy
+            .select_epochs_to_prune(&sizes, EpochRef::new(602))
+            .is_empty());
+    }
+}
@@ -1,15 +1,254 @@
+use crate::block_stores::block_cache::BlockCache;
+use crate::block_stores::block_storage_interface::BlockNotFound;
+use crate::block_stores::inmemory_block_store::InmemoryBlockStore;
+use crate::block_stores::multiple_strategy_block_store::MultipleStrategyBlockStorage;
+use anyhow::bail;
+use log::{debug, warn};
+use solana_lite_rpc_core::commitment_utils::Commitment;
+use solana_lite_rpc_core::structures::produced_block::{ConfirmedTransactionInfo, ProducedBlock};
+use solana_lite_rpc_core::types::BlockStream;
+use solana_lite_rpc_core::AnyhowJoinHandle;
+use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use solana_transaction_status::TransactionConfirmationStatus;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+// keep blocks of 1000 slots in memory by default
+pub const DEFAULT_NB_RECENT_SLOTS_TO_CACHE: u64 = 1000;
+
+/// serves blocks from the recent blocks seen on the block stream
+/// and falls back to the persistent block storage (if configured)
+#[derive(Clone)]
 pub struct History {
-    // Placeholder
+    recent_blocks: InmemoryBlockStore,
+    block_storage: Option<Arc<MultipleStrategyBlockStorage>>,
+    // read cache in front of the block storage; invalidated when blocks progress
+    block_cache: Option<BlockCache>,
+    // highest slot seen with commitment level finalized
+    finalized_slot: Arc<AtomicU64>,
 }
 
 impl History {
-    pub fn new() -> Self {
-        History {}
+    pub fn new(
+        block_storage: Option<MultipleStrategyBlockStorage>,
+        block_cache: Option<BlockCache>,
+        number_of_recent_slots: u64,
+    ) -> Self {
+        History {
+            recent_blocks: InmemoryBlockStore::new(number_of_recent_slots),
+            block_storage: block_storage.map(Arc::new),
+            block_cache,
+            finalized_slot: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn start_listening(&self, blocks_notifier: BlockStream) -> AnyhowJoinHandle {
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut blocks_notifier = blocks_notifier;
+            loop {
+                match blocks_notifier.recv().await {
+                    Ok(block) => {
+                        this.add_recent_block(&block);
+                    }
+                    Err(RecvError::Lagged(missed_blocks)) => {
+                        warn!(
+                            "History could not keep up with block stream - missed {} blocks",
+                            missed_blocks
+                        );
+                    }
+                    Err(RecvError::Closed) => {
+                        bail!("Block stream has been closed - abort");
+                    }
+                }
+            }
+        })
+    }
+
+    pub fn add_recent_block(&self, block: &ProducedBlock) {
+        if block.commitment_config.commitment == CommitmentLevel::Finalized {
+            self.finalized_slot.fetch_max(block.slot, Ordering::Relaxed);
+        }
+        if let Some(block_cache) = &self.block_cache {
+            block_cache.on_block_commitment(block);
+        }
+        self.recent_blocks.save(block);
+    }
+
+    pub fn get_finalized_slot(&self) -> Slot {
+        self.finalized_slot.load(Ordering::Relaxed)
+    }
+
+    pub fn block_storage(&self) -> Option<&MultipleStrategyBlockStorage> {
+        self.block_storage.as_deref()
+    }
+
+    // None if the block is not (yet) known with the requested commitment level
+    pub async fn get_block(
+        &self,
+        slot: Slot,
+        commitment_config: CommitmentConfig,
+    ) -> anyhow::Result<Option<ProducedBlock>> {
+        let requested_commitment = Commitment::from(commitment_config);
+
+        if let Some(block) = self.recent_blocks.get(slot) {
+            if Commitment::from(block.commitment_config) >= requested_commitment {
+                return Ok(Some(block));
+            }
+        }
+
+        let Some(block_storage) = &self.block_storage else {
+            return Ok(None);
+        };
+
+        let finalized_slot = self.get_finalized_slot();
+        if requested_commitment == Commitment::Finalized && slot > finalized_slot {
+            debug!(
+                "Block {} is not finalized yet (finalized slot {})",
+                slot, finalized_slot
+            );
+            return Ok(None);
+        }
+
+        match block_storage.query_block(slot).await {
+            Ok(block_storage_data) => {
+                debug!(
+                    "Block {} served from {:?}",
+                    slot, block_storage_data.result_source
+                );
+                // the block storage only contains blocks with commitment level confirmed or higher
+                let block = if slot <= finalized_slot {
+                    block_storage_data.block.to_finalized_block()
+                } else {
+                    block_storage_data.block
+                };
+                Ok(Some(block))
+            }
+            Err(err) if BlockNotFound::is_block_not_found(&err) => {
+                debug!("Block {} not found in block storage: {:?}", slot, err);
+                Ok(None)
+            }
+            Err(err) => Err(err.context(format!("query block {} from block storage", slot))),
+        }
+    }
+}
+
+impl History {
+    // slots of the blocks in range known with the requested commitment level (ascending), at most limit slots
+    pub async fn get_blocks(
+        &self,
+        slot_range: RangeInclusive<Slot>,
+        limit: Option<usize>,
+        commitment_config: CommitmentConfig,
+    ) -> anyhow::Result<Vec<Slot>> {
+        let requested_commitment = Commitment::from(commitment_config);
+
+        // blocks beyond the finalized slot are not finalized yet
+        let slot_range = if requested_commitment == Commitment::Finalized {
+            *slot_range.start()..=(*slot_range.end()).min(self.get_finalized_slot())
+        } else {
+            slot_range
+        };
+        if slot_range.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut slots: BTreeSet<Slot> = self
+            .recent_blocks
+            .get_slots(&slot_range, requested_commitment)
+            .into_iter()
+            .collect();
+
+        if let Some(block_storage) = &self.block_storage {
+            // the block storage only contains blocks with commitment level confirmed or higher
+            slots.extend(block_storage.query_slots(slot_range, limit).await?);
+        }
+
+        Ok(slots
+            .into_iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+}
+
+impl History {
+    // None if the transaction is not (yet) known with the requested commitment level
+    pub async fn get_transaction(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+    ) -> anyhow::Result<Option<ConfirmedTransactionInfo>> {
+        let requested_commitment = Commitment::from(commitment_config);
+
+        if let Some(tx) = self.recent_blocks.get_transaction(signature) {
+            if Commitment::from(tx.commitment_config) >= requested_commitment {
+                return Ok(Some(tx));
+            }
+        }
+
+        let Some(block_storage) = &self.block_storage else {
+            return Ok(None);
+        };
+
+        let finalized_slot = self.get_finalized_slot();
+        let Some(mut tx) = block_storage.query_transaction(signature).await? else {
+            return Ok(None);
+        };
+        // the block storage only contains blocks with commitment level confirmed or higher
+        if tx.slot <= finalized_slot {
+            tx.commitment_config = CommitmentConfig::finalized();
+        } else if requested_commitment == Commitment::Finalized {
+            return Ok(None);
+        }
+
+        Ok(Some(tx))
+    }
+
+    // newest first; None if no block storage is configured
+    pub async fn get_signatures_for_address(
+        &self,
+        account: Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        commitment_config: CommitmentConfig,
+    ) -> anyhow::Result<Option<Vec<RpcConfirmedTransactionStatusWithSignature>>> {
+        let Some(block_storage) = &self.block_storage else {
+            return Ok(None);
+        };
+
+        let finalized_slot = self.get_finalized_slot();
+        // the block storage only contains blocks with commitment level confirmed or higher
+        let max_slot = if Commitment::from(commitment_config) == Commitment::Finalized {
+            Some(finalized_slot)
+        } else {
+            None
+        };
+
+        let mut signatures = block_storage
+            .query_signatures_for_address(account, before, until, max_slot, limit)
+            .await?;
+        for signature in signatures.iter_mut() {
+            signature.confirmation_status = Some(if signature.slot <= finalized_slot {
+                TransactionConfirmationStatus::Finalized
+            } else {
+                TransactionConfirmationStatus::Confirmed
+            });
+        }
+
+        Ok(Some(signatures))
     }
 }
 
 impl Default for History {
     fn default() -> Self {
-        Self::new()
+        Self::new(None, None, DEFAULT_NB_RECENT_SLOTS_TO_CACHE)
     }
 }
@@ -1,2 +1,5 @@
+pub mod block_importer;
+pub mod block_persistence_service;
 pub mod block_stores;
+pub mod epoch_retention_service;
 pub mod history;
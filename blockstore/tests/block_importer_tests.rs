@@ -0,0 +1,145 @@
+use solana_lite_rpc_blockstore::block_importer::{BlockFetcher, BlockImporter, ImportCheckpoint};
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig;
+use solana_lite_rpc_core::structures::epoch::EpochCache;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::slot_history::Slot;
+use stand_in_rpc::{create_test_block, start_stand_in_rpc};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+mod stand_in_rpc;
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "blockstore-import-{}-{}.checkpoint",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn test_fetch_blocks_from_rpc() {
+    let blocks = vec![
+        create_test_block(100),
+        create_test_block(101),
+        create_test_block(103),
+        create_test_block(110),
+    ];
+    let (url, server) = start_stand_in_rpc(blocks.clone(), HashSet::from([101])).await;
+    let fetcher = BlockFetcher::new(Arc::new(RpcClient::new(url)), 2);
+
+    let slots = fetcher.get_block_slots(&(100..=105)).await.unwrap();
+    assert_eq!(slots, vec![100, 101, 103]);
+
+    // slot 101 fails once and is retried
+    let fetched = fetcher.fetch_blocks(&slots).await.unwrap();
+    assert_eq!(fetched.len(), 3);
+    for (fetched, expected) in fetched.iter().zip(blocks.iter()) {
+        assert_eq!(fetched.slot, expected.slot);
+        assert_eq!(fetched.blockhash, expected.blockhash);
+        assert_eq!(fetched.previous_blockhash, expected.previous_blockhash);
+        assert_eq!(fetched.parent_slot, expected.parent_slot);
+        assert_eq!(fetched.block_height, expected.block_height);
+        assert_eq!(fetched.block_time, expected.block_time);
+        assert_eq!(fetched.rewards, expected.rewards);
+        assert_eq!(fetched.commitment_config, CommitmentConfig::finalized());
+    }
+
+    // skipped slot
+    assert!(fetcher.fetch_block(102).await.is_err());
+
+    server.stop().unwrap();
+}
+
+#[test]
+fn test_checkpoint_save_and_load() {
+    let path = checkpoint_path("save-and-load");
+    assert_eq!(ImportCheckpoint::load(&path).unwrap(), None);
+
+    let mut checkpoint = ImportCheckpoint::new(&(1000..=1999));
+    assert!(checkpoint.is_for_range(&(1000..=1999)));
+    assert!(!checkpoint.is_for_range(&(1000..=2999)));
+    assert!(!checkpoint.is_complete());
+
+    checkpoint.next_slot = 1500;
+    checkpoint.save(&path).unwrap();
+    assert_eq!(
+        ImportCheckpoint::load(&path).unwrap(),
+        Some(checkpoint.clone())
+    );
+
+    checkpoint.next_slot = 2000;
+    checkpoint.save(&path).unwrap();
+    let loaded = ImportCheckpoint::load(&path).unwrap().unwrap();
+    assert!(loaded.is_complete());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_import_and_resume() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    // epoch 1 with 1000 slots per epoch
+    let slots: Vec<Slot> = (1200..1300).filter(|slot| slot % 7 != 0).collect();
+    let blocks = slots.iter().map(|slot| create_test_block(*slot)).collect();
+    let (url, server) = start_stand_in_rpc(blocks, HashSet::new()).await;
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let create_importer = |checkpoint_path: PathBuf| {
+        let url = url.clone();
+        let epoch_cache = epoch_cache.clone();
+        let pg_session_config = pg_session_config.clone();
+        async move {
+            BlockImporter::new(
+                BlockFetcher::new(Arc::new(RpcClient::new(url)), 4),
+                PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await,
+                PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config).await,
+                epoch_cache,
+                checkpoint_path,
+                16,
+            )
+        }
+    };
+
+    // first run crashes after importing slots up to 1249
+    let first_half_path = checkpoint_path("import-first-half");
+    create_importer(first_half_path.clone())
+        .await
+        .import(1200..=1249)
+        .await
+        .unwrap();
+    std::fs::remove_file(&first_half_path).unwrap();
+
+    let path = checkpoint_path("import");
+    let mut checkpoint = ImportCheckpoint::new(&(1200..=1299));
+    checkpoint.next_slot = 1250;
+    checkpoint.save(&path).unwrap();
+
+    // resumed run only imports the remaining slots
+    let importer = create_importer(path.clone()).await;
+    let stats = importer.import(1200..=1299).await.unwrap();
+    assert_eq!(
+        stats.blocks_imported as usize,
+        slots.iter().filter(|slot| **slot >= 1250).count()
+    );
+    assert!(ImportCheckpoint::load(&path)
+        .unwrap()
+        .unwrap()
+        .is_complete());
+    importer.verify(&(1200..=1299)).await.unwrap();
+
+    // a different range must not reuse the checkpoint
+    assert!(importer.import(1200..=1399).await.is_err());
+
+    std::fs::remove_file(&path).unwrap();
+    server.stop().unwrap();
+}
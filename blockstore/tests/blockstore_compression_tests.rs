@@ -0,0 +1,267 @@
+mod common;
+
+use common::{build_create_legacy_schema_statement, save_legacy_block};
+use log::info;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::{
+    BinaryCompression, PostgresSession, PostgresSessionConfig,
+};
+use solana_lite_rpc_core::structures::epoch::{EpochCache, EpochRef};
+use solana_lite_rpc_core::structures::produced_block::{
+    ProducedBlock, ProducedBlockInner, TransactionInfo,
+};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::message::{Message, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::TransactionError;
+use std::time::Instant;
+
+const NB_BLOCKS: u64 = 20;
+const NB_TRANSACTIONS_PER_BLOCK: usize = 500;
+// 1000 slots per epoch
+const LEGACY_EPOCH: u64 = 9;
+
+// accounts and programs recur across transactions like on mainnet
+struct AccountPool {
+    payers: Vec<Pubkey>,
+    destinations: Vec<Pubkey>,
+    oracle_program: Pubkey,
+    oracle_accounts: Vec<Pubkey>,
+}
+
+impl AccountPool {
+    fn new() -> Self {
+        Self {
+            payers: (0..64).map(|_| Pubkey::new_unique()).collect(),
+            destinations: (0..256).map(|_| Pubkey::new_unique()).collect(),
+            oracle_program: Pubkey::new_unique(),
+            oracle_accounts: (0..16).map(|_| Pubkey::new_unique()).collect(),
+        }
+    }
+}
+
+// every other transaction is an oracle price update with a mostly zero payload, every 10th failed
+fn create_test_transaction(
+    pool: &AccountPool,
+    recent_blockhash: Hash,
+    i: usize,
+) -> TransactionInfo {
+    let payer = pool.payers[i % pool.payers.len()];
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+        ComputeBudgetInstruction::set_compute_unit_price(10_000),
+    ];
+    let (writable_accounts, readable_accounts) = if i % 2 == 0 {
+        let destination = pool.destinations[(i * 7) % pool.destinations.len()];
+        instructions.push(system_instruction::transfer(
+            &payer,
+            &destination,
+            1_000_000 + i as u64,
+        ));
+        (vec![payer, destination], vec![])
+    } else {
+        let oracle_account = pool.oracle_accounts[i % pool.oracle_accounts.len()];
+        let mut payload = vec![0u8; 256];
+        payload[..8].copy_from_slice(&(42_000_000 + i as i64).to_le_bytes());
+        payload[8..16].copy_from_slice(&(1_700_000_000 + i as u64).to_le_bytes());
+        instructions.push(Instruction::new_with_bytes(
+            pool.oracle_program,
+            &payload,
+            vec![
+                AccountMeta::new(oracle_account, false),
+                AccountMeta::new_readonly(payer, true),
+            ],
+        ));
+        (vec![payer, oracle_account], vec![pool.oracle_program])
+    };
+    let message = VersionedMessage::Legacy(Message::new_with_blockhash(
+        &instructions,
+        Some(&payer),
+        &recent_blockhash,
+    ));
+    TransactionInfo {
+        signature: Signature::new_unique(),
+        is_vote: false,
+        err: (i % 10 == 0).then_some(TransactionError::InstructionError(
+            2,
+            InstructionError::InsufficientFunds,
+        )),
+        cu_requested: Some(200_000),
+        prioritization_fees: Some(10_000),
+        cu_consumed: Some(450),
+        recent_blockhash,
+        writable_accounts,
+        readable_accounts,
+        message,
+        address_lookup_tables: vec![],
+    }
+}
+
+fn create_test_block(pool: &AccountPool, slot: Slot) -> ProducedBlock {
+    // the transactions of a block reference few recent blockhashes
+    let recent_blockhashes = [Hash::new_unique(), Hash::new_unique()];
+    let inner = ProducedBlockInner {
+        block_height: slot,
+        blockhash: Hash::new_unique(),
+        previous_blockhash: Hash::new_unique(),
+        parent_slot: slot - 1,
+        transactions: (0..NB_TRANSACTIONS_PER_BLOCK)
+            .map(|i| create_test_transaction(pool, recent_blockhashes[i % 2], i))
+            .collect(),
+        block_time: 1_700_000_000 + slot,
+        leader_id: None,
+        slot,
+        rewards: None,
+    };
+    ProducedBlock::new(inner, CommitmentConfig::confirmed())
+}
+
+// bytes of the message and err columns and of the whole table including indexes
+async fn query_transaction_sizes(session: &PostgresSession, epoch: u64) -> (i64, i64) {
+    let schema = format!("rpc2a_epoch_{}", epoch);
+    let statement = format!(
+        r#"
+            SELECT
+                (SELECT sum(pg_column_size(message)) + coalesce(sum(pg_column_size(err)), 0)
+                    FROM {schema}.transaction_blockdata)::bigint AS column_bytes,
+                pg_total_relation_size('{schema}.transaction_blockdata') AS table_bytes
+        "#,
+    );
+    let row = session.query_one(&statement, &[]).await.unwrap();
+    (row.get("column_bytes"), row.get("table_bytes"))
+}
+
+// writes the same blocks with the legacy text layout and each compression and compares the stored sizes
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_compare_compression_size_and_throughput() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage_query =
+        PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let session = PostgresSession::new(pg_session_config.clone())
+        .await
+        .unwrap();
+
+    let pool = AccountPool::new();
+    let blocks = (0..NB_BLOCKS)
+        .map(|i| create_test_block(&pool, 5000 + i))
+        .collect::<Vec<_>>();
+    let nb_transactions = NB_BLOCKS as usize * NB_TRANSACTIONS_PER_BLOCK;
+
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    block_storage
+        .drop_epoch_schema(EpochRef::new(LEGACY_EPOCH))
+        .await
+        .unwrap();
+    session
+        .execute_multiple(&build_create_legacy_schema_statement(LEGACY_EPOCH))
+        .await
+        .unwrap();
+    let started = Instant::now();
+    for block in &blocks {
+        save_legacy_block(&session, LEGACY_EPOCH, block).await;
+    }
+    let elapsed = started.elapsed();
+    let (legacy_column_bytes, legacy_table_bytes) =
+        query_transaction_sizes(&session, LEGACY_EPOCH).await;
+    info!(
+        "legacy base64 text: {} column bytes, {} table bytes for {} transactions, written in {:.2}ms",
+        legacy_column_bytes,
+        legacy_table_bytes,
+        nb_transactions,
+        elapsed.as_secs_f64() * 1000.0
+    );
+    block_storage
+        .drop_epoch_schema(EpochRef::new(LEGACY_EPOCH))
+        .await
+        .unwrap();
+
+    // epoch 5 uncompressed, epoch 7 with zstd
+    let mut column_sizes = vec![];
+    for (compression, epoch) in [(BinaryCompression::None, 5), (BinaryCompression::Zstd, 7)] {
+        let block_storage = PostgresBlockStore::new_with_compression(
+            epoch_cache.clone(),
+            pg_session_config.clone(),
+            compression,
+        )
+        .await;
+        for dropped_epoch in [epoch, epoch + 1] {
+            block_storage
+                .drop_epoch_schema(EpochRef::new(dropped_epoch))
+                .await
+                .unwrap();
+        }
+
+        let first_slot = epoch * 1000;
+        block_storage
+            .prepare_epoch_schema(first_slot)
+            .await
+            .unwrap();
+
+        let started = Instant::now();
+        for (i, block) in blocks.iter().enumerate() {
+            let block = ProducedBlock::new(
+                ProducedBlockInner {
+                    transactions: block.transactions.clone(),
+                    leader_id: None,
+                    blockhash: block.blockhash,
+                    block_height: block.block_height,
+                    slot: first_slot + i as u64,
+                    parent_slot: first_slot + i as u64 - 1,
+                    block_time: block.block_time,
+                    previous_blockhash: block.previous_blockhash,
+                    rewards: None,
+                },
+                block.commitment_config,
+            );
+            block_storage.save_block(&block).await.unwrap();
+        }
+        let elapsed = started.elapsed();
+
+        // round trip
+        let restored = block_storage_query.query_block(first_slot).await.unwrap();
+        assert_eq!(restored.transactions.len(), NB_TRANSACTIONS_PER_BLOCK);
+        for (restored, original) in restored.transactions.iter().zip(&blocks[0].transactions) {
+            assert_eq!(restored.signature, original.signature);
+            assert_eq!(restored.message, original.message);
+            assert_eq!(restored.err, original.err);
+        }
+
+        let (column_bytes, table_bytes) = query_transaction_sizes(&session, epoch).await;
+        info!(
+            "{}: {} column bytes ({:.1}% saved), {} table bytes for {} transactions, written in {:.2}ms ({:.0} tx/s)",
+            compression,
+            column_bytes,
+            100.0 * (1.0 - column_bytes as f64 / legacy_column_bytes as f64),
+            table_bytes,
+            nb_transactions,
+            elapsed.as_secs_f64() * 1000.0,
+            nb_transactions as f64 / elapsed.as_secs_f64()
+        );
+        column_sizes.push(column_bytes);
+
+        for dropped_epoch in [epoch, epoch + 1] {
+            block_storage
+                .drop_epoch_schema(EpochRef::new(dropped_epoch))
+                .await
+                .unwrap();
+        }
+    }
+
+    let (raw_column_bytes, zstd_column_bytes) = (column_sizes[0], column_sizes[1]);
+    // base64 inflates by 4/3
+    assert!(raw_column_bytes * 5 < legacy_column_bytes * 4);
+    // the zero padded payloads compress
+    assert!(zstd_column_bytes < raw_column_bytes);
+}
@@ -0,0 +1,191 @@
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig;
+use solana_lite_rpc_core::structures::epoch::{EpochCache, EpochRef};
+use solana_lite_rpc_core::structures::produced_block::{ProducedBlock, TransactionInfo};
+use solana_lite_rpc_core::test_utils::{self, create_test_tx};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::slot_history::Slot;
+
+fn create_fork_block(
+    slot: Slot,
+    parent_slot: Slot,
+    transactions: Vec<TransactionInfo>,
+) -> ProducedBlock {
+    test_utils::create_fork_block(
+        slot,
+        parent_slot,
+        CommitmentConfig::confirmed(),
+        transactions,
+    )
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_finalized_block_removes_abandoned_fork() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query = PostgresQueryBlockStore::new(epoch_cache, pg_session_config).await;
+
+    for epoch in [9, 10] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(epoch))
+            .await
+            .unwrap();
+    }
+    block_storage.prepare_epoch_schema(9000).await.unwrap();
+
+    //         / 9001 - 9003          (abandoned)
+    // 9000 --
+    //         \ 9002 - 9004          (finalized)
+    let shared_tx = create_test_tx();
+    let abandoned_tx = create_test_tx();
+    let block_9000 = create_fork_block(9000, 8999, vec![create_test_tx()]);
+    let block_9001 = create_fork_block(9001, 9000, vec![create_test_tx()]);
+    let block_9002 = create_fork_block(9002, 9000, vec![create_test_tx()]);
+    let block_9003 = create_fork_block(9003, 9001, vec![abandoned_tx.clone(), shared_tx.clone()]);
+    let block_9004 = create_fork_block(9004, 9002, vec![shared_tx.clone()]);
+    for block in [
+        &block_9000,
+        &block_9001,
+        &block_9002,
+        &block_9003,
+        &block_9004,
+    ] {
+        block_storage.save_block(block).await.unwrap();
+    }
+    assert!(block_storage_query.query_block(9003).await.is_ok());
+
+    block_storage
+        .progress_block_commitment_level(&block_9000.to_finalized_block())
+        .await
+        .unwrap();
+    block_storage
+        .progress_block_commitment_level(&block_9002.to_finalized_block())
+        .await
+        .unwrap();
+    assert!(block_storage_query.query_block(9001).await.is_err());
+    assert!(block_storage_query.query_block(9003).await.is_ok());
+
+    block_storage
+        .progress_block_commitment_level(&block_9004.to_finalized_block())
+        .await
+        .unwrap();
+    assert!(block_storage_query.query_block(9003).await.is_err());
+    assert_eq!(
+        block_storage_query
+            .query_slots(9000..=9010, None)
+            .await
+            .unwrap(),
+        vec![9000, 9002, 9004]
+    );
+
+    assert!(block_storage_query
+        .query_transaction(&abandoned_tx.signature)
+        .await
+        .unwrap()
+        .is_none());
+    // the copy in the block of the abandoned fork is gone
+    let shared = block_storage_query
+        .query_transaction(&shared_tx.signature)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shared.slot, 9004);
+    let signatures = block_storage_query
+        .query_signatures_for_address(abandoned_tx.writable_accounts[0], None, None, None, 10)
+        .await
+        .unwrap();
+    assert!(signatures.is_empty());
+
+    // a different block at the finalized slot is replaced
+    let replacement = create_fork_block(9005, 9004, vec![]);
+    block_storage
+        .save_block(&create_fork_block(9005, 9004, vec![create_test_tx()]))
+        .await
+        .unwrap();
+    block_storage
+        .progress_block_commitment_level(&replacement.to_finalized_block())
+        .await
+        .unwrap();
+    let restored = block_storage_query.query_block(9005).await.unwrap();
+    assert_eq!(restored.blockhash, replacement.blockhash);
+    assert!(restored.transactions.is_empty());
+
+    for epoch in [9, 10] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(epoch))
+            .await
+            .unwrap();
+    }
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_transaction_on_both_forks_survives_finalization() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query = PostgresQueryBlockStore::new(epoch_cache, pg_session_config).await;
+
+    for epoch in [17, 18] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(epoch))
+            .await
+            .unwrap();
+    }
+    block_storage.prepare_epoch_schema(17000).await.unwrap();
+
+    //          / 17001 - 17003       (finalized)
+    // 17000 --
+    //          \ 17002               (abandoned)
+    // the canonical block is saved before the block of the abandoned fork
+    let shared_tx = create_test_tx();
+    let block_17000 = create_fork_block(17000, 16999, vec![create_test_tx()]);
+    let block_17001 = create_fork_block(17001, 17000, vec![shared_tx.clone()]);
+    let block_17002 = create_fork_block(17002, 17000, vec![shared_tx.clone()]);
+    let block_17003 = create_fork_block(17003, 17001, vec![create_test_tx()]);
+    for block in [&block_17000, &block_17001, &block_17002, &block_17003] {
+        block_storage.save_block(block).await.unwrap();
+    }
+
+    for block in [&block_17000, &block_17001, &block_17003] {
+        block_storage
+            .progress_block_commitment_level(&block.to_finalized_block())
+            .await
+            .unwrap();
+    }
+    assert!(block_storage_query.query_block(17002).await.is_err());
+
+    let shared = block_storage_query
+        .query_transaction(&shared_tx.signature)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shared.slot, 17001);
+    let restored = block_storage_query.query_block(17001).await.unwrap();
+    assert_eq!(restored.transactions.len(), 1);
+    assert_eq!(restored.transactions[0].signature, shared_tx.signature);
+
+    let signatures = block_storage_query
+        .query_signatures_for_address(shared_tx.writable_accounts[0], None, None, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].slot, 17001);
+    assert_eq!(signatures[0].signature, shared_tx.signature.to_string());
+
+    for epoch in [17, 18] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(epoch))
+            .await
+            .unwrap();
+    }
+}
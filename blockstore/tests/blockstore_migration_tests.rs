@@ -0,0 +1,144 @@
+mod common;
+
+use common::{build_create_legacy_schema_statement, save_legacy_block};
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::{PostgresSession, PostgresSessionConfig};
+use solana_lite_rpc_core::structures::epoch::{EpochCache, EpochRef};
+use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
+use solana_lite_rpc_core::test_utils::{create_fork_block, create_test_tx};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::slot_history::Slot;
+
+// 1000 slots per epoch
+const LEGACY_EPOCH: u64 = 11;
+const BACKFILL_EPOCH: u64 = 12;
+
+fn create_test_block(slot: Slot) -> ProducedBlock {
+    create_fork_block(
+        slot,
+        slot - 1,
+        CommitmentConfig::confirmed(),
+        vec![create_test_tx(), create_test_tx()],
+    )
+}
+
+// every transaction of the block is found by its payer
+async fn assert_indexed(block_storage_query: &PostgresQueryBlockStore, block: &ProducedBlock) {
+    for tx in &block.transactions {
+        let signatures = block_storage_query
+            .query_signatures_for_address(tx.writable_accounts[0], None, None, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].signature, tx.signature.to_string());
+        assert_eq!(signatures[0].slot, block.slot);
+    }
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_save_block_into_migrated_legacy_schema() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query =
+        PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+
+    let epoch = EpochRef::new(LEGACY_EPOCH);
+    block_storage.drop_epoch_schema(epoch).await.unwrap();
+
+    let session = PostgresSession::new(pg_session_config.clone())
+        .await
+        .unwrap();
+    session
+        .execute_multiple(&build_create_legacy_schema_statement(LEGACY_EPOCH))
+        .await
+        .unwrap();
+
+    assert!(block_storage.migrate_epoch_schema(epoch).await.unwrap());
+    // already on the current schema version
+    assert!(!block_storage.migrate_epoch_schema(epoch).await.unwrap());
+
+    let slot = LEGACY_EPOCH * 1000 + 1;
+    let block = create_test_block(slot);
+    block_storage.save_block(&block).await.unwrap();
+
+    let restored = block_storage_query.query_block(slot).await.unwrap();
+    assert_eq!(restored.blockhash, block.blockhash);
+    assert_eq!(restored.transactions.len(), block.transactions.len());
+    for (restored, original) in restored.transactions.iter().zip(&block.transactions) {
+        assert_eq!(restored.signature, original.signature);
+        assert_eq!(restored.message, original.message);
+    }
+
+    // the account index was added by the migration
+    let account = block.transactions[0].writable_accounts[0];
+    let signatures = block_storage_query
+        .query_signatures_for_address(account, None, None, None, 10)
+        .await
+        .unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(
+        signatures[0].signature,
+        block.transactions[0].signature.to_string()
+    );
+
+    block_storage.drop_epoch_schema(epoch).await.unwrap();
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_migration_backfills_account_index() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query =
+        PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+
+    let epoch = EpochRef::new(BACKFILL_EPOCH);
+    block_storage.drop_epoch_schema(epoch).await.unwrap();
+
+    let session = PostgresSession::new(pg_session_config.clone())
+        .await
+        .unwrap();
+    session
+        .execute_multiple(&build_create_legacy_schema_statement(BACKFILL_EPOCH))
+        .await
+        .unwrap();
+    // written before the account index existed
+    let legacy_block = create_test_block(BACKFILL_EPOCH * 1000 + 1);
+    save_legacy_block(&session, BACKFILL_EPOCH, &legacy_block).await;
+
+    assert!(block_storage.migrate_epoch_schema(epoch).await.unwrap());
+
+    assert_indexed(&block_storage_query, &legacy_block).await;
+
+    // interrupted after the columns were migrated but before the index was complete
+    let schema = format!("rpc2a_epoch_{}", BACKFILL_EPOCH);
+    session
+        .execute_multiple(&format!(
+            "DELETE FROM {schema}.account_transactions; UPDATE {schema}.schema_version SET version = 2;"
+        ))
+        .await
+        .unwrap();
+    let restarted_block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    assert!(restarted_block_storage
+        .migrate_epoch_schema(epoch)
+        .await
+        .unwrap());
+    assert!(!restarted_block_storage
+        .migrate_epoch_schema(epoch)
+        .await
+        .unwrap());
+    assert_indexed(&block_storage_query, &legacy_block).await;
+
+    block_storage.drop_epoch_schema(epoch).await.unwrap();
+}
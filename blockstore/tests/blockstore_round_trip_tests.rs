@@ -0,0 +1,120 @@
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig;
+use solana_lite_rpc_core::structures::epoch::{EpochCache, EpochRef};
+use solana_lite_rpc_core::structures::produced_block::{
+    ProducedBlock, ProducedBlockInner, TransactionInfo,
+};
+use solana_lite_rpc_core::test_utils::{create_test_block_inner, create_test_tx};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::slot_history::Slot;
+use solana_transaction_status::{Reward, RewardType};
+
+// 1000 slots per epoch
+const EPOCH: u64 = 13;
+
+// with compute units so that every column is compared
+fn create_compute_budget_tx() -> TransactionInfo {
+    TransactionInfo {
+        cu_requested: Some(200_000),
+        prioritization_fees: Some(10_000),
+        cu_consumed: Some(450),
+        ..create_test_tx()
+    }
+}
+
+fn create_test_block(slot: Slot, rewards: Option<Vec<Reward>>) -> ProducedBlock {
+    let transactions = (0..5).map(|_| create_compute_budget_tx()).collect();
+    let inner = ProducedBlockInner {
+        leader_id: Some(Pubkey::new_unique().to_string()),
+        rewards,
+        ..create_test_block_inner(slot, slot - 1, transactions)
+    };
+    ProducedBlock::new(inner, CommitmentConfig::confirmed())
+}
+
+// edge values of the reward columns
+fn create_test_rewards() -> Vec<Reward> {
+    vec![
+        Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: 5000,
+            post_balance: u64::MAX,
+            reward_type: Some(RewardType::Fee),
+            commission: None,
+        },
+        Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: -42,
+            post_balance: 0,
+            reward_type: Some(RewardType::Voting),
+            commission: Some(10),
+        },
+    ]
+}
+
+// same checks as the in-memory round trip in postgres_block.rs but through the database
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_save_and_query_block_round_trip() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query =
+        PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+
+    for dropped_epoch in [EPOCH, EPOCH + 1] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(dropped_epoch))
+            .await
+            .unwrap();
+    }
+
+    let first_slot = EPOCH * 1000;
+    block_storage
+        .prepare_epoch_schema(first_slot)
+        .await
+        .unwrap();
+
+    let with_rewards = create_test_block(first_slot + 1, Some(create_test_rewards()));
+    let without_rewards = create_test_block(first_slot + 2, None);
+    for block in [&with_rewards, &without_rewards] {
+        block_storage.save_block(block).await.unwrap();
+    }
+
+    for block in [&with_rewards, &without_rewards] {
+        let restored = block_storage_query.query_block(block.slot).await.unwrap();
+
+        assert_eq!(restored.slot, block.slot);
+        assert_eq!(restored.blockhash, block.blockhash);
+        assert_eq!(restored.block_height, block.block_height);
+        assert_eq!(restored.parent_slot, block.parent_slot);
+        assert_eq!(restored.block_time, block.block_time);
+        assert_eq!(restored.previous_blockhash, block.previous_blockhash);
+        assert_eq!(restored.leader_id, block.leader_id);
+        assert_eq!(restored.rewards, block.rewards);
+
+        // in block order
+        assert_eq!(restored.transactions.len(), block.transactions.len());
+        for (restored, original) in restored.transactions.iter().zip(&block.transactions) {
+            assert_eq!(restored.signature, original.signature);
+            assert_eq!(restored.message, original.message);
+            assert_eq!(restored.cu_requested, original.cu_requested);
+            assert_eq!(restored.prioritization_fees, original.prioritization_fees);
+            assert_eq!(restored.cu_consumed, original.cu_consumed);
+            assert_eq!(restored.recent_blockhash, original.recent_blockhash);
+            assert_eq!(restored.writable_accounts, original.writable_accounts);
+        }
+    }
+
+    for dropped_epoch in [EPOCH, EPOCH + 1] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(dropped_epoch))
+            .await
+            .unwrap();
+    }
+}
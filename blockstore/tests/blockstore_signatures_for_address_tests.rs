@@ -0,0 +1,120 @@
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig;
+use solana_lite_rpc_core::structures::epoch::{EpochCache, EpochRef};
+use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
+use solana_lite_rpc_core::test_utils::{create_fork_block, create_test_tx_with_accounts};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+
+// 1000 slots per epoch; the account is touched in both epochs
+const FIRST_EPOCH: u64 = 15;
+const SECOND_EPOCH: u64 = 16;
+const NB_BLOCKS_PER_EPOCH: u64 = 3;
+
+fn create_test_block(slot: Slot, account: Pubkey) -> ProducedBlock {
+    let tx = create_test_tx_with_accounts(vec![Pubkey::new_unique(), account], vec![]);
+    create_fork_block(slot, slot - 1, CommitmentConfig::confirmed(), vec![tx])
+}
+
+// signatures of the page, newest first
+async fn query_signatures(
+    block_storage_query: &PostgresQueryBlockStore,
+    account: Pubkey,
+    before: Option<Signature>,
+    until: Option<Signature>,
+    limit: usize,
+) -> Vec<Signature> {
+    block_storage_query
+        .query_signatures_for_address(account, before, until, None, limit)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|status| status.signature.parse().unwrap())
+        .collect()
+}
+
+async fn drop_epoch_schemas(block_storage: &PostgresBlockStore) {
+    for dropped_epoch in [FIRST_EPOCH, SECOND_EPOCH, SECOND_EPOCH + 1] {
+        block_storage
+            .drop_epoch_schema(EpochRef::new(dropped_epoch))
+            .await
+            .unwrap();
+    }
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_paginate_signatures_for_address_across_epochs() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let block_storage =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query =
+        PostgresQueryBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    drop_epoch_schemas(&block_storage).await;
+
+    let account = Pubkey::new_unique();
+    let mut blocks = vec![];
+    for epoch in [FIRST_EPOCH, SECOND_EPOCH] {
+        block_storage
+            .prepare_epoch_schema(epoch * 1000)
+            .await
+            .unwrap();
+        for i in 1..=NB_BLOCKS_PER_EPOCH {
+            let block = create_test_block(epoch * 1000 + i, account);
+            block_storage.save_block(&block).await.unwrap();
+            blocks.push(block);
+        }
+    }
+    // newest first
+    let signatures = blocks
+        .iter()
+        .rev()
+        .map(|block| block.transactions[0].signature)
+        .collect::<Vec<_>>();
+
+    // first page ends in the older epoch
+    let page = query_signatures(&block_storage_query, account, None, None, 4).await;
+    assert_eq!(page, signatures[..4]);
+
+    // the next page continues in the older epoch
+    let page = query_signatures(&block_storage_query, account, Some(page[3]), None, 4).await;
+    assert_eq!(page, signatures[4..]);
+
+    // before and until are exclusive and may be in different epochs
+    let page = query_signatures(
+        &block_storage_query,
+        account,
+        Some(signatures[0]),
+        Some(signatures[5]),
+        10,
+    )
+    .await;
+    assert_eq!(page, signatures[1..5]);
+
+    // the limit applies across epochs
+    let page = query_signatures(
+        &block_storage_query,
+        account,
+        Some(signatures[1]),
+        Some(signatures[5]),
+        2,
+    )
+    .await;
+    assert_eq!(page, signatures[2..4]);
+
+    // until within the newer epoch stops before the older epoch
+    let page = query_signatures(&block_storage_query, account, None, Some(signatures[2]), 10).await;
+    assert_eq!(page, signatures[..2]);
+
+    // the oldest transaction has no successor
+    let page = query_signatures(&block_storage_query, account, Some(signatures[5]), None, 10).await;
+    assert!(page.is_empty());
+
+    drop_epoch_schemas(&block_storage).await;
+}
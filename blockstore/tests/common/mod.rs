@@ -0,0 +1,111 @@
+use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSession;
+use solana_lite_rpc_core::encoding::BASE64;
+use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
+
+// epoch schema as written before schema versioning: text columns, no account index
+pub fn build_create_legacy_schema_statement(epoch: u64) -> String {
+    let schema = format!("rpc2a_epoch_{}", epoch);
+    format!(
+        r#"
+            CREATE SCHEMA {schema};
+            CREATE TABLE {schema}.blocks (
+                slot BIGINT NOT NULL,
+                blockhash TEXT NOT NULL,
+                leader_id TEXT,
+                block_height BIGINT NOT NULL,
+                parent_slot BIGINT NOT NULL,
+                block_time BIGINT NOT NULL,
+                previous_blockhash TEXT NOT NULL,
+                rewards TEXT,
+                CONSTRAINT pk_block_slot PRIMARY KEY(slot)
+            );
+            CREATE TABLE {schema}.transaction_ids(
+                transaction_id bigserial PRIMARY KEY,
+                signature text NOT NULL,
+                UNIQUE(signature)
+            );
+            CREATE TABLE {schema}.transaction_blockdata(
+                transaction_id bigint PRIMARY KEY,
+                slot bigint NOT NULL,
+                cu_requested bigint,
+                prioritization_fees bigint,
+                cu_consumed bigint,
+                recent_blockhash text NOT NULL,
+                err text,
+                message text NOT NULL
+            );
+            CREATE INDEX idx_slot ON {schema}.transaction_blockdata USING btree (slot);
+        "#,
+        schema = schema
+    )
+}
+
+// same as the legacy writer: message and err as base64 text
+pub async fn save_legacy_block(session: &PostgresSession, epoch: u64, block: &ProducedBlock) {
+    let schema = format!("rpc2a_epoch_{}", epoch);
+    let statement = format!(
+        r#"
+            INSERT INTO {schema}.blocks(slot, blockhash, leader_id, block_height, parent_slot, block_time, previous_blockhash, rewards)
+            VALUES ($1, $2, NULL, $3, $4, $5, $6, NULL)
+        "#,
+    );
+    session
+        .execute(
+            &statement,
+            &[
+                &(block.slot as i64),
+                &block.blockhash.to_string(),
+                &(block.block_height as i64),
+                &(block.parent_slot as i64),
+                &(block.block_time as i64),
+                &block.previous_blockhash.to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+
+    let statement = format!(
+        r#"
+            WITH data AS (
+                SELECT unnest($1::text[]) AS signature, unnest($2::text[]) AS recent_blockhash,
+                    unnest($3::text[]) AS err, unnest($4::text[]) AS message
+            ), ids AS (
+                INSERT INTO {schema}.transaction_ids(signature) SELECT signature FROM data
+                RETURNING transaction_id, signature
+            )
+            INSERT INTO {schema}.transaction_blockdata(transaction_id, slot, cu_requested, prioritization_fees, cu_consumed, recent_blockhash, err, message)
+            SELECT ids.transaction_id, $5, 200000, 10000, 450, data.recent_blockhash, data.err, data.message
+            FROM ids JOIN data USING (signature)
+        "#,
+    );
+    let transactions = &block.transactions;
+    let signatures = transactions
+        .iter()
+        .map(|tx| tx.signature.to_string())
+        .collect::<Vec<_>>();
+    let recent_blockhashes = transactions
+        .iter()
+        .map(|tx| tx.recent_blockhash.to_string())
+        .collect::<Vec<_>>();
+    let errs = transactions
+        .iter()
+        .map(|tx| tx.err.as_ref().map(|err| BASE64.serialize(err).unwrap()))
+        .collect::<Vec<_>>();
+    let messages = transactions
+        .iter()
+        .map(|tx| BASE64.encode(tx.message.serialize()))
+        .collect::<Vec<_>>();
+    session
+        .execute(
+            &statement,
+            &[
+                &signatures,
+                &recent_blockhashes,
+                &errs,
+                &messages,
+                &(block.slot as i64),
+            ],
+        )
+        .await
+        .unwrap();
+}
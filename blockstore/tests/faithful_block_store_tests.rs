@@ -0,0 +1,94 @@
+use solana_lite_rpc_blockstore::block_stores::faithful_history::faithful_block_store::FaithfulBlockStore;
+use solana_lite_rpc_blockstore::block_stores::multiple_strategy_block_store::{
+    BlockSource, MultipleStrategyBlockStorage,
+};
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig;
+use solana_lite_rpc_core::structures::epoch::EpochCache;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use stand_in_rpc::{create_test_block, start_stand_in_rpc};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+mod stand_in_rpc;
+
+#[tokio::test]
+async fn test_faithful_block_store() {
+    let archived_block = create_test_block(520);
+    let (url, server) = start_stand_in_rpc(
+        vec![create_test_block(500), archived_block.clone()],
+        HashSet::new(),
+    )
+    .await;
+    let faithful_block_store = FaithfulBlockStore::new(Arc::new(RpcClient::new(url)));
+
+    assert_eq!(
+        faithful_block_store.get_first_available_slot().await,
+        Some(500)
+    );
+
+    let block = faithful_block_store.get_block(520).await.unwrap();
+    assert_eq!(block.slot, 520);
+    assert_eq!(block.blockhash, archived_block.blockhash);
+    assert_eq!(block.parent_slot, archived_block.parent_slot);
+    assert_eq!(block.block_time, archived_block.block_time);
+    assert_eq!(block.rewards, archived_block.rewards);
+    assert_eq!(block.commitment_config, CommitmentConfig::finalized());
+
+    assert!(faithful_block_store.get_block(510).await.is_err());
+
+    server.stop().unwrap();
+}
+
+#[ignore = "need postgres database"]
+#[tokio::test]
+async fn test_route_old_blocks_to_faithful_archive() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    // epoch 0 is only in the archive, epoch 1 is in postgres
+    let (url, server) = start_stand_in_rpc(
+        vec![create_test_block(500), create_test_block(520)],
+        HashSet::new(),
+    )
+    .await;
+
+    let pg_session_config = PostgresSessionConfig::new_for_tests();
+    let epoch_cache = EpochCache::new_for_tests();
+    let persistent_store =
+        PostgresBlockStore::new(epoch_cache.clone(), pg_session_config.clone()).await;
+    let block_storage_query = PostgresQueryBlockStore::new(epoch_cache, pg_session_config).await;
+    let multi_store =
+        MultipleStrategyBlockStorage::new(block_storage_query, Some(Arc::new(RpcClient::new(url))));
+
+    persistent_store.prepare_epoch_schema(1200).await.unwrap();
+    persistent_store
+        .save_block(&create_test_block(1200).to_confirmed_block())
+        .await
+        .unwrap();
+    persistent_store
+        .save_block(&create_test_block(1201).to_confirmed_block())
+        .await
+        .unwrap();
+
+    let recent = multi_store.query_block(1201).await.unwrap();
+    assert!(matches!(
+        recent.result_source,
+        BlockSource::RecentEpochDatabase
+    ));
+
+    let archived = multi_store.query_block(520).await.unwrap();
+    assert_eq!(archived.slot, 520);
+    assert!(matches!(
+        archived.result_source,
+        BlockSource::FaithfulArchive
+    ));
+
+    // neither in postgres nor in the archive
+    assert!(multi_store.query_block(510).await.is_err());
+
+    assert_eq!(*multi_store.get_slot_range().await.start(), 500);
+
+    server.stop().unwrap();
+}
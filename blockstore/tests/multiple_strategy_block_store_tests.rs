@@ -5,28 +5,13 @@ use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_wri
 use solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig;
 use solana_lite_rpc_core::structures::epoch::EpochCache;
 use solana_lite_rpc_core::structures::produced_block::{ProducedBlock, ProducedBlockInner};
-use solana_sdk::pubkey::Pubkey;
-use solana_sdk::reward_type::RewardType;
-use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash};
-use solana_transaction_status::Reward;
+use solana_lite_rpc_core::test_utils::{create_test_block_inner, create_test_rewards};
+use solana_sdk::commitment_config::CommitmentConfig;
 
 pub fn create_test_block(slot: u64, commitment_config: CommitmentConfig) -> ProducedBlock {
     let inner = ProducedBlockInner {
-        block_height: slot,
-        blockhash: Hash::new_unique(),
-        previous_blockhash: Hash::new_unique(),
-        parent_slot: slot - 1,
-        transactions: vec![],
-        block_time: 0,
-        leader_id: None,
-        slot,
-        rewards: Some(vec![Reward {
-            pubkey: Pubkey::new_unique().to_string(),
-            lamports: 5000,
-            post_balance: 1000000,
-            reward_type: Some(RewardType::Voting),
-            commission: None,
-        }]),
+        rewards: Some(create_test_rewards()),
+        ..create_test_block_inner(slot, slot - 1, vec![])
     };
     ProducedBlock::new(inner, commitment_config)
 }
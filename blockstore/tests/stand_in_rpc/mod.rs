@@ -0,0 +1,104 @@
+// stand-in for a solana rpc node (or faithful_history) serving a fixed set of blocks
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::server::{ServerBuilder, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::RpcModule;
+use solana_lite_rpc_core::structures::produced_block::{ProducedBlock, ProducedBlockInner};
+use solana_lite_rpc_core::test_utils::{create_test_block_inner, create_test_rewards};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::slot_history::Slot;
+use solana_transaction_status::{BlockEncodingOptions, TransactionDetails, UiTransactionEncoding};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+pub fn create_test_block(slot: Slot) -> ProducedBlock {
+    let inner = ProducedBlockInner {
+        rewards: Some(create_test_rewards()),
+        ..create_test_block_inner(slot, slot - 1, vec![])
+    };
+    ProducedBlock::new(inner, CommitmentConfig::finalized())
+}
+
+struct StandInRpc {
+    blocks: BTreeMap<Slot, ProducedBlock>,
+    // slots for which the next getBlock call fails
+    failing_slots: Mutex<HashSet<Slot>>,
+}
+
+// serves getVersion, getFirstAvailableBlock, getBlocks and getBlock
+pub async fn start_stand_in_rpc(
+    blocks: Vec<ProducedBlock>,
+    failing_slots: HashSet<Slot>,
+) -> (String, ServerHandle) {
+    let context = StandInRpc {
+        blocks: blocks
+            .into_iter()
+            .map(|block| (block.slot, block))
+            .collect(),
+        failing_slots: Mutex::new(failing_slots),
+    };
+    let mut module = RpcModule::new(context);
+    module
+        .register_method("getVersion", |_, _| -> RpcResult<serde_json::Value> {
+            Ok(serde_json::json!({ "solana-core": "1.18.16", "feature-set": 0 }))
+        })
+        .unwrap();
+    module
+        .register_method("getFirstAvailableBlock", |_, context| -> RpcResult<Slot> {
+            Ok(context.blocks.keys().next().copied().unwrap_or_default())
+        })
+        .unwrap();
+    module
+        .register_method("getBlocks", |params, context| -> RpcResult<Vec<Slot>> {
+            let (start_slot, end_slot, _config) =
+                params.parse::<(Slot, Option<Slot>, Option<serde_json::Value>)>()?;
+            let end_slot = end_slot.unwrap_or(Slot::MAX);
+            Ok(context
+                .blocks
+                .range(start_slot..=end_slot)
+                .map(|(slot, _)| *slot)
+                .collect())
+        })
+        .unwrap();
+    module
+        .register_method(
+            "getBlock",
+            |params, context| -> RpcResult<serde_json::Value> {
+                let (slot, _config) = params.parse::<(Slot, Option<serde_json::Value>)>()?;
+                if context.failing_slots.lock().unwrap().remove(&slot) {
+                    return Err(ErrorObjectOwned::owned(
+                        -32004,
+                        "Block not available",
+                        None::<()>,
+                    ));
+                }
+                let Some(block) = context.blocks.get(&slot) else {
+                    return Err(ErrorObjectOwned::owned(
+                        -32007,
+                        "Slot was skipped",
+                        None::<()>,
+                    ));
+                };
+                let ui_block = block
+                    .encode_with_options(
+                        UiTransactionEncoding::Base64,
+                        BlockEncodingOptions {
+                            transaction_details: TransactionDetails::Full,
+                            show_rewards: true,
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                    .unwrap();
+                Ok(serde_json::to_value(ui_block).unwrap())
+            },
+        )
+        .unwrap();
+
+    let server = ServerBuilder::default()
+        .http_only()
+        .build("127.0.0.1:0")
+        .await
+        .unwrap();
+    let url = format!("http://{}", server.local_addr().unwrap());
+    (url, server.start(module))
+}
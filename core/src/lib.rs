@@ -6,6 +6,8 @@ pub mod network_utils;
 pub mod solana_utils;
 pub mod stores;
 pub mod structures;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod traits;
 pub mod types;
 pub mod utils;
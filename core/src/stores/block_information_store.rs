@@ -11,6 +11,9 @@ use crate::structures::block_info::BlockInfo;
 use crate::structures::produced_block::ProducedBlock;
 use solana_sdk::hash::Hash;
 
+// same as solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY; solana rpc caps confirmations there
+const MAX_LOCKOUT_HISTORY: u64 = 31;
+
 #[derive(Clone, Debug)]
 pub struct BlockInformation {
     pub slot: u64,
@@ -203,6 +206,20 @@ impl BlockInformationStore {
         })
     }
 
+    // number of confirmed blocks built on top of the block at slot; None if the block is not known (anymore)
+    pub async fn get_confirmation_count(&self, slot: Slot) -> Option<usize> {
+        let block_info = self.get_block_info_by_slot(slot)?;
+        let latest_confirmed = self
+            .get_latest_block_information(CommitmentConfig::confirmed())
+            .await;
+        Some(
+            latest_confirmed
+                .block_height
+                .saturating_sub(block_info.block_height)
+                .min(MAX_LOCKOUT_HISTORY) as usize,
+        )
+    }
+
     pub fn get_last_blockheight(&self) -> u64 {
         self.last_blockheight
             .load(std::sync::atomic::Ordering::Relaxed)
@@ -1,6 +1,6 @@
 use crate::structures::leaderschedule::CalculatedSchedule;
-use dashmap::DashMap;
 use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
 use solana_sdk::slot_history::Slot;
 use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
 use std::sync::{atomic::AtomicU64, Arc};
@@ -8,8 +8,11 @@ use tokio::sync::RwLock;
 
 use crate::{
     stores::{
-        block_information_store::BlockInformationStore, cluster_info_store::ClusterInfo,
-        subscription_store::SubscriptionStore, tx_store::TxStore,
+        block_information_store::BlockInformationStore,
+        cluster_info_store::ClusterInfo,
+        subscription_store::SubscriptionStore,
+        tx_lifecycle_store::{TransactionLifecycle, TxLifecycleStore},
+        tx_store::TxStore,
     },
     structures::{
         epoch::{Epoch, EpochCache},
@@ -33,6 +36,7 @@ pub struct SlotCache {
 pub struct DataCache {
     pub block_information_store: BlockInformationStore,
     pub txs: TxStore,
+    pub tx_lifecycles: TxLifecycleStore,
     pub tx_subs: SubscriptionStore,
     pub slot_cache: SlotCache,
     pub identity_stakes: IdentityStakes,
@@ -49,6 +53,7 @@ impl DataCache {
             .await;
         self.block_information_store.clean().await;
         self.txs.clean(block_info.block_height);
+        self.tx_lifecycles.clean(block_info.block_height);
 
         self.tx_subs.clean(ttl_duration);
     }
@@ -64,6 +69,26 @@ impl DataCache {
                 .is_transaction_confirmed(&sent_transaction_info.signature)
     }
 
+    // marks sent transactions whose blockhash expired and notifies their subscribers
+    pub async fn expire_transactions(&self) {
+        let current_slot = self.slot_cache.get_current_slot();
+        let expired = self.txs.expire(
+            self.block_information_store.get_last_blockheight(),
+            current_slot,
+        );
+        for signature in expired {
+            self.tx_subs.notify_expired(&signature, current_slot).await;
+        }
+    }
+
+    pub fn get_transaction_lifecycle(&self, signature: &Signature) -> Option<TransactionLifecycle> {
+        let mut lifecycle = self.tx_lifecycles.get(signature)?;
+        lifecycle.expired = !lifecycle.has_landed()
+            && self.block_information_store.get_last_blockheight()
+                > lifecycle.last_valid_block_height;
+        Some(lifecycle)
+    }
+
     pub async fn get_current_epoch(&self, commitment: CommitmentConfig) -> Epoch {
         let BlockInformation { slot, .. } = self
             .block_information_store
@@ -87,9 +112,8 @@ impl DataCache {
             identity_stakes: IdentityStakes::new(Pubkey::new_unique()),
             slot_cache: SlotCache::new(0),
             tx_subs: SubscriptionStore::default(),
-            txs: TxStore {
-                store: Arc::new(DashMap::new()),
-            },
+            txs: TxStore::default(),
+            tx_lifecycles: TxLifecycleStore::default(),
             epoch_data: EpochCache::new_for_tests(),
             leader_schedule: Arc::new(RwLock::new(CalculatedSchedule::default())),
         }
@@ -4,4 +4,5 @@ pub mod block_information_store;
 pub mod cluster_info_store;
 pub mod data_cache;
 pub mod subscription_store;
+pub mod tx_lifecycle_store;
 pub mod tx_store;
@@ -1,8 +1,11 @@
 use crate::commitment_utils::Commitment;
 use crate::{structures::produced_block::TransactionInfo, types::SubscptionHanderSink};
 use dashmap::DashMap;
-use solana_client::rpc_response::{ProcessedSignatureResult, RpcSignatureResult};
+use solana_client::rpc_response::{
+    ProcessedSignatureResult, ReceivedSignatureResult, RpcSignatureResult,
+};
 use solana_sdk::signature::Signature;
+use solana_sdk::transaction::TransactionError;
 use solana_sdk::{commitment_config::CommitmentConfig, slot_history::Slot};
 use std::{sync::Arc, time::Duration};
 use tokio::time::Instant;
@@ -11,6 +14,8 @@ use tokio::time::Instant;
 pub struct SubscriptionStore {
     pub signature_subscribers:
         Arc<DashMap<(Signature, Commitment), (SubscptionHanderSink, Instant)>>,
+    // signature subscriptions with enableReceivedNotification
+    pub received_signature_subscribers: Arc<DashMap<Signature, Vec<SubscptionHanderSink>>>,
 }
 
 impl SubscriptionStore {
@@ -26,6 +31,31 @@ impl SubscriptionStore {
         );
     }
 
+    pub fn signature_received_subscribe(&self, signature: Signature, sink: SubscptionHanderSink) {
+        self.received_signature_subscribers
+            .entry(signature)
+            .or_default()
+            .push(sink);
+    }
+
+    pub async fn notify_received(&self, signature: &Signature, slot: Slot) {
+        if let Some((_sig, sinks)) = self.received_signature_subscribers.remove(signature) {
+            for sink in sinks {
+                Self::send_received(&sink, slot).await;
+            }
+        }
+    }
+
+    pub async fn send_received(sink: &SubscptionHanderSink, slot: Slot) {
+        let signature_result =
+            RpcSignatureResult::ReceivedSignature(ReceivedSignatureResult::ReceivedSignature);
+        sink.send(
+            slot,
+            serde_json::to_value(signature_result).expect("Should be serializable in json"),
+        )
+        .await;
+    }
+
     pub fn signature_un_subscribe(
         &self,
         signature: Signature,
@@ -58,9 +88,38 @@ impl SubscriptionStore {
         }
     }
 
+    // terminal notification for subscribers of a transaction which can no longer land
+    pub async fn notify_expired(&self, signature: &Signature, slot: Slot) {
+        self.received_signature_subscribers.remove(signature);
+        for commitment_config in [
+            CommitmentConfig::processed(),
+            CommitmentConfig::confirmed(),
+            CommitmentConfig::finalized(),
+        ] {
+            if let Some((_key, (sink, _))) = self
+                .signature_subscribers
+                .remove(&(*signature, Commitment::from(commitment_config)))
+            {
+                // same shape as a processed signature so that clients stop waiting
+                sink.send(
+                    slot,
+                    serde_json::json!({
+                        "err": TransactionError::BlockhashNotFound,
+                        "expired": true,
+                    }),
+                )
+                .await;
+            }
+        }
+    }
+
     pub fn clean(&self, ttl_duration: Duration) {
         self.signature_subscribers
             .retain(|_k, (sink, instant)| !sink.is_closed() && instant.elapsed() < ttl_duration);
+        self.received_signature_subscribers.retain(|_k, sinks| {
+            sinks.retain(|sink| !sink.is_closed());
+            !sinks.is_empty()
+        });
     }
 
     pub fn number_of_subscribers(&self) -> usize {
@@ -0,0 +1,468 @@
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+// bounds the memory of transactions replayed to many leaders
+const MAX_FORWARDS_PER_TRANSACTION: usize = 128;
+const LIFECYCLE_EVENTS_CAPACITY: usize = 16_384;
+
+/// Result of writing a transaction to the QUIC stream of a leader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ForwardOutcome {
+    Sent,
+    Timeout,
+    ConnectionError,
+    NoConnection,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionForward {
+    pub leader_identity: String,
+    pub slot: Slot,
+    pub outcome: ForwardOutcome,
+}
+
+/// What lite-rpc did with a transaction it received and where it landed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionLifecycle {
+    pub received_slot: Slot,
+    // unix timestamp in milliseconds
+    pub received_local_time: i64,
+    pub last_valid_block_height: u64,
+    pub forwards: Vec<TransactionForward>,
+    pub replay_count: usize,
+    pub processed_slot: Option<Slot>,
+    pub confirmed_slot: Option<Slot>,
+    pub finalized_slot: Option<Slot>,
+    // set when read, see DataCache::get_transaction_lifecycle
+    pub expired: bool,
+}
+
+impl TransactionLifecycle {
+    pub fn has_landed(&self) -> bool {
+        self.processed_slot.is_some()
+            || self.confirmed_slot.is_some()
+            || self.finalized_slot.is_some()
+    }
+
+    // events which led to this state, for subscribers joining late
+    pub fn events(&self) -> Vec<TransactionLifecycleEvent> {
+        let mut events = vec![TransactionLifecycleEvent::Received {
+            slot: self.received_slot,
+        }];
+        events.extend(
+            self.forwards
+                .iter()
+                .map(|forward| TransactionLifecycleEvent::Forwarded(forward.clone())),
+        );
+        if self.replay_count > 0 {
+            events.push(TransactionLifecycleEvent::Replayed {
+                replay_count: self.replay_count,
+            });
+        }
+        if let Some(slot) = self.processed_slot {
+            events.push(TransactionLifecycleEvent::Processed { slot });
+        }
+        if let Some(slot) = self.confirmed_slot {
+            events.push(TransactionLifecycleEvent::Confirmed { slot });
+        }
+        if let Some(slot) = self.finalized_slot {
+            events.push(TransactionLifecycleEvent::Finalized { slot });
+        }
+        if self.expired {
+            events.push(TransactionLifecycleEvent::Expired {
+                last_valid_block_height: self.last_valid_block_height,
+            });
+        }
+        events
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TransactionLifecycleEvent {
+    #[serde(rename_all = "camelCase")]
+    Received {
+        slot: Slot,
+    },
+    Forwarded(TransactionForward),
+    #[serde(rename_all = "camelCase")]
+    Replayed {
+        replay_count: usize,
+    },
+    Processed {
+        slot: Slot,
+    },
+    Confirmed {
+        slot: Slot,
+    },
+    Finalized {
+        slot: Slot,
+    },
+    #[serde(rename_all = "camelCase")]
+    Expired {
+        last_valid_block_height: u64,
+    },
+}
+
+impl TransactionLifecycleEvent {
+    // no more events follow
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            TransactionLifecycleEvent::Finalized { .. } | TransactionLifecycleEvent::Expired { .. }
+        )
+    }
+}
+
+/// State a subscriber has already been notified about
+/// a subscriber reads the lifecycle after subscribing and after lagging, so the same event can be seen twice
+#[derive(Debug, Default)]
+pub struct ReportedLifecycle {
+    received: bool,
+    forwards: Vec<TransactionForward>,
+    replay_count: usize,
+    processed: bool,
+    confirmed: bool,
+    finalized: bool,
+    expired: bool,
+}
+
+impl ReportedLifecycle {
+    // true if the event was not reported yet, it is then considered reported
+    pub fn report(&mut self, event: &TransactionLifecycleEvent) -> bool {
+        match event {
+            TransactionLifecycleEvent::Received { .. } => {
+                !std::mem::replace(&mut self.received, true)
+            }
+            TransactionLifecycleEvent::Forwarded(forward) => {
+                if self.forwards.contains(forward) {
+                    return false;
+                }
+                if self.forwards.len() >= MAX_FORWARDS_PER_TRANSACTION {
+                    self.forwards.remove(0);
+                }
+                self.forwards.push(forward.clone());
+                true
+            }
+            TransactionLifecycleEvent::Replayed { replay_count } => {
+                if *replay_count <= self.replay_count {
+                    return false;
+                }
+                self.replay_count = *replay_count;
+                true
+            }
+            TransactionLifecycleEvent::Processed { .. } => {
+                !std::mem::replace(&mut self.processed, true)
+            }
+            TransactionLifecycleEvent::Confirmed { .. } => {
+                !std::mem::replace(&mut self.confirmed, true)
+            }
+            TransactionLifecycleEvent::Finalized { .. } => {
+                !std::mem::replace(&mut self.finalized, true)
+            }
+            TransactionLifecycleEvent::Expired { .. } => {
+                !std::mem::replace(&mut self.expired, true)
+            }
+        }
+    }
+
+    // events of the lifecycle which were not reported yet, these are considered reported
+    pub fn report_lifecycle(
+        &mut self,
+        lifecycle: &TransactionLifecycle,
+    ) -> Vec<TransactionLifecycleEvent> {
+        lifecycle
+            .events()
+            .into_iter()
+            .filter(|event| self.report(event))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionLifecycleNotification {
+    pub signature: Signature,
+    pub event: TransactionLifecycleEvent,
+}
+
+/// Lifecycle of the transactions sent through lite-rpc
+#[derive(Clone, Debug)]
+pub struct TxLifecycleStore {
+    pub store: Arc<DashMap<Signature, TransactionLifecycle>>,
+    events: Arc<broadcast::Sender<TransactionLifecycleNotification>>,
+}
+
+impl Default for TxLifecycleStore {
+    fn default() -> Self {
+        let (events, _) = broadcast::channel(LIFECYCLE_EVENTS_CAPACITY);
+        Self {
+            store: Arc::new(DashMap::new()),
+            events: Arc::new(events),
+        }
+    }
+}
+
+impl TxLifecycleStore {
+    pub fn subscribe(&self) -> broadcast::Receiver<TransactionLifecycleNotification> {
+        self.events.subscribe()
+    }
+
+    fn notify(&self, signature: Signature, event: TransactionLifecycleEvent) {
+        if self.events.receiver_count() > 0 {
+            // error only if there are no receivers
+            let _ = self
+                .events
+                .send(TransactionLifecycleNotification { signature, event });
+        }
+    }
+
+    pub fn record_received(
+        &self,
+        signature: Signature,
+        received_slot: Slot,
+        last_valid_block_height: u64,
+    ) {
+        if self.store.contains_key(&signature) {
+            // a resent transaction keeps its history
+            return;
+        }
+        self.store
+            .entry(signature)
+            .or_insert_with(|| TransactionLifecycle {
+                received_slot,
+                received_local_time: Utc::now().timestamp_millis(),
+                last_valid_block_height,
+                forwards: vec![],
+                replay_count: 0,
+                processed_slot: None,
+                confirmed_slot: None,
+                finalized_slot: None,
+                expired: false,
+            });
+        self.notify(
+            signature,
+            TransactionLifecycleEvent::Received {
+                slot: received_slot,
+            },
+        );
+    }
+
+    pub fn record_forward(
+        &self,
+        signature: &Signature,
+        leader_identity: &Pubkey,
+        slot: Slot,
+        outcome: ForwardOutcome,
+    ) {
+        if let Some(mut lifecycle) = self.store.get_mut(signature) {
+            if lifecycle.forwards.len() >= MAX_FORWARDS_PER_TRANSACTION {
+                lifecycle.forwards.remove(0);
+            }
+            let forward = TransactionForward {
+                leader_identity: leader_identity.to_string(),
+                slot,
+                outcome,
+            };
+            lifecycle.forwards.push(forward.clone());
+            drop(lifecycle);
+            self.notify(*signature, TransactionLifecycleEvent::Forwarded(forward));
+        }
+    }
+
+    pub fn record_replay(&self, signature: &Signature, replay_count: usize) {
+        if let Some(mut lifecycle) = self.store.get_mut(signature) {
+            lifecycle.replay_count = replay_count;
+            drop(lifecycle);
+            self.notify(
+                *signature,
+                TransactionLifecycleEvent::Replayed { replay_count },
+            );
+        }
+    }
+
+    // only transactions sent through lite-rpc are tracked
+    pub fn record_landed(&self, signature: &Signature, slot: Slot, commitment: CommitmentLevel) {
+        if let Some(mut lifecycle) = self.store.get_mut(signature) {
+            let event = match commitment {
+                CommitmentLevel::Finalized => {
+                    lifecycle.confirmed_slot.get_or_insert(slot);
+                    lifecycle.finalized_slot = Some(slot);
+                    TransactionLifecycleEvent::Finalized { slot }
+                }
+                CommitmentLevel::Confirmed => {
+                    lifecycle.confirmed_slot = Some(slot);
+                    TransactionLifecycleEvent::Confirmed { slot }
+                }
+                _ => {
+                    lifecycle.processed_slot = Some(slot);
+                    TransactionLifecycleEvent::Processed { slot }
+                }
+            };
+            drop(lifecycle);
+            self.notify(*signature, event);
+        }
+    }
+
+    pub fn get(&self, signature: &Signature) -> Option<TransactionLifecycle> {
+        self.store.get(signature).map(|x| x.value().clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    pub fn clean(&self, current_finalized_blockheight: u64) {
+        let length_before = self.store.len();
+        self.store
+            .retain(|_k, v| v.last_valid_block_height >= current_finalized_blockheight);
+        log::info!(
+            "Cleaned {} transaction lifecycles",
+            length_before.saturating_sub(self.store.len())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_transaction_until_finalized() {
+        let store = TxLifecycleStore::default();
+        let signature = Signature::new_unique();
+        let leader = Pubkey::new_unique();
+
+        // transactions not sent through lite-rpc are ignored
+        store.record_landed(&signature, 10, CommitmentLevel::Processed);
+        assert!(store.get(&signature).is_none());
+
+        store.record_received(signature, 8, 300);
+        store.record_forward(&signature, &leader, 8, ForwardOutcome::Timeout);
+        store.record_replay(&signature, 1);
+        store.record_forward(&signature, &leader, 9, ForwardOutcome::Sent);
+        store.record_landed(&signature, 10, CommitmentLevel::Processed);
+        store.record_landed(&signature, 10, CommitmentLevel::Finalized);
+
+        let lifecycle = store.get(&signature).unwrap();
+        assert_eq!(lifecycle.received_slot, 8);
+        assert_eq!(lifecycle.replay_count, 1);
+        assert_eq!(
+            lifecycle
+                .forwards
+                .iter()
+                .map(|forward| forward.outcome)
+                .collect::<Vec<_>>(),
+            vec![ForwardOutcome::Timeout, ForwardOutcome::Sent]
+        );
+        assert_eq!(lifecycle.forwards[0].leader_identity, leader.to_string());
+        assert_eq!(lifecycle.processed_slot, Some(10));
+        assert_eq!(lifecycle.confirmed_slot, Some(10));
+        assert_eq!(lifecycle.finalized_slot, Some(10));
+
+        store.clean(301);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn notifies_lifecycle_events() {
+        let store = TxLifecycleStore::default();
+        let signature = Signature::new_unique();
+        let leader = Pubkey::new_unique();
+        let mut events = store.subscribe();
+
+        store.record_received(signature, 8, 300);
+        // resending does not restart the lifecycle
+        store.record_received(signature, 9, 300);
+        store.record_forward(&signature, &leader, 8, ForwardOutcome::Sent);
+        store.record_replay(&signature, 1);
+        store.record_landed(&signature, 10, CommitmentLevel::Confirmed);
+        store.record_landed(&signature, 10, CommitmentLevel::Finalized);
+
+        let mut received = vec![];
+        while let Ok(notification) = events.try_recv() {
+            assert_eq!(notification.signature, signature);
+            received.push(notification.event);
+        }
+        assert_eq!(received, store.get(&signature).unwrap().events());
+        assert!(received.last().unwrap().is_final());
+        assert_eq!(
+            serde_json::to_value(&received[0]).unwrap(),
+            serde_json::json!({"event": "received", "slot": 8})
+        );
+    }
+
+    #[test]
+    fn reports_each_event_once() {
+        let store = TxLifecycleStore::default();
+        let signature = Signature::new_unique();
+        let leader = Pubkey::new_unique();
+        let mut events = store.subscribe();
+        let mut reported = ReportedLifecycle::default();
+
+        store.record_received(signature, 8, 300);
+        store.record_forward(&signature, &leader, 8, ForwardOutcome::Sent);
+        // snapshot read after subscribing contains the events which are also notified
+        let snapshot = reported.report_lifecycle(&store.get(&signature).unwrap());
+        assert_eq!(snapshot.len(), 2);
+
+        store.record_replay(&signature, 1);
+        store.record_landed(&signature, 10, CommitmentLevel::Confirmed);
+
+        let mut notified = vec![];
+        while let Ok(notification) = events.try_recv() {
+            if reported.report(&notification.event) {
+                notified.push(notification.event);
+            }
+        }
+        assert_eq!(
+            notified,
+            vec![
+                TransactionLifecycleEvent::Replayed { replay_count: 1 },
+                TransactionLifecycleEvent::Confirmed { slot: 10 },
+            ]
+        );
+
+        // missed notifications are recovered from the lifecycle
+        store.record_landed(&signature, 10, CommitmentLevel::Finalized);
+        assert_eq!(
+            reported.report_lifecycle(&store.get(&signature).unwrap()),
+            vec![TransactionLifecycleEvent::Finalized { slot: 10 }]
+        );
+        assert!(reported
+            .report_lifecycle(&store.get(&signature).unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn bounds_forwards() {
+        let store = TxLifecycleStore::default();
+        let signature = Signature::new_unique();
+        store.record_received(signature, 0, 300);
+        for slot in 0..200 {
+            store.record_forward(
+                &signature,
+                &Pubkey::new_unique(),
+                slot,
+                ForwardOutcome::Sent,
+            );
+        }
+        let lifecycle = store.get(&signature).unwrap();
+        assert_eq!(lifecycle.forwards.len(), MAX_FORWARDS_PER_TRANSACTION);
+        assert_eq!(lifecycle.forwards.last().unwrap().slot, 199);
+    }
+}
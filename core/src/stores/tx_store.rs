@@ -1,7 +1,9 @@
 use dashmap::DashMap;
 use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
 use solana_transaction_status::TransactionStatus;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
 
 /// Transaction Properties
 
@@ -10,11 +12,16 @@ pub struct TxProps {
     pub status: Option<TransactionStatus>,
     pub last_valid_blockheight: u64,
     pub sent_by_lite_rpc: bool,
+    // slot at which the blockhash of a sent transaction expired before it landed
+    // the status stays none like on solana rpc; the expiry is reported by the transaction lifecycle
+    pub expired_slot: Option<Slot>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct TxStore {
     pub store: Arc<DashMap<Signature, TxProps>>,
+    // sent transactions by last valid blockheight, to find the ones expiring
+    pub expiry_queue: Arc<Mutex<BTreeMap<u64, Vec<Signature>>>>,
 }
 
 impl TxStore {
@@ -34,6 +41,7 @@ impl TxStore {
                     status: Some(transaction_status),
                     last_valid_blockheight,
                     sent_by_lite_rpc: false,
+                    expired_slot: None,
                 },
             );
             false
@@ -41,9 +49,42 @@ impl TxStore {
     }
 
     pub fn insert(&self, signature: Signature, props: TxProps) -> Option<TxProps> {
+        if props.sent_by_lite_rpc && props.status.is_none() {
+            self.expiry_queue
+                .lock()
+                .unwrap()
+                .entry(props.last_valid_blockheight)
+                .or_default()
+                .push(signature);
+        }
         self.store.insert(signature, props)
     }
 
+    /// marks the sent transactions which did not land before current_blockheight passed their last valid blockheight
+    pub fn expire(&self, current_blockheight: u64, current_slot: Slot) -> Vec<Signature> {
+        let expiring = {
+            let mut expiry_queue = self.expiry_queue.lock().unwrap();
+            let pending = expiry_queue.split_off(&current_blockheight);
+            std::mem::replace(&mut *expiry_queue, pending)
+        };
+
+        let mut expired = vec![];
+        for signature in expiring.into_values().flatten() {
+            if let Some(mut props) = self.store.get_mut(&signature) {
+                // resent with a later blockhash or already marked
+                if props.status.is_some()
+                    || props.expired_slot.is_some()
+                    || props.last_valid_blockheight >= current_blockheight
+                {
+                    continue;
+                }
+                props.expired_slot = Some(current_slot);
+                expired.push(signature);
+            }
+        }
+        expired
+    }
+
     pub fn len(&self) -> usize {
         self.store.len()
     }
@@ -64,6 +105,12 @@ impl TxStore {
         let length_before = self.store.len();
         self.store
             .retain(|_k, v| v.last_valid_blockheight >= current_finalized_blockheight);
+        self.expiry_queue
+            .lock()
+            .unwrap()
+            .retain(|last_valid_blockheight, _| {
+                *last_valid_blockheight >= current_finalized_blockheight
+            });
         log::info!(
             "Cleaned {} transactions",
             length_before.saturating_sub(self.store.len())
@@ -77,3 +124,51 @@ impl TxStore {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent_tx(last_valid_blockheight: u64) -> TxProps {
+        TxProps {
+            status: None,
+            last_valid_blockheight,
+            sent_by_lite_rpc: true,
+            expired_slot: None,
+        }
+    }
+
+    #[test]
+    fn expires_sent_transactions_which_did_not_land() {
+        let store = TxStore::default();
+        let landed = Signature::new_unique();
+        let pending = Signature::new_unique();
+        let expiring = Signature::new_unique();
+        store.insert(landed, sent_tx(100));
+        store.insert(pending, sent_tx(150));
+        store.insert(expiring, sent_tx(100));
+        store.update_status(
+            landed,
+            TransactionStatus {
+                slot: 90,
+                confirmations: None,
+                status: Ok(()),
+                err: None,
+                confirmation_status: None,
+            },
+            100,
+        );
+
+        // valid up to and including the last valid blockheight
+        assert!(store.expire(100, 1000).is_empty());
+        assert_eq!(store.expire(101, 1001), vec![expiring]);
+        assert!(store.expire(102, 1002).is_empty());
+
+        assert_eq!(store.get(&expiring).unwrap().expired_slot, Some(1001));
+        // no status for getSignatureStatuses
+        assert!(store.get(&expiring).unwrap().status.is_none());
+        assert_eq!(store.get(&pending).unwrap().expired_slot, None);
+        assert_eq!(store.get(&landed).unwrap().expired_slot, None);
+        assert_eq!(store.expiry_queue.lock().unwrap().len(), 1);
+    }
+}
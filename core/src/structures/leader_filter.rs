@@ -0,0 +1,72 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// Leader identities transactions may be forwarded to
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LeaderFilter {
+    // only these leaders if set
+    pub allow_list: Option<BTreeSet<Pubkey>>,
+    pub deny_list: BTreeSet<Pubkey>,
+}
+
+impl LeaderFilter {
+    pub fn new(allow_list: Option<Vec<String>>, deny_list: Vec<String>) -> anyhow::Result<Self> {
+        let parse = |identities: Vec<String>| {
+            identities
+                .iter()
+                .map(|identity| {
+                    Pubkey::from_str(identity.trim())
+                        .with_context(|| format!("invalid leader identity {identity}"))
+                })
+                .collect::<anyhow::Result<BTreeSet<_>>>()
+        };
+        Ok(Self {
+            allow_list: allow_list.map(parse).transpose()?,
+            deny_list: parse(deny_list)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allow_list.is_none() && self.deny_list.is_empty()
+    }
+
+    pub fn allows(&self, identity: &Pubkey) -> bool {
+        !self.deny_list.contains(identity)
+            && self
+                .allow_list
+                .as_ref()
+                .map_or(true, |allow_list| allow_list.contains(identity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deny_list_wins_over_allow_list() {
+        let allowed = Pubkey::new_unique();
+        let denied = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        assert!(LeaderFilter::default().allows(&other));
+
+        let filter = LeaderFilter::new(
+            Some(vec![allowed.to_string(), denied.to_string()]),
+            vec![denied.to_string()],
+        )
+        .unwrap();
+        assert!(filter.allows(&allowed));
+        assert!(!filter.allows(&denied));
+        assert!(!filter.allows(&other));
+
+        let filter = LeaderFilter::new(None, vec![denied.to_string()]).unwrap();
+        assert!(filter.allows(&other));
+        assert!(!filter.allows(&denied));
+
+        assert!(LeaderFilter::new(None, vec!["not a pubkey".to_string()]).is_err());
+    }
+}
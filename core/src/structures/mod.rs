@@ -6,6 +6,7 @@ pub mod block_info;
 pub mod epoch;
 pub mod identity_stakes;
 pub mod leader_data;
+pub mod leader_filter;
 pub mod leaderschedule;
 pub mod notifications;
 pub mod prioritization_fee_heap;
@@ -142,6 +142,7 @@ mod tests {
             transaction: Arc::new(vec![]),
             last_valid_block_height: 0,
             prioritization_fee,
+            leader_filter: None,
         };
 
         let tx_0 = tx_creator(Signature::new_unique(), 0);
@@ -208,6 +209,7 @@ mod tests {
                             transaction: Arc::new(vec![]),
                             last_valid_block_height: height + 10,
                             prioritization_fee,
+                            leader_filter: None,
                         };
                         p_heap.insert(info).await;
                     }
@@ -4,8 +4,14 @@ use solana_sdk::message::v0::MessageAddressTableLookup;
 use solana_sdk::message::VersionedMessage;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
-use solana_sdk::{slot_history::Slot, transaction::TransactionError};
-use solana_transaction_status::Reward;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_sdk::{clock::UnixTimestamp, slot_history::Slot, transaction::TransactionError};
+use solana_transaction_status::{
+    BlockEncodingOptions, ConfirmedBlock, ConfirmedTransactionWithStatusMeta, EncodeError,
+    EncodedConfirmedTransactionWithStatusMeta, Reward, TransactionDetails, TransactionStatusMeta,
+    TransactionWithStatusMeta, UiConfirmedBlock, UiTransactionEncoding,
+    VersionedTransactionWithStatusMeta,
+};
 use std::fmt::Debug;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -25,6 +31,67 @@ pub struct TransactionInfo {
     pub address_lookup_tables: Vec<MessageAddressTableLookup>,
 }
 
+impl TransactionInfo {
+    /// false if the transaction has more signatures than the tracked first one
+    pub fn has_all_signatures(&self) -> bool {
+        self.message.header().num_required_signatures <= 1
+    }
+
+    /// rebuild the transaction from the message
+    /// note: only the first signature is tracked, the other signatures are filled with defaults
+    pub fn to_versioned_transaction(&self) -> VersionedTransaction {
+        let num_signatures = self.message.header().num_required_signatures.max(1) as usize;
+        let mut signatures = vec![Signature::default(); num_signatures];
+        signatures[0] = self.signature;
+        VersionedTransaction {
+            signatures,
+            message: self.message.clone(),
+        }
+    }
+
+    /// note: fees, balances and logs are not tracked by lite-rpc; the meta is a placeholder which must not be served
+    pub fn to_transaction_with_status_meta(&self) -> TransactionWithStatusMeta {
+        let meta = TransactionStatusMeta {
+            status: self.err.clone().map_or(Ok(()), Err),
+            compute_units_consumed: self.cu_consumed,
+            ..TransactionStatusMeta::default()
+        };
+        TransactionWithStatusMeta::Complete(VersionedTransactionWithStatusMeta {
+            transaction: self.to_versioned_transaction(),
+            meta,
+        })
+    }
+}
+
+/// transaction with the information of the block it was included in
+#[derive(Debug, Clone)]
+pub struct ConfirmedTransactionInfo {
+    pub slot: Slot,
+    pub block_time: u64,
+    pub commitment_config: CommitmentConfig,
+    pub transaction: TransactionInfo,
+}
+
+impl ConfirmedTransactionInfo {
+    /// encode like solana rpc getTransaction does but without meta, as fees, balances and logs are not tracked
+    /// note: check has_all_signatures first, the other signatures would be defaults
+    pub fn encode(
+        &self,
+        encoding: UiTransactionEncoding,
+        max_supported_transaction_version: Option<u8>,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, EncodeError> {
+        let mut encoded = ConfirmedTransactionWithStatusMeta {
+            slot: self.slot,
+            tx_with_meta: self.transaction.to_transaction_with_status_meta(),
+            block_time: Some(self.block_time as UnixTimestamp),
+        }
+        .encode(encoding, max_supported_transaction_version)?;
+        // the placeholder meta is only needed for encoding
+        encoded.transaction.meta = None;
+        Ok(encoded)
+    }
+}
+
 #[derive(Clone)]
 pub struct ProducedBlock {
     // Arc is required for channels
@@ -88,4 +155,41 @@ impl ProducedBlock {
             commitment_config: CommitmentConfig::finalized(),
         }
     }
+
+    /// map to the block type used by solana rpc
+    pub fn to_solana_confirmed_block(&self) -> ConfirmedBlock {
+        ConfirmedBlock {
+            previous_blockhash: self.previous_blockhash.to_string(),
+            blockhash: self.blockhash.to_string(),
+            parent_slot: self.parent_slot,
+            transactions: self
+                .transactions
+                .iter()
+                .map(|tx| tx.to_transaction_with_status_meta())
+                .collect(),
+            rewards: self.rewards.clone().unwrap_or_default(),
+            block_time: Some(self.block_time as UnixTimestamp),
+            block_height: Some(self.block_height),
+        }
+    }
+
+    /// transaction details which can be served without inventing data
+    /// full and accounts would expose the placeholder signatures and meta of to_transaction_with_status_meta
+    pub fn supports_transaction_details(transaction_details: TransactionDetails) -> bool {
+        matches!(
+            transaction_details,
+            TransactionDetails::Signatures | TransactionDetails::None
+        )
+    }
+
+    /// encode like solana rpc getBlock does (encoding, transactionDetails, rewards, maxSupportedTransactionVersion)
+    /// note: check supports_transaction_details first
+    pub fn encode_with_options(
+        &self,
+        encoding: UiTransactionEncoding,
+        options: BlockEncodingOptions,
+    ) -> Result<UiConfirmedBlock, EncodeError> {
+        self.to_solana_confirmed_block()
+            .encode_with_options(encoding, options)
+    }
 }
@@ -20,6 +20,10 @@ impl TxData {
     pub fn new(signature: Signature, tx_raw: Vec<u8>) -> Self {
         TxData(signature, tx_raw)
     }
+
+    pub fn signature(&self) -> &Signature {
+        &self.0
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
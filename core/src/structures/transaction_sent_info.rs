@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_sdk::slot_history::Slot;
 
+use super::leader_filter::LeaderFilter;
+
 pub type WireTransaction = Vec<u8>;
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq)]
@@ -12,4 +15,14 @@ pub struct SentTransactionInfo {
     pub transaction: Arc<WireTransaction>,
     pub last_valid_block_height: u64,
     pub prioritization_fee: u64,
+    // restricts the leaders of this transaction in addition to the lists of the tpu service
+    pub leader_filter: Option<Arc<LeaderFilter>>,
+}
+
+impl SentTransactionInfo {
+    pub fn allows_leader(&self, identity: &Pubkey) -> bool {
+        self.leader_filter
+            .as_ref()
+            .map_or(true, |leader_filter| leader_filter.allows(identity))
+    }
 }
@@ -0,0 +1,96 @@
+// factories for tests of core and the crates depending on it (feature test-utils)
+use crate::structures::produced_block::{ProducedBlock, ProducedBlockInner, TransactionInfo};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
+use solana_sdk::message::{v0, MessageHeader, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::reward_type::RewardType;
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use solana_transaction_status::Reward;
+
+/// transaction with a unique signature; the payer is its only account
+pub fn create_test_tx() -> TransactionInfo {
+    create_test_tx_with_accounts(vec![Pubkey::new_unique()], vec![])
+}
+
+/// the first writable account is the payer; the readable accounts are not signers
+pub fn create_test_tx_with_accounts(
+    writable_accounts: Vec<Pubkey>,
+    readable_accounts: Vec<Pubkey>,
+) -> TransactionInfo {
+    let message = VersionedMessage::V0(v0::Message {
+        header: MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: readable_accounts.len() as u8,
+        },
+        account_keys: writable_accounts
+            .iter()
+            .chain(&readable_accounts)
+            .copied()
+            .collect(),
+        recent_blockhash: Hash::new_unique(),
+        ..v0::Message::default()
+    });
+    TransactionInfo {
+        signature: Signature::new_unique(),
+        is_vote: false,
+        err: None,
+        cu_requested: None,
+        prioritization_fees: None,
+        cu_consumed: None,
+        recent_blockhash: *message.recent_blockhash(),
+        message,
+        writable_accounts,
+        readable_accounts,
+        address_lookup_tables: vec![],
+    }
+}
+
+/// use with struct update syntax to set the leader or the rewards
+pub fn create_test_block_inner(
+    slot: Slot,
+    parent_slot: Slot,
+    transactions: Vec<TransactionInfo>,
+) -> ProducedBlockInner {
+    ProducedBlockInner {
+        transactions,
+        leader_id: None,
+        blockhash: Hash::new_unique(),
+        block_height: slot,
+        slot,
+        parent_slot,
+        block_time: 1_700_000_000 + slot,
+        previous_blockhash: Hash::new_unique(),
+        rewards: None,
+    }
+}
+
+/// empty block on top of the previous slot
+pub fn create_test_block(slot: Slot, commitment_config: CommitmentConfig) -> ProducedBlock {
+    create_fork_block(slot, slot - 1, commitment_config, vec![])
+}
+
+pub fn create_fork_block(
+    slot: Slot,
+    parent_slot: Slot,
+    commitment_config: CommitmentConfig,
+    transactions: Vec<TransactionInfo>,
+) -> ProducedBlock {
+    ProducedBlock::new(
+        create_test_block_inner(slot, parent_slot, transactions),
+        commitment_config,
+    )
+}
+
+/// fee reward of the leader
+pub fn create_test_rewards() -> Vec<Reward> {
+    vec![Reward {
+        pubkey: Pubkey::new_unique().to_string(),
+        lamports: 5000,
+        post_balance: 1_000_000,
+        reward_type: Some(RewardType::Fee),
+        commission: None,
+    }]
+}
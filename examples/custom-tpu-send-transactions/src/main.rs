@@ -1,7 +1,7 @@
 use std::{collections::HashSet, ops::Mul, str::FromStr, sync::Arc, time::Duration};
 
 use clap::Parser;
-use dashmap::{DashMap, DashSet};
+use dashmap::DashSet;
 use itertools::Itertools;
 use rand::{
     distributions::{Alphanumeric, Distribution},
@@ -20,11 +20,12 @@ use solana_lite_rpc_core::{
         cluster_info_store::ClusterInfo,
         data_cache::{DataCache, SlotCache},
         subscription_store::SubscriptionStore,
+        tx_lifecycle_store::TxLifecycleStore,
         tx_store::TxStore,
     },
     structures::{
-        epoch::EpochCache, identity_stakes::IdentityStakes, leaderschedule::CalculatedSchedule,
-        transaction_sent_info::SentTransactionInfo,
+        epoch::EpochCache, identity_stakes::IdentityStakes, leader_filter::LeaderFilter,
+        leaderschedule::CalculatedSchedule, transaction_sent_info::SentTransactionInfo,
     },
     utils::wait_till_block_of_commitment_is_recieved,
 };
@@ -35,7 +36,7 @@ use solana_lite_rpc_services::{
         tpu_connection_path::TpuConnectionPath,
         tpu_service::{TpuService, TpuServiceConfig},
     },
-    transaction_replayer::TransactionReplayer,
+    transaction_replayer::{ReplayStrategy, TransactionReplayer},
     transaction_service::TransactionServiceBuilder,
     tx_sender::TxSender,
 };
@@ -323,9 +324,8 @@ async fn main() -> anyhow::Result<()> {
         identity_stakes: IdentityStakes::new(validator_identity.pubkey()),
         slot_cache: SlotCache::new(finalize_slot),
         tx_subs: SubscriptionStore::default(),
-        txs: TxStore {
-            store: Arc::new(DashMap::new()),
-        },
+        txs: TxStore::default(),
+        tx_lifecycles: TxLifecycleStore::default(),
         epoch_data: EpochCache::new_for_tests(),
         leader_schedule: Arc::new(RwLock::new(CalculatedSchedule::default())),
     };
@@ -362,6 +362,8 @@ async fn main() -> anyhow::Result<()> {
             prioritization_heap_size,
         },
         tpu_connection_path: TpuConnectionPath::QuicDirectPath,
+        leader_filter: LeaderFilter::default(),
+        static_tpu_targets: vec![],
     };
 
     let tpu_service: TpuService = TpuService::new(
@@ -377,6 +379,7 @@ async fn main() -> anyhow::Result<()> {
             tpu_service.clone(),
             data_cache.clone(),
             Duration::from_secs(1),
+            ReplayStrategy::default(),
         ),
         tpu_service,
         10000,
@@ -385,6 +388,8 @@ async fn main() -> anyhow::Result<()> {
         None,
         data_cache.block_information_store.clone(),
         10,
+        None,
+        None,
         endpoints.slot_notifier,
     );
 
@@ -455,6 +460,7 @@ async fn main() -> anyhow::Result<()> {
                 slot,
                 transaction: Arc::new(raw_tx),
                 prioritization_fee: priority_fee,
+                leader_filter: None,
             };
             let _ = transaction_service
                 .transaction_channel
@@ -0,0 +1,138 @@
+use jsonrpsee::types::ErrorObjectOwned;
+
+use crate::rpc_errors::RpcErrors;
+
+/// Decides if a transaction is queued depending on how full the transaction queue is
+/// Above pressure_threshold only transactions paying at least the priority_percentile of recent compute unit prices are queued
+/// Transactions are never queued once the queue is full, so that sendTransaction does not wait for room
+#[derive(Debug, Clone, Copy)]
+pub struct AdmissionControl {
+    pub pressure_threshold: usize,
+    pub queue_capacity: usize,
+    // in percent, a multiple of 5 like the percentiles of PrioFeesStats
+    pub priority_percentile: u32,
+}
+
+/// reason for not queueing a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdmissionError {
+    QueueFull {
+        queue_depth: usize,
+    },
+    ComputeUnitPriceTooLow {
+        queue_depth: usize,
+        compute_unit_price: u64,
+        required_compute_unit_price: u64,
+    },
+}
+
+impl From<AdmissionError> for ErrorObjectOwned {
+    fn from(err: AdmissionError) -> Self {
+        match err {
+            AdmissionError::QueueFull { queue_depth } => ErrorObjectOwned::owned(
+                RpcErrors::TransactionNotAdmitted as i32,
+                "Transaction queue is full, retry later",
+                Some(serde_json::json!({
+                    "reason": "queueFull",
+                    "queueDepth": queue_depth,
+                })),
+            ),
+            AdmissionError::ComputeUnitPriceTooLow {
+                queue_depth,
+                compute_unit_price,
+                required_compute_unit_price,
+            } => ErrorObjectOwned::owned(
+                RpcErrors::TransactionNotAdmitted as i32,
+                format!(
+                    "Transaction queue is under pressure, compute unit price {} is below {}",
+                    compute_unit_price, required_compute_unit_price
+                ),
+                Some(serde_json::json!({
+                    "reason": "computeUnitPriceTooLow",
+                    "queueDepth": queue_depth,
+                    "computeUnitPrice": compute_unit_price,
+                    "requiredComputeUnitPrice": required_compute_unit_price,
+                })),
+            ),
+        }
+    }
+}
+
+impl AdmissionControl {
+    pub fn is_under_pressure(&self, queue_depth: usize) -> bool {
+        queue_depth >= self.pressure_threshold.min(self.queue_capacity)
+    }
+
+    // as used by PrioFeesStats::get_percentile
+    pub fn priority_percentile(&self) -> f32 {
+        self.priority_percentile as f32 / 100.0
+    }
+
+    // required_compute_unit_price is None as long as there are no fee stats; then only a full queue rejects
+    pub fn check(
+        &self,
+        queue_depth: usize,
+        compute_unit_price: u64,
+        required_compute_unit_price: Option<u64>,
+    ) -> Result<(), AdmissionError> {
+        if queue_depth >= self.queue_capacity {
+            return Err(AdmissionError::QueueFull { queue_depth });
+        }
+        if !self.is_under_pressure(queue_depth) {
+            return Ok(());
+        }
+        match required_compute_unit_price {
+            Some(required_compute_unit_price)
+                if compute_unit_price < required_compute_unit_price =>
+            {
+                Err(AdmissionError::ComputeUnitPriceTooLow {
+                    queue_depth,
+                    compute_unit_price,
+                    required_compute_unit_price,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_higher_compute_unit_price_under_pressure() {
+        let admission_control = AdmissionControl {
+            pressure_threshold: 50,
+            queue_capacity: 100,
+            priority_percentile: 75,
+        };
+        assert_eq!(admission_control.check(10, 0, Some(1000)), Ok(()));
+        assert_eq!(admission_control.check(60, 1000, Some(1000)), Ok(()));
+        assert_eq!(admission_control.check(60, 0, None), Ok(()));
+        assert_eq!(
+            admission_control.check(60, 999, Some(1000)),
+            Err(AdmissionError::ComputeUnitPriceTooLow {
+                queue_depth: 60,
+                compute_unit_price: 999,
+                required_compute_unit_price: 1000,
+            })
+        );
+        assert_eq!(
+            admission_control.check(100, u64::MAX, Some(1000)),
+            Err(AdmissionError::QueueFull { queue_depth: 100 })
+        );
+    }
+
+    #[test]
+    fn reports_reason() {
+        let err: ErrorObjectOwned = AdmissionError::ComputeUnitPriceTooLow {
+            queue_depth: 60,
+            compute_unit_price: 999,
+            required_compute_unit_price: 1000,
+        }
+        .into();
+        assert_eq!(err.code(), RpcErrors::TransactionNotAdmitted as i32);
+        assert!(err.data().unwrap().get().contains("computeUnitPriceTooLow"));
+    }
+}
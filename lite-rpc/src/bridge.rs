@@ -1,13 +1,21 @@
 use itertools::Itertools;
 use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::ErrorObjectOwned;
 use prometheus::{opts, register_int_counter, IntCounter};
-use solana_account_decoder::UiAccount;
+use solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
 use solana_lite_rpc_accounts::account_service::AccountService;
 use solana_lite_rpc_core::encoding::{BASE58, BASE64};
 use solana_lite_rpc_prioritization_fees::account_prio_service::AccountPrioService;
 use solana_lite_rpc_prioritization_fees::prioritization_fee_calculation_method::PrioritizationFeeCalculationMethod;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcSendTransactionConfig};
+use solana_rpc_client_api::config::{
+    RpcAccountInfoConfig, RpcBlockConfig, RpcEncodingConfigWrapper, RpcSendTransactionConfig,
+    RpcTransactionConfig,
+};
+use solana_rpc_client_api::request::{
+    MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
+    MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
+};
 use solana_rpc_client_api::response::{OptionalContext, RpcKeyedAccount};
 use solana_rpc_client_api::{
     config::{
@@ -24,9 +32,11 @@ use solana_rpc_client_api::{
 use solana_sdk::epoch_info::EpochInfo;
 use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
 use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, slot_history::Slot};
 use solana_transaction_status::{
-    TransactionBinaryEncoding, TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
+    BlockEncodingOptions, EncodeError, TransactionBinaryEncoding, TransactionConfirmationStatus,
+    TransactionDetails, TransactionStatus, UiConfirmedBlock, UiTransactionEncoding,
 };
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -36,19 +46,48 @@ use solana_lite_rpc_blockstore::history::History;
 use solana_lite_rpc_core::solana_utils::hash_from_str;
 use solana_lite_rpc_core::stores::{
     block_information_store::BlockInformation, data_cache::DataCache,
+    tx_lifecycle_store::TransactionLifecycle,
 };
+use solana_lite_rpc_core::structures::leader_filter::LeaderFilter;
+use solana_lite_rpc_core::structures::produced_block::ProducedBlock;
 use solana_lite_rpc_services::{
-    transaction_service::TransactionService, tx_sender::TXS_IN_CHANNEL,
+    transaction_service::{TransactionQueueFull, TransactionService},
+    tx_sender::TXS_IN_CHANNEL,
 };
 
+use crate::admission_control::{AdmissionControl, AdmissionError};
+use crate::preflight::{self, PreflightError};
 use crate::rpc_errors::RpcErrors;
-use crate::{configs::IsBlockHashValidConfig, rpc::LiteRpcServer};
+use crate::rpc_types::{EncodedConfirmedTransaction, SendTransactionResult};
+use crate::MAX_SEND_TRANSACTIONS_BATCH_SIZE;
+use crate::{
+    configs::{IsBlockHashValidConfig, LiteRpcSendTransactionConfig},
+    rpc::LiteRpcServer,
+};
 use solana_lite_rpc_prioritization_fees::rpc_data::{AccountPrioFeesStats, PrioFeesStats};
 use solana_lite_rpc_prioritization_fees::PrioFeesService;
 
 lazy_static::lazy_static! {
+    static ref RPC_GET_BLOCK: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_get_block", "RPC call to get block")).unwrap();
+    static ref RPC_GET_BLOCKS: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_get_blocks", "RPC call to get blocks")).unwrap();
+    static ref RPC_GET_BLOCKS_WITH_LIMIT: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_get_blocks_with_limit", "RPC call to get blocks with limit")).unwrap();
+    static ref RPC_GET_SIGNATURES_FOR_ADDRESS: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_get_signatures_for_address", "RPC call to get signatures for address")).unwrap();
+    static ref RPC_GET_TRANSACTION: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_get_transaction", "RPC call to get transaction")).unwrap();
     static ref RPC_SEND_TX: IntCounter =
     register_int_counter!(opts!("literpc_rpc_send_tx", "RPC call send transaction")).unwrap();
+    static ref RPC_SEND_TXS: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_send_txs", "RPC call send transactions batch")).unwrap();
+    static ref RPC_GET_TRANSACTION_LIFECYCLE: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_get_transaction_lifecycle", "RPC call to get transaction lifecycle")).unwrap();
+    static ref RPC_SEND_TX_PREFLIGHT_FAILED: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_send_tx_preflight_failed", "Transactions rejected by the sendTransaction preflight checks")).unwrap();
+    static ref RPC_SEND_TX_NOT_ADMITTED: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_send_tx_not_admitted", "Transactions rejected because the transaction queue is under pressure")).unwrap();
     static ref RPC_GET_LATEST_BLOCKHASH: IntCounter =
     register_int_counter!(opts!("literpc_rpc_get_latest_blockhash", "RPC call to get latest block hash")).unwrap();
     static ref RPC_IS_BLOCKHASH_VALID: IntCounter =
@@ -71,6 +110,7 @@ pub struct LiteBridge {
     prio_fees_service: PrioFeesService,
     account_priofees_service: AccountPrioService,
     accounts_service: Option<AccountService>,
+    admission_control: AdmissionControl,
 }
 
 impl LiteBridge {
@@ -82,6 +122,7 @@ impl LiteBridge {
         prio_fees_service: PrioFeesService,
         account_priofees_service: AccountPrioService,
         accounts_service: Option<AccountService>,
+        admission_control: AdmissionControl,
     ) -> Self {
         Self {
             rpc_client,
@@ -91,44 +132,376 @@ impl LiteBridge {
             prio_fees_service,
             account_priofees_service,
             accounts_service,
+            admission_control,
+        }
+    }
+
+    // status of recently seen transactions; older transactions only if search_transaction_history is set
+    async fn get_signature_status(
+        &self,
+        signature: &Signature,
+        search_transaction_history: bool,
+    ) -> RpcResult<Option<TransactionStatus>> {
+        // expired transactions have no status like on solana rpc; the expiry is reported by the transaction lifecycle
+        let status = match self.data_cache.txs.get(signature).and_then(|tx| tx.status) {
+            Some(status) => Some(status),
+            None if search_transaction_history => self
+                .history
+                .get_transaction(signature, CommitmentConfig::confirmed())
+                .await
+                .map_err(|_| jsonrpsee::types::error::ErrorCode::InternalError)?
+                .map(|tx| TransactionStatus {
+                    slot: tx.slot,
+                    confirmations: None,
+                    status: tx.transaction.err.clone().map_or(Ok(()), Err),
+                    err: tx.transaction.err,
+                    confirmation_status: Some(if tx.commitment_config.is_finalized() {
+                        TransactionConfirmationStatus::Finalized
+                    } else {
+                        TransactionConfirmationStatus::Confirmed
+                    }),
+                }),
+            None => None,
+        };
+        let Some(mut status) = status else {
+            return Ok(None);
+        };
+
+        // same as solana rpc: no confirmations once finalized
+        status.confirmations =
+            if status.confirmation_status == Some(TransactionConfirmationStatus::Finalized) {
+                None
+            } else {
+                self.data_cache
+                    .block_information_store
+                    .get_confirmation_count(status.slot)
+                    .await
+            };
+        Ok(Some(status))
+    }
+
+    // cheap checks instead of a simulation; rejects transactions which cannot land
+    // queued are the transactions of the same batch which are about to be queued
+    // the queue may fill up after this check, sending rejects them then, see queue_full_error
+    async fn admission_check(&self, wire_transaction: &[u8], queued: usize) -> RpcResult<()> {
+        let queue_depth = self.transaction_service.queue_depth() + queued;
+        if !self.admission_control.is_under_pressure(queue_depth) {
+            return Ok(());
+        }
+        let tx = bincode::deserialize::<VersionedTransaction>(wire_transaction)
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+        let required_compute_unit_price = self
+            .prio_fees_service
+            .get_latest_priofees()
+            .await
+            .and_then(|(_, stats)| {
+                stats
+                    .get_percentile(self.admission_control.priority_percentile())
+                    .map(|(by_tx, _)| by_tx)
+            });
+        self.admission_control
+            .check(
+                queue_depth,
+                preflight::compute_unit_price(&tx),
+                required_compute_unit_price,
+            )
+            .map_err(|err| {
+                RPC_SEND_TX_NOT_ADMITTED.inc();
+                err.into()
+            })
+    }
+
+    // the transaction service does not wait for room in the queue
+    fn queue_full_error(&self) -> ErrorObjectOwned {
+        RPC_SEND_TX_NOT_ADMITTED.inc();
+        AdmissionError::QueueFull {
+            queue_depth: self.transaction_service.queue_depth(),
+        }
+        .into()
+    }
+
+    async fn preflight_check(
+        &self,
+        wire_transaction: &[u8],
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<()> {
+        let tx = bincode::deserialize::<VersionedTransaction>(wire_transaction)
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+        tx.sanitize()
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+        preflight::verify_signatures(&tx)?;
+
+        // resending a transaction which has not landed yet is fine
+        if self
+            .data_cache
+            .txs
+            .is_transaction_confirmed(&tx.signatures[0])
+        {
+            return Err(
+                PreflightError::TransactionError(TransactionError::AlreadyProcessed).into(),
+            );
+        }
+
+        // the nonce value of a durable nonce transaction is not a blockhash
+        if !tx.uses_durable_nonce() {
+            let (is_blockhash_valid, _) = self
+                .data_cache
+                .block_information_store
+                .is_blockhash_valid(tx.message.recent_blockhash(), commitment_config)
+                .await;
+            if !is_blockhash_valid {
+                return Err(
+                    PreflightError::TransactionError(TransactionError::BlockhashNotFound).into(),
+                );
+            }
+        }
+
+        if let Some(accounts_service) = &self.accounts_service {
+            let fee_payer = tx.message.static_account_keys()[0];
+            let config = RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 0,
+                }),
+                commitment: Some(commitment_config),
+                min_context_slot: None,
+            };
+            // only accounts matching the account filters are known
+            if let Ok((_, Some(account))) =
+                accounts_service.get_account(fee_payer, Some(config)).await
+            {
+                if account.lamports < preflight::estimate_fee(&tx) {
+                    return Err(PreflightError::TransactionError(
+                        TransactionError::InsufficientFundsForFee,
+                    )
+                    .into());
+                }
+            }
         }
+
+        Ok(())
     }
 }
 
 #[jsonrpsee::core::async_trait]
 impl LiteRpcServer for LiteBridge {
-    async fn get_block(&self, _slot: u64) -> RpcResult<Option<UiConfirmedBlock>> {
-        // let block = self.blockstore.block_storage.query_block(slot).await;
-        // if block.is_ok() {
-        //     // TO DO Convert to UIConfirmed Block
-        //     Err(jsonrpsee::core::Error::HttpNotImplemented)
-        // } else {
-        //     Ok(None)
-        // }
+    async fn get_block(
+        &self,
+        slot: u64,
+        config: Option<RpcEncodingConfigWrapper<RpcBlockConfig>>,
+    ) -> RpcResult<Option<UiConfirmedBlock>> {
+        RPC_GET_BLOCK.inc();
 
-        // TODO get_block might deserve different implementation based on whether we serve from "blockstore module" vs. from "send tx module"
+        let config = config
+            .map(|config| config.convert_to_current())
+            .unwrap_or_default();
+        let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
+        let commitment_config = config.commitment.unwrap_or_default();
+        if commitment_config.is_processed() {
+            // same as solana rpc: getBlock is not supported for processed commitment
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
 
-        // under progress
-        Err(jsonrpsee::types::error::ErrorCode::MethodNotFound.into())
+        let block = self
+            .history
+            .get_block(slot, commitment_config)
+            .await
+            .map_err(|err| {
+                log::error!("getBlock {} failed: {:?}", slot, err);
+                jsonrpsee::types::error::ErrorCode::InternalError
+            })?;
+        let Some(block) = block else {
+            return Ok(None);
+        };
+
+        let options = BlockEncodingOptions {
+            transaction_details: config
+                .transaction_details
+                .unwrap_or(TransactionDetails::Full),
+            show_rewards: config.rewards.unwrap_or(true),
+            max_supported_transaction_version: config.max_supported_transaction_version,
+        };
+        if !ProducedBlock::supports_transaction_details(options.transaction_details) {
+            return Err(ErrorObjectOwned::owned(
+                jsonrpsee::types::error::ErrorCode::InvalidParams.code(),
+                "transactionDetails full and accounts are not supported, use signatures or none",
+                None::<()>,
+            ));
+        }
+        match block.encode_with_options(encoding, options) {
+            Ok(block) => Ok(Some(block)),
+            Err(EncodeError::UnsupportedTransactionVersion(_)) => {
+                Err(jsonrpsee::types::error::ErrorCode::ServerError(
+                    RpcErrors::UnsupportedTransactionVersion as i32,
+                )
+                .into())
+            }
+            Err(_) => Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into()),
+        }
     }
 
     async fn get_blocks(
         &self,
-        _start_slot: Slot,
-        _config: Option<RpcBlocksConfigWrapper>,
-        _commitment: Option<CommitmentConfig>,
+        start_slot: Slot,
+        config: Option<RpcBlocksConfigWrapper>,
+        commitment: Option<CommitmentConfig>,
     ) -> RpcResult<Vec<Slot>> {
-        // under progress
-        Err(jsonrpsee::types::error::ErrorCode::MethodNotFound.into())
+        RPC_GET_BLOCKS.inc();
+
+        let (end_slot, config_commitment) = config.map(|config| config.unzip()).unwrap_or_default();
+        let commitment_config = commitment.or(config_commitment).unwrap_or_default();
+        if commitment_config.is_processed() {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+
+        let latest_slot = self
+            .data_cache
+            .block_information_store
+            .get_latest_block_information(commitment_config)
+            .await
+            .slot;
+        let end_slot = end_slot.unwrap_or(latest_slot).min(latest_slot);
+        if end_slot < start_slot {
+            return Ok(vec![]);
+        }
+        if end_slot - start_slot > MAX_GET_CONFIRMED_BLOCKS_RANGE {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+
+        self.history
+            .get_blocks(start_slot..=end_slot, None, commitment_config)
+            .await
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InternalError.into())
+    }
+
+    async fn get_blocks_with_limit(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+        commitment: Option<CommitmentConfig>,
+    ) -> RpcResult<Vec<Slot>> {
+        RPC_GET_BLOCKS_WITH_LIMIT.inc();
+
+        let commitment_config = commitment.unwrap_or_default();
+        if commitment_config.is_processed() || limit as u64 > MAX_GET_CONFIRMED_BLOCKS_RANGE {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+
+        let latest_slot = self
+            .data_cache
+            .block_information_store
+            .get_latest_block_information(commitment_config)
+            .await
+            .slot;
+        if latest_slot < start_slot {
+            return Ok(vec![]);
+        }
+
+        self.history
+            .get_blocks(start_slot..=latest_slot, Some(limit), commitment_config)
+            .await
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InternalError.into())
     }
 
     async fn get_signatures_for_address(
         &self,
-        _address: String,
-        _config: Option<RpcSignaturesForAddressConfig>,
+        address: String,
+        config: Option<RpcSignaturesForAddressConfig>,
     ) -> RpcResult<Vec<RpcConfirmedTransactionStatusWithSignature>> {
-        // under progress
-        Err(jsonrpsee::types::error::ErrorCode::MethodNotFound.into())
+        RPC_GET_SIGNATURES_FOR_ADDRESS.inc();
+
+        let config = config.unwrap_or_default();
+        let Ok(address) = Pubkey::from_str(&address) else {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        };
+        let parse_signature = |signature: Option<String>| {
+            signature
+                .map(|signature| Signature::from_str(&signature))
+                .transpose()
+                .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)
+        };
+        let before = parse_signature(config.before)?;
+        let until = parse_signature(config.until)?;
+        let limit = config
+            .limit
+            .unwrap_or(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT);
+        if limit == 0 || limit > MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+        let commitment_config = config.commitment.unwrap_or_default();
+        if commitment_config.is_processed() {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+
+        match self
+            .history
+            .get_signatures_for_address(address, before, until, limit, commitment_config)
+            .await
+        {
+            Ok(Some(signatures)) => Ok(signatures),
+            // no block storage configured
+            Ok(None) => Err(jsonrpsee::types::error::ErrorCode::MethodNotFound.into()),
+            Err(_) => Err(jsonrpsee::types::error::ErrorCode::InternalError.into()),
+        }
+    }
+
+    async fn get_transaction(
+        &self,
+        signature_str: String,
+        config: Option<RpcEncodingConfigWrapper<RpcTransactionConfig>>,
+    ) -> RpcResult<Option<EncodedConfirmedTransaction>> {
+        RPC_GET_TRANSACTION.inc();
+
+        let Ok(signature) = Signature::from_str(&signature_str) else {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        };
+        let config = config
+            .map(|config| config.convert_to_current())
+            .unwrap_or_default();
+        let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
+        if !matches!(
+            encoding,
+            UiTransactionEncoding::Json
+                | UiTransactionEncoding::Base64
+                | UiTransactionEncoding::Base58
+        ) {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+        let commitment_config = config.commitment.unwrap_or_default();
+        if commitment_config.is_processed() {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+
+        let transaction = self
+            .history
+            .get_transaction(&signature, commitment_config)
+            .await
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InternalError)?;
+        let Some(transaction) = transaction else {
+            return Ok(None);
+        };
+        if !transaction.transaction.has_all_signatures() {
+            return Err(ErrorObjectOwned::owned(
+                jsonrpsee::types::error::ErrorCode::InvalidParams.code(),
+                "Transactions with more than one signature are not supported, only the first signature is stored",
+                None::<()>,
+            ));
+        }
+
+        match transaction.encode(encoding, config.max_supported_transaction_version) {
+            Ok(transaction) => Ok(Some(transaction.into())),
+            Err(EncodeError::UnsupportedTransactionVersion(_)) => {
+                Err(jsonrpsee::types::error::ErrorCode::ServerError(
+                    RpcErrors::UnsupportedTransactionVersion as i32,
+                )
+                .into())
+            }
+            Err(_) => Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into()),
+        }
     }
 
     async fn get_cluster_nodes(&self) -> RpcResult<Vec<RpcContactInfo>> {
@@ -279,15 +652,29 @@ impl LiteRpcServer for LiteBridge {
     async fn get_signature_statuses(
         &self,
         sigs: Vec<String>,
-        _config: Option<RpcSignatureStatusConfig>,
+        config: Option<RpcSignatureStatusConfig>,
     ) -> RpcResult<RpcResponse<Vec<Option<TransactionStatus>>>> {
         RPC_GET_SIGNATURE_STATUSES.inc();
 
-        let sig_statuses = sigs
+        if sigs.len() > MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+        let signatures = sigs
             .iter()
-            .map(|sig| Signature::from_str(sig).expect("signature must be valid"))
-            .map(|sig| self.data_cache.txs.get(&sig).and_then(|v| v.status))
-            .collect();
+            .map(|sig| Signature::from_str(sig))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+        let search_transaction_history = config
+            .map(|config| config.search_transaction_history)
+            .unwrap_or(false);
+
+        let mut sig_statuses = Vec::with_capacity(signatures.len());
+        for signature in &signatures {
+            sig_statuses.push(
+                self.get_signature_status(signature, search_transaction_history)
+                    .await?,
+            );
+        }
 
         Ok(RpcResponse {
             context: RpcResponseContext {
@@ -347,59 +734,48 @@ impl LiteRpcServer for LiteBridge {
     async fn send_transaction(
         &self,
         tx: String,
-        send_transaction_config: Option<RpcSendTransactionConfig>,
+        send_transaction_config: Option<LiteRpcSendTransactionConfig>,
     ) -> RpcResult<String> {
         RPC_SEND_TX.inc();
 
-        // Copied these constants from solana labs code
-        const MAX_BASE58_SIZE: usize = 1683;
-        const MAX_BASE64_SIZE: usize = 1644;
-
-        let RpcSendTransactionConfig {
-            encoding,
-            max_retries,
-            ..
+        let LiteRpcSendTransactionConfig {
+            config:
+                RpcSendTransactionConfig {
+                    skip_preflight,
+                    preflight_commitment,
+                    encoding,
+                    max_retries,
+                    ..
+                },
+            replay_strategy,
+            leader_allow_list,
+            leader_deny_list,
         } = send_transaction_config.unwrap_or_default();
+        let leader_filter =
+            LeaderFilter::new(leader_allow_list, leader_deny_list.unwrap_or_default())
+                .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
 
-        let encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
-        let expected_size = match encoding {
-            UiTransactionEncoding::Base58 => MAX_BASE58_SIZE,
-            UiTransactionEncoding::Base64 => MAX_BASE64_SIZE,
-            _ => usize::MAX,
-        };
-        if tx.len() > expected_size {
-            return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
-        }
-
-        let binary_encoding = encoding
-            .into_binary_encoding()
-            .ok_or(jsonrpsee::types::error::ErrorCode::InvalidParams)?;
-
-        let wire_output = match binary_encoding {
-            TransactionBinaryEncoding::Base58 => {
-                if tx.len() > MAX_BASE58_SIZE {
-                    return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
-                }
-                BASE58
-                    .decode(tx)
-                    .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?
-            }
-            TransactionBinaryEncoding::Base64 => {
-                if tx.len() > MAX_BASE64_SIZE {
-                    return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
-                }
-                BASE64
-                    .decode(tx)
-                    .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?
+        let wire_output =
+            decode_wire_transaction(tx, encoding.unwrap_or(UiTransactionEncoding::Base58))?;
+        if !skip_preflight {
+            let commitment_config = CommitmentConfig {
+                commitment: preflight_commitment.unwrap_or_default(),
+            };
+            if let Err(err) = self.preflight_check(&wire_output, commitment_config).await {
+                RPC_SEND_TX_PREFLIGHT_FAILED.inc();
+                return Err(err);
             }
-        };
-        if wire_output.len() > PACKET_DATA_SIZE {
-            return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
         }
+        self.admission_check(&wire_output, 0).await?;
         let max_retries = max_retries.map(|x| x as u16);
         match self
             .transaction_service
-            .send_wire_transaction(wire_output, max_retries)
+            .send_wire_transaction(
+                wire_output,
+                max_retries,
+                replay_strategy,
+                Some(leader_filter),
+            )
             .await
         {
             Ok(sig) => {
@@ -407,10 +783,115 @@ impl LiteRpcServer for LiteBridge {
 
                 Ok(sig)
             }
+            Err(err) if TransactionQueueFull::is_queue_full(&err) => Err(self.queue_full_error()),
             Err(_) => Err(jsonrpsee::types::error::ErrorCode::InternalError.into()),
         }
     }
 
+    async fn send_transactions(
+        &self,
+        txs: Vec<String>,
+        send_transaction_config: Option<LiteRpcSendTransactionConfig>,
+    ) -> RpcResult<Vec<SendTransactionResult>> {
+        RPC_SEND_TXS.inc();
+
+        if txs.len() > MAX_SEND_TRANSACTIONS_BATCH_SIZE {
+            return Err(jsonrpsee::types::error::ErrorCode::InvalidParams.into());
+        }
+
+        let LiteRpcSendTransactionConfig {
+            config:
+                RpcSendTransactionConfig {
+                    skip_preflight,
+                    preflight_commitment,
+                    encoding,
+                    max_retries,
+                    ..
+                },
+            replay_strategy,
+            leader_allow_list,
+            leader_deny_list,
+        } = send_transaction_config.unwrap_or_default();
+        let leader_filter =
+            LeaderFilter::new(leader_allow_list, leader_deny_list.unwrap_or_default())
+                .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+        let encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+        let commitment_config = CommitmentConfig {
+            commitment: preflight_commitment.unwrap_or_default(),
+        };
+
+        // transactions failing the checks are answered right away, the others are sent as one group
+        let mut results = Vec::with_capacity(txs.len());
+        let mut wire_transactions = vec![];
+        for tx in txs {
+            let wire_output = match decode_wire_transaction(tx, encoding) {
+                Ok(wire_output) => wire_output,
+                Err(err) => {
+                    results.push(Some(SendTransactionResult::Error(err)));
+                    continue;
+                }
+            };
+            if !skip_preflight {
+                if let Err(err) = self.preflight_check(&wire_output, commitment_config).await {
+                    RPC_SEND_TX_PREFLIGHT_FAILED.inc();
+                    results.push(Some(SendTransactionResult::Error(err)));
+                    continue;
+                }
+            }
+            if let Err(err) = self
+                .admission_check(&wire_output, wire_transactions.len())
+                .await
+            {
+                results.push(Some(SendTransactionResult::Error(err)));
+                continue;
+            }
+            results.push(None);
+            wire_transactions.push(wire_output);
+        }
+
+        let mut sent = self
+            .transaction_service
+            .send_wire_transactions(
+                wire_transactions,
+                max_retries.map(|x| x as u16),
+                replay_strategy,
+                Some(leader_filter),
+            )
+            .await
+            .into_iter();
+        let results = results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| {
+                    match sent.next().expect("a result per sent transaction") {
+                        Ok(sig) => {
+                            TXS_IN_CHANNEL.inc();
+                            SendTransactionResult::Signature(sig)
+                        }
+                        Err(err) if TransactionQueueFull::is_queue_full(&err) => {
+                            SendTransactionResult::Error(self.queue_full_error())
+                        }
+                        Err(_) => SendTransactionResult::Error(
+                            jsonrpsee::types::error::ErrorCode::InternalError.into(),
+                        ),
+                    }
+                })
+            })
+            .collect();
+        Ok(results)
+    }
+
+    async fn get_transaction_lifecycle(
+        &self,
+        signature: String,
+    ) -> RpcResult<Option<TransactionLifecycle>> {
+        RPC_GET_TRANSACTION_LIFECYCLE.inc();
+
+        let signature = Signature::from_str(&signature)
+            .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+        Ok(self.data_cache.get_transaction_lifecycle(&signature))
+    }
+
     fn get_version(&self) -> RpcResult<RpcVersionInfo> {
         RPC_GET_VERSION.inc();
 
@@ -726,3 +1207,45 @@ impl LiteRpcServer for LiteBridge {
         }
     }
 }
+
+// Copied these constants from solana labs code
+const MAX_BASE58_SIZE: usize = 1683;
+const MAX_BASE64_SIZE: usize = 1644;
+
+fn decode_wire_transaction(tx: String, encoding: UiTransactionEncoding) -> RpcResult<Vec<u8>> {
+    let expected_size = match encoding {
+        UiTransactionEncoding::Base58 => MAX_BASE58_SIZE,
+        UiTransactionEncoding::Base64 => MAX_BASE64_SIZE,
+        _ => usize::MAX,
+    };
+    if tx.len() > expected_size {
+        return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
+    }
+
+    let binary_encoding = encoding
+        .into_binary_encoding()
+        .ok_or(jsonrpsee::types::error::ErrorCode::InvalidParams)?;
+
+    let wire_output = match binary_encoding {
+        TransactionBinaryEncoding::Base58 => {
+            if tx.len() > MAX_BASE58_SIZE {
+                return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
+            }
+            BASE58
+                .decode(tx)
+                .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?
+        }
+        TransactionBinaryEncoding::Base64 => {
+            if tx.len() > MAX_BASE64_SIZE {
+                return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
+            }
+            BASE64
+                .decode(tx)
+                .map_err(|_| jsonrpsee::types::error::ErrorCode::InvalidParams)?
+        }
+    };
+    if wire_output.len() > PACKET_DATA_SIZE {
+        return Err(jsonrpsee::types::error::ErrorCode::OversizedRequest.into());
+    }
+    Ok(wire_output)
+}
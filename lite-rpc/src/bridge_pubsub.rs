@@ -2,9 +2,12 @@ use prometheus::{opts, register_int_counter, IntCounter};
 use solana_lite_rpc_accounts::account_service::AccountService;
 use solana_lite_rpc_core::{
     commitment_utils::Commitment,
-    stores::data_cache::DataCache,
+    stores::{
+        data_cache::DataCache,
+        tx_lifecycle_store::{ReportedLifecycle, TransactionLifecycleEvent},
+    },
     structures::account_data::AccountNotificationMessage,
-    types::{BlockInfoStream, BlockStream},
+    types::{BlockInfoStream, BlockStream, SubscptionHanderSink},
 };
 use std::{str::FromStr, sync::Arc, time::Duration};
 use tokio::sync::broadcast::error::RecvError::{Closed, Lagged};
@@ -36,6 +39,8 @@ use solana_sdk::signature::Signature;
 lazy_static::lazy_static! {
     static ref RPC_SIGNATURE_SUBSCRIBE: IntCounter =
     register_int_counter!(opts!("literpc_rpc_signature_subscribe", "RPC call to subscribe to signature")).unwrap();
+    static ref RPC_TRANSACTION_LIFECYCLE_SUBSCRIBE: IntCounter =
+    register_int_counter!(opts!("literpc_rpc_transaction_lifecycle_subscribe", "RPC call to subscribe to transaction lifecycle")).unwrap();
     static ref RPC_BLOCK_PRIOFEES_SUBSCRIBE: IntCounter =
     register_int_counter!(opts!("literpc_rpc_block_priofees_subscribe", "RPC call to subscribe to block prio fees")).unwrap();
     static ref RPC_ACCOUNT_PRIOFEES_SUBSCRIBE: IntCounter =
@@ -135,7 +140,6 @@ impl LiteRpcPubSubServer for LitePubSubBridge {
         todo!()
     }
 
-    // WARN: enable_received_notification: bool is ignored
     async fn signature_subscribe(
         &self,
         pending: PendingSubscriptionSink,
@@ -146,16 +150,121 @@ impl LiteRpcPubSubServer for LitePubSubBridge {
         let signature = Signature::from_str(&signature)?;
         let sink = pending.accept().await?;
 
-        let jsonrpsee_sink = JsonRpseeSubscriptionHandlerSink::new(sink);
+        let jsonrpsee_sink: SubscptionHanderSink =
+            Arc::new(JsonRpseeSubscriptionHandlerSink::new(sink));
+        if config.enable_received_notification.unwrap_or(false) {
+            self.data_cache
+                .tx_subs
+                .signature_received_subscribe(signature, jsonrpsee_sink.clone());
+            // the transaction might have been received before the subscription
+            if let Some(lifecycle) = self.data_cache.tx_lifecycles.get(&signature) {
+                self.data_cache
+                    .tx_subs
+                    .notify_received(&signature, lifecycle.received_slot)
+                    .await;
+            }
+        }
         self.data_cache.tx_subs.signature_subscribe(
             signature,
             config.commitment.unwrap_or_default(),
-            Arc::new(jsonrpsee_sink),
+            jsonrpsee_sink,
         );
 
         Ok(())
     }
 
+    async fn transaction_lifecycle_subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        signature: String,
+    ) -> SubscriptionResult {
+        RPC_TRANSACTION_LIFECYCLE_SUBSCRIBE.inc();
+        let signature = Signature::from_str(&signature)?;
+        let sink = pending.accept().await?;
+
+        let data_cache = self.data_cache.clone();
+        // subscribe before reading the past events so that none is missed
+        let mut lifecycle_events = data_cache.tx_lifecycles.subscribe();
+        tokio::spawn(async move {
+            let send_event = |event: TransactionLifecycleEvent| {
+                let result_message = jsonrpsee::SubscriptionMessage::from_json(&RpcResponse {
+                    context: RpcResponseContext {
+                        slot: data_cache.slot_cache.get_current_slot(),
+                        api_version: None,
+                    },
+                    value: event,
+                });
+                sink.send(result_message.unwrap())
+            };
+            // events of the snapshot may be notified again
+            let mut reported = ReportedLifecycle::default();
+            let mut events = data_cache
+                .get_transaction_lifecycle(&signature)
+                .map(|lifecycle| reported.report_lifecycle(&lifecycle))
+                .unwrap_or_default();
+
+            // also when notifications of other transactions keep arriving
+            let mut check_interval = tokio::time::interval(Duration::from_secs(1));
+
+            loop {
+                for event in events.drain(..) {
+                    let is_final = event.is_final();
+                    if let Err(DisconnectError(_subscription_message)) = send_event(event).await {
+                        log::debug!("Stopping subscription task on disconnect");
+                        return;
+                    }
+                    if is_final {
+                        return;
+                    }
+                }
+
+                events = tokio::select! {
+                    received = lifecycle_events.recv() => match received {
+                        Ok(notification) => {
+                            if notification.signature != signature
+                                || !reported.report(&notification.event)
+                            {
+                                continue;
+                            }
+                            vec![notification.event]
+                        }
+                        Err(Lagged(lagged)) => {
+                            // this usually happens if there is one "slow receiver", see https://docs.rs/tokio/latest/tokio/sync/broadcast/index.html#lagging
+                            log::warn!(
+                                "subscriber laggs some({}) transaction lifecycle messages - reading the lifecycle",
+                                lagged
+                            );
+                            // the missed events, including a final one, are part of the lifecycle
+                            data_cache
+                                .get_transaction_lifecycle(&signature)
+                                .map(|lifecycle| reported.report_lifecycle(&lifecycle))
+                                .unwrap_or_default()
+                        }
+                        Err(Closed) => {
+                            log::error!(
+                                "failed to receive transaction lifecycle notifications, sender closed - aborting"
+                            );
+                            return;
+                        }
+                    },
+                    _ = check_interval.tick() => {
+                        // check if sink is still open
+                        if sink.is_closed() {
+                            break;
+                        }
+                        // expiry is not an event of the store
+                        data_cache
+                            .get_transaction_lifecycle(&signature)
+                            .map(|lifecycle| reported.report_lifecycle(&lifecycle))
+                            .unwrap_or_default()
+                    }
+                };
+            }
+        });
+
+        Ok(())
+    }
+
     async fn slot_updates_subscribe(
         &self,
         _pending: PendingSubscriptionSink,
@@ -6,13 +6,16 @@ use std::{env, time::Duration};
 
 use crate::postgres_logger::{self, PostgresSessionConfig};
 use crate::{
-    DEFAULT_FANOUT_SIZE, DEFAULT_GRPC_ADDR, DEFAULT_RETRY_TIMEOUT, DEFAULT_RPC_ADDR,
-    DEFAULT_WS_ADDR, MAX_RETRIES,
+    DEFAULT_ADMISSION_PRIORITY_PERCENTILE, DEFAULT_FANOUT_SIZE, DEFAULT_GRPC_ADDR,
+    DEFAULT_RETRY_TIMEOUT, DEFAULT_RPC_ADDR, DEFAULT_WS_ADDR, MAX_RETRIES,
 };
 use anyhow::Context;
 use clap::Parser;
 use dotenv::dotenv;
+use solana_lite_rpc_blockstore::block_stores::block_cache::DEFAULT_BLOCK_CACHE_CAPACITY;
+use solana_lite_rpc_blockstore::block_stores::postgres::BinaryCompression;
 use solana_lite_rpc_services::quic_connection_utils::QuicConnectionParameters;
+use solana_lite_rpc_services::transaction_replayer::ReplayStrategy;
 use solana_rpc_client_api::client_error::reqwest::Url;
 
 #[derive(Parser, Debug, Clone)]
@@ -44,6 +47,30 @@ pub struct Config {
     pub maximum_retries_per_tx: usize,
     #[serde(default = "Config::default_transaction_retry_after_secs")]
     pub transaction_retry_after_secs: u64,
+    /// how long durable nonce transactions are retried; derived from maximum_retries_per_tx if not set
+    #[serde(default)]
+    pub durable_nonce_time_budget_secs: Option<u64>,
+    /// linear, exponential, leaderChange or burst; transactions can override it in sendTransaction
+    #[serde(default)]
+    pub replay_strategy: ReplayStrategy,
+    /// file to journal pending transactions to so that they are resent after a restart
+    #[serde(default)]
+    pub transaction_journal_path: Option<String>,
+    /// queue depth from which only transactions paying at least admission_priority_percentile are queued; half the queue if not set
+    #[serde(default)]
+    pub admission_queue_threshold: Option<usize>,
+    /// percentile of the compute unit prices of recent blocks, a multiple of 5
+    #[serde(default = "Config::default_admission_priority_percentile")]
+    pub admission_priority_percentile: u32,
+    /// forward transactions only to these leader identities if set
+    #[serde(default)]
+    pub leader_allow_list: Option<Vec<String>>,
+    /// never forward transactions to these leader identities
+    #[serde(default)]
+    pub leader_deny_list: Vec<String>,
+    /// json array of StaticTpuTargetConfig which get every transaction in addition to the leaders
+    #[serde(default)]
+    pub static_tpu_targets: Option<String>,
     #[serde(default)]
     pub quic_proxy_addr: Option<String>,
     #[serde(default)]
@@ -77,6 +104,39 @@ pub struct Config {
     #[serde(default)]
     pub postgres: Option<postgres_logger::PostgresSessionConfig>,
 
+    /// blockstore postgres config, used to serve history (getBlock, ...)
+    #[serde(default)]
+    pub blockstore_postgres:
+        Option<solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig>,
+
+    /// directory of the embedded blockstore, alternative to blockstore_postgres for small deployments
+    #[serde(default)]
+    pub blockstore_local_path: Option<String>,
+
+    /// write confirmed and finalized blocks to the blockstore (requires blockstore_postgres or blockstore_local_path)
+    #[serde(default)]
+    pub enable_blockstore_writer: bool,
+
+    /// faithful_history rpc endpoint serving blocks older than the epochs in the blockstore (requires blockstore_postgres)
+    #[serde(default)]
+    pub faithful_rpc_addr: Option<String>,
+
+    /// number of most recent epochs kept in the blockstore; older epochs are dropped once faithful_history serves them
+    #[serde(default)]
+    pub blockstore_retention_epochs: Option<u64>,
+
+    /// max disk usage of the blockstore epochs in bytes; oldest epochs are dropped once faithful_history serves them
+    #[serde(default)]
+    pub blockstore_retention_max_bytes: Option<u64>,
+
+    /// compression of newly created blockstore epoch schemas: none or zstd
+    #[serde(default)]
+    pub blockstore_compression: BinaryCompression,
+
+    /// number of blocks kept in the read cache in front of the blockstore; 0 disables the cache
+    #[serde(default = "Config::default_blockstore_cache_blocks")]
+    pub blockstore_cache_blocks: usize,
+
     #[serde(default)]
     pub max_number_of_connection: Option<usize>,
 
@@ -155,6 +215,47 @@ impl Config {
             .map(|secs| secs.parse().unwrap())
             .unwrap_or(config.transaction_retry_after_secs);
 
+        config.durable_nonce_time_budget_secs = env::var("DURABLE_NONCE_TIME_BUDGET")
+            .map(|secs| Some(secs.parse().unwrap()))
+            .unwrap_or(config.durable_nonce_time_budget_secs);
+
+        config.replay_strategy = env::var("REPLAY_STRATEGY")
+            .map(|strategy| strategy.parse().unwrap())
+            .unwrap_or(config.replay_strategy);
+
+        config.transaction_journal_path = env::var("TRANSACTION_JOURNAL_PATH")
+            .ok()
+            .or(config.transaction_journal_path);
+
+        config.admission_queue_threshold = env::var("ADMISSION_QUEUE_THRESHOLD")
+            .map(|value| value.parse::<usize>().unwrap())
+            .ok()
+            .or(config.admission_queue_threshold);
+
+        config.admission_priority_percentile = env::var("ADMISSION_PRIORITY_PERCENTILE")
+            .map(|value| value.parse::<u32>().unwrap())
+            .unwrap_or(config.admission_priority_percentile);
+        config.leader_allow_list = env::var("LEADER_ALLOW_LIST")
+            .map(|value| Some(value.split(',').map(str::to_string).collect()))
+            .unwrap_or(config.leader_allow_list);
+
+        config.leader_deny_list = env::var("LEADER_DENY_LIST")
+            .map(|value| value.split(',').map(str::to_string).collect())
+            .unwrap_or(config.leader_deny_list);
+
+        config.static_tpu_targets = env::var("STATIC_TPU_TARGETS")
+            .ok()
+            .or(config.static_tpu_targets);
+
+        if config.admission_priority_percentile > 100
+            || config.admission_priority_percentile % 5 != 0
+        {
+            anyhow::bail!(
+                "Admission priority percentile must be a multiple of 5 up to 100, got {}",
+                config.admission_priority_percentile
+            );
+        }
+
         config.quic_proxy_addr = env::var("QUIC_PROXY_ADDR").ok();
 
         config.use_grpc = env::var("USE_GRPC")
@@ -224,6 +325,36 @@ impl Config {
             .ok()
             .or(config.enable_accounts_on_demand_accounts_service);
 
+        config.enable_blockstore_writer = env::var("ENABLE_BLOCKSTORE_WRITER")
+            .map(|value| value.parse::<bool>().unwrap())
+            .unwrap_or(config.enable_blockstore_writer);
+
+        config.blockstore_local_path = env::var("BLOCKSTORE_LOCAL_PATH")
+            .ok()
+            .or(config.blockstore_local_path);
+
+        config.faithful_rpc_addr = env::var("FAITHFUL_RPC_ADDR")
+            .ok()
+            .or(config.faithful_rpc_addr);
+
+        config.blockstore_retention_epochs = env::var("BLOCKSTORE_RETENTION_EPOCHS")
+            .map(|value| value.parse::<u64>().unwrap())
+            .ok()
+            .or(config.blockstore_retention_epochs);
+
+        config.blockstore_retention_max_bytes = env::var("BLOCKSTORE_RETENTION_MAX_BYTES")
+            .map(|value| value.parse::<u64>().unwrap())
+            .ok()
+            .or(config.blockstore_retention_max_bytes);
+
+        config.blockstore_compression = env::var("BLOCKSTORE_COMPRESSION")
+            .map(|value| value.parse::<BinaryCompression>().unwrap())
+            .unwrap_or(config.blockstore_compression);
+
+        config.blockstore_cache_blocks = env::var("BLOCKSTORE_CACHE_BLOCKS")
+            .map(|value| value.parse::<usize>().unwrap())
+            .unwrap_or(config.blockstore_cache_blocks);
+
         config.postgres = PostgresSessionConfig::new_from_env()?.or(config.postgres);
         config.quic_connection_parameters = config
             .quic_connection_parameters
@@ -267,6 +398,14 @@ impl Config {
         DEFAULT_RETRY_TIMEOUT
     }
 
+    pub const fn default_admission_priority_percentile() -> u32 {
+        DEFAULT_ADMISSION_PRIORITY_PERCENTILE
+    }
+
+    pub const fn default_blockstore_cache_blocks() -> usize {
+        DEFAULT_BLOCK_CACHE_CAPACITY
+    }
+
     pub fn default_grpc_addr() -> String {
         DEFAULT_GRPC_ADDR.to_string()
     }
@@ -304,6 +443,16 @@ impl Config {
     }
 }
 
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaticTpuTargetConfig {
+    pub identity: String,
+    // host:port of the tpu quic port
+    pub tpu_address: String,
+    #[serde(default)]
+    pub stake: Option<u64>,
+}
+
 #[derive(Clone)]
 pub struct GrpcSource {
     pub addr: String,
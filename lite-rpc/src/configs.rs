@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use solana_lite_rpc_core::encoding::BinaryEncoding;
+use solana_lite_rpc_services::transaction_replayer::ReplayStrategy;
+use solana_rpc_client_api::config::RpcSendTransactionConfig;
 use solana_sdk::commitment_config::CommitmentLevel;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -21,3 +23,17 @@ pub struct IsBlockHashValidConfig {
     pub commitment: Option<CommitmentLevel>,
     //    pub minContextSlot: Option<u64>,
 }
+
+/// sendTransaction config of solana rpc with the lite-rpc extensions
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiteRpcSendTransactionConfig {
+    #[serde(flatten)]
+    pub config: RpcSendTransactionConfig,
+    /// overrides the replay strategy of lite-rpc for this transaction
+    pub replay_strategy: Option<ReplayStrategy>,
+    /// forward only to these leader identities, in addition to the allow list of lite-rpc
+    pub leader_allow_list: Option<Vec<String>>,
+    /// never forward to these leader identities, in addition to the deny list of lite-rpc
+    pub leader_deny_list: Option<Vec<String>>,
+}
@@ -1,6 +1,7 @@
 use const_env::from_env;
 use solana_transaction_status::TransactionConfirmationStatus;
 
+pub mod admission_control;
 pub mod bridge;
 pub mod bridge_pubsub;
 pub mod cli;
@@ -8,9 +9,11 @@ pub mod configs;
 pub mod errors;
 pub mod jsonrpsee_subscrption_handler_sink;
 pub mod postgres_logger;
+pub mod preflight;
 pub mod rpc;
 pub mod rpc_errors;
 pub mod rpc_pubsub;
+pub mod rpc_types;
 pub mod service_spawner;
 pub mod start_server;
 
@@ -22,6 +25,12 @@ pub const DEFAULT_WS_ADDR: &str = "ws://0.0.0.0:8900";
 #[from_env]
 pub const DEFAULT_MAX_NUMBER_OF_TXS_IN_QUEUE: usize = 200_000;
 
+#[from_env]
+pub const MAX_SEND_TRANSACTIONS_BATCH_SIZE: usize = 1000;
+
+#[from_env]
+pub const DEFAULT_ADMISSION_PRIORITY_PERCENTILE: u32 = 75;
+
 /// 25 slots in 10s send to little more leaders
 #[from_env]
 pub const DEFAULT_FANOUT_SIZE: u64 = 18;
@@ -1,22 +1,36 @@
 pub mod rpc_tester;
 
 use crate::rpc_tester::RpcTester;
-use anyhow::bail;
-use dashmap::DashMap;
+use anyhow::{bail, Context};
 use itertools::Itertools;
+use lite_rpc::admission_control::AdmissionControl;
 use lite_rpc::bridge::LiteBridge;
 use lite_rpc::bridge_pubsub::LitePubSubBridge;
-use lite_rpc::cli::Config;
+use lite_rpc::cli::{Config, StaticTpuTargetConfig};
 use lite_rpc::postgres_logger::PostgresLogger;
 use lite_rpc::service_spawner::ServiceSpawner;
 use lite_rpc::start_server::start_servers;
-use lite_rpc::DEFAULT_MAX_NUMBER_OF_TXS_IN_QUEUE;
-use log::info;
+use lite_rpc::{DEFAULT_MAX_NUMBER_OF_TXS_IN_QUEUE, NB_SLOTS_TRANSACTIONS_TO_CACHE};
+use log::{info, warn};
 use solana_lite_rpc_accounts::account_service::AccountService;
 use solana_lite_rpc_accounts::account_store_interface::AccountStorageInterface;
 use solana_lite_rpc_accounts::inmemory_account_store::InmemoryAccountStore;
 use solana_lite_rpc_accounts_on_demand::accounts_on_demand::AccountsOnDemand;
 use solana_lite_rpc_address_lookup_tables::address_lookup_table_store::AddressLookupTableStore;
+use solana_lite_rpc_blockstore::block_persistence_service::BlockPersistenceService;
+use solana_lite_rpc_blockstore::block_stores::block_cache::{BlockCache, CachedBlockStorageReader};
+use solana_lite_rpc_blockstore::block_stores::block_storage_interface::{
+    BlockStorageReader, BlockStorageWriter,
+};
+use solana_lite_rpc_blockstore::block_stores::faithful_history::faithful_block_store::FaithfulBlockStore;
+use solana_lite_rpc_blockstore::block_stores::local_block_store::LocalBlockStore;
+use solana_lite_rpc_blockstore::block_stores::multiple_strategy_block_store::MultipleStrategyBlockStorage;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_query::PostgresQueryBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::postgres_block_store_writer::PostgresBlockStore;
+use solana_lite_rpc_blockstore::block_stores::postgres::BinaryCompression;
+use solana_lite_rpc_blockstore::epoch_retention_service::{
+    EpochRetentionPolicy, EpochRetentionService,
+};
 use solana_lite_rpc_blockstore::history::History;
 use solana_lite_rpc_cluster_endpoints::endpoint_stremers::EndpointStreaming;
 
@@ -36,9 +50,11 @@ use solana_lite_rpc_core::stores::{
     cluster_info_store::ClusterInfo,
     data_cache::{DataCache, SlotCache},
     subscription_store::SubscriptionStore,
+    tx_lifecycle_store::TxLifecycleStore,
     tx_store::TxStore,
 };
 use solana_lite_rpc_core::structures::account_filter::AccountFilters;
+use solana_lite_rpc_core::structures::leader_filter::LeaderFilter;
 use solana_lite_rpc_core::structures::leaderschedule::CalculatedSchedule;
 use solana_lite_rpc_core::structures::{
     epoch::EpochCache, identity_stakes::IdentityStakes, notifications::NotificationSender,
@@ -50,7 +66,10 @@ use solana_lite_rpc_core::AnyhowJoinHandle;
 use solana_lite_rpc_prioritization_fees::account_prio_service::AccountPrioService;
 use solana_lite_rpc_services::data_caching_service::DataCachingService;
 use solana_lite_rpc_services::tpu_utils::tpu_connection_path::TpuConnectionPath;
-use solana_lite_rpc_services::tpu_utils::tpu_service::{TpuService, TpuServiceConfig};
+use solana_lite_rpc_services::tpu_utils::tpu_service::{
+    StaticTpuTarget, TpuService, TpuServiceConfig,
+};
+use solana_lite_rpc_services::transaction_journal::TransactionJournal;
 use solana_lite_rpc_services::transaction_replayer::TransactionReplayer;
 use solana_lite_rpc_services::tx_sender::TxSender;
 
@@ -59,9 +78,11 @@ use solana_lite_rpc_prioritization_fees::start_block_priofees_task;
 use solana_lite_rpc_util::obfuscate_rpcurl;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
 use std::net::{SocketAddr, ToSocketAddrs};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::AsyncReadExt;
@@ -96,6 +117,131 @@ pub async fn start_postgres(
     Ok((Some(postgres_send), postgres))
 }
 
+type BlockStorageHandles = (
+    Option<Arc<dyn BlockStorageReader>>,
+    Option<Arc<dyn BlockStorageWriter>>,
+);
+
+// blockstore backend serving history and, if enabled, persisting the block stream
+pub async fn create_block_storage(
+    blockstore_postgres: Option<
+        solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig,
+    >,
+    blockstore_local_path: Option<String>,
+    writer_enabled: bool,
+    compression: BinaryCompression,
+    epoch_cache: EpochCache,
+) -> anyhow::Result<BlockStorageHandles> {
+    match (blockstore_postgres, blockstore_local_path) {
+        (Some(_), Some(_)) => {
+            bail!("Configure either blockstore postgres or a local blockstore path, not both")
+        }
+        (Some(config), None) => {
+            info!("Using postgres blockstore");
+            let block_storage_query =
+                PostgresQueryBlockStore::new(epoch_cache.clone(), config.clone()).await;
+            let block_storage_writer: Option<Arc<dyn BlockStorageWriter>> = if writer_enabled {
+                let block_storage =
+                    PostgresBlockStore::new_with_compression(epoch_cache, config, compression)
+                        .await;
+                // legacy schemas are not served until migrated; the migration locks their tables
+                // older schemas get their account index backfilled
+                let migration_storage = block_storage.clone();
+                tokio::spawn(async move {
+                    match migration_storage.migrate_epoch_schemas().await {
+                        Ok(migrated) if !migrated.is_empty() => {
+                            info!("Migrated blockstore epoch schemas {:?}", migrated)
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            log::error!("Failed to migrate blockstore epoch schemas: {err:?}")
+                        }
+                    }
+                });
+                Some(Arc::new(block_storage))
+            } else {
+                None
+            };
+            let block_storage_reader: Arc<dyn BlockStorageReader> = Arc::new(block_storage_query);
+            Ok((Some(block_storage_reader), block_storage_writer))
+        }
+        (None, Some(path)) => {
+            info!("Using local blockstore at {}", path);
+            // reader and writer must share the instance
+            let local_block_store = Arc::new(LocalBlockStore::open(path, epoch_cache)?);
+            let block_storage_writer: Option<Arc<dyn BlockStorageWriter>> =
+                writer_enabled.then(|| local_block_store.clone() as Arc<dyn BlockStorageWriter>);
+            let block_storage_reader: Arc<dyn BlockStorageReader> = local_block_store;
+            Ok((Some(block_storage_reader), block_storage_writer))
+        }
+        (None, None) => {
+            if writer_enabled {
+                bail!("Blockstore writer requires blockstore postgres config or a local blockstore path");
+            }
+            Ok((None, None))
+        }
+    }
+}
+
+pub fn start_block_persistence(
+    block_storage_writer: Option<Arc<dyn BlockStorageWriter>>,
+    epoch_cache: EpochCache,
+    blocks_notifier: BlockStream,
+) -> AnyhowJoinHandle {
+    let Some(block_storage_writer) = block_storage_writer else {
+        return tokio::spawn(async {
+            std::future::pending::<()>().await;
+            unreachable!()
+        });
+    };
+
+    info!("Writing blocks to blockstore");
+    BlockPersistenceService::new(block_storage_writer, epoch_cache).start(blocks_notifier)
+}
+
+pub fn start_transaction_journal(
+    journal: Option<TransactionJournal>,
+    data_cache: DataCache,
+) -> AnyhowJoinHandle {
+    let Some(journal) = journal else {
+        return tokio::spawn(async {
+            std::future::pending::<()>().await;
+            unreachable!()
+        });
+    };
+    journal.start_service(data_cache)
+}
+
+pub async fn start_epoch_retention(
+    writer_enabled: bool,
+    policy: EpochRetentionPolicy,
+    config: Option<solana_lite_rpc_blockstore::block_stores::postgres::PostgresSessionConfig>,
+    epoch_cache: EpochCache,
+    faithful_rpc_client: Option<Arc<RpcClient>>,
+) -> anyhow::Result<AnyhowJoinHandle> {
+    if !writer_enabled || !policy.is_enabled() {
+        return Ok(tokio::spawn(async {
+            std::future::pending::<()>().await;
+            unreachable!()
+        }));
+    }
+    let Some(config) = config else {
+        bail!("Blockstore epoch retention requires blockstore postgres config");
+    };
+
+    let block_storage = PostgresBlockStore::new(epoch_cache.clone(), config.clone()).await;
+    let block_storage_query = PostgresQueryBlockStore::new(epoch_cache, config).await;
+    let faithful_block_storage =
+        faithful_rpc_client.map(|rpc_client| Arc::new(FaithfulBlockStore::new(rpc_client)));
+    Ok(EpochRetentionService::new(
+        block_storage,
+        block_storage_query,
+        faithful_block_storage,
+        policy,
+    )
+    .start())
+}
+
 pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow::Result<()> {
     let grpc_sources = args.get_grpc_sources();
     log::info!("grpc_sources:{grpc_sources:?}");
@@ -104,10 +250,26 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
         lite_rpc_http_addr,
         fanout_size,
         postgres,
+        blockstore_postgres,
+        blockstore_local_path,
+        enable_blockstore_writer,
+        faithful_rpc_addr,
+        blockstore_retention_epochs,
+        blockstore_retention_max_bytes,
+        blockstore_compression,
+        blockstore_cache_blocks,
         prometheus_addr,
         identity_keypair,
         maximum_retries_per_tx,
         transaction_retry_after_secs,
+        durable_nonce_time_budget_secs,
+        replay_strategy,
+        transaction_journal_path,
+        admission_queue_threshold,
+        admission_priority_percentile,
+        leader_allow_list,
+        leader_deny_list,
+        static_tpu_targets,
         quic_proxy_addr,
         use_grpc,
         enable_grpc_stream_inspection,
@@ -246,9 +408,8 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
         identity_stakes: IdentityStakes::new(validator_identity.pubkey()),
         slot_cache: SlotCache::new(finalized_block_info.slot),
         tx_subs: SubscriptionStore::default(),
-        txs: TxStore {
-            store: Arc::new(DashMap::new()),
-        },
+        txs: TxStore::default(),
+        tx_lifecycles: TxLifecycleStore::default(),
         epoch_data,
         leader_schedule: Arc::new(RwLock::new(CalculatedSchedule::default())),
     };
@@ -309,6 +470,8 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
         maximum_transaction_in_queue: 20000,
         quic_connection_params: quic_connection_parameters.unwrap_or_default(),
         tpu_connection_path,
+        leader_filter: LeaderFilter::new(leader_allow_list, leader_deny_list)?,
+        static_tpu_targets: parse_static_tpu_targets(static_tpu_targets)?,
     };
 
     let spawner = ServiceSpawner {
@@ -324,8 +487,13 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
     )
     .await?;
     let tx_sender = TxSender::new(data_cache.clone(), tpu_service.clone());
-    let tx_replayer =
-        TransactionReplayer::new(tpu_service.clone(), data_cache.clone(), retry_after);
+    let journal = transaction_journal_path.map(TransactionJournal::new);
+    let tx_replayer = TransactionReplayer::new(
+        tpu_service.clone(),
+        data_cache.clone(),
+        retry_after,
+        replay_strategy,
+    );
     let (transaction_service, tx_service_jh) = spawner.spawn_tx_service(
         tx_sender,
         tx_replayer,
@@ -333,13 +501,90 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
         DEFAULT_MAX_NUMBER_OF_TXS_IN_QUEUE,
         notification_channel.clone(),
         maximum_retries_per_tx,
+        durable_nonce_time_budget_secs.map(Duration::from_secs),
+        journal.clone(),
         slot_notifier.resubscribe(),
     );
+    if let Some(journal) = &journal {
+        let (restored, dropped) = transaction_service
+            .restore_journal_entries(journal.load()?)
+            .await?;
+        info!(
+            "Restored {} pending transactions from journal, dropped {} expired",
+            restored, dropped
+        );
+    }
+    let journal_task = start_transaction_journal(journal, data_cache.clone());
 
     let support_service =
         tokio::spawn(async move { spawner.spawn_support_services(prometheus_addr).await });
 
-    let history = History::new();
+    let (block_storage_reader, block_storage_writer) = create_block_storage(
+        blockstore_postgres.clone(),
+        blockstore_local_path,
+        enable_blockstore_writer,
+        blockstore_compression,
+        data_cache.epoch_data.clone(),
+    )
+    .await?;
+
+    let block_persistence_task = start_block_persistence(
+        block_storage_writer,
+        data_cache.epoch_data.clone(),
+        blocks_notifier.resubscribe(),
+    );
+
+    let faithful_rpc_client =
+        faithful_rpc_addr.map(|faithful_rpc_addr| Arc::new(RpcClient::new(faithful_rpc_addr)));
+
+    let epoch_retention_task = start_epoch_retention(
+        enable_blockstore_writer,
+        EpochRetentionPolicy {
+            keep_epochs: blockstore_retention_epochs,
+            max_disk_bytes: blockstore_retention_max_bytes,
+        },
+        blockstore_postgres.clone(),
+        data_cache.epoch_data.clone(),
+        faithful_rpc_client.clone(),
+    )
+    .await?;
+
+    let block_cache = (block_storage_reader.is_some() && blockstore_cache_blocks > 0)
+        .then(|| BlockCache::new(blockstore_cache_blocks));
+    let block_storage = match block_storage_reader {
+        Some(block_storage_reader) => {
+            info!("Serving history from blockstore");
+            if faithful_rpc_client.is_some() {
+                info!("Serving old blocks from faithful_history");
+            }
+            let block_storage_reader: Arc<dyn BlockStorageReader> = match &block_cache {
+                Some(block_cache) => {
+                    info!(
+                        "Caching up to {} blockstore blocks",
+                        blockstore_cache_blocks
+                    );
+                    Arc::new(CachedBlockStorageReader::new(
+                        block_storage_reader,
+                        block_cache.clone(),
+                    ))
+                }
+                None => block_storage_reader,
+            };
+            Some(MultipleStrategyBlockStorage::new(
+                block_storage_reader,
+                faithful_rpc_client,
+            ))
+        }
+        None => {
+            if faithful_rpc_client.is_some() {
+                warn!("Ignoring faithful_history endpoint as the blockstore is disabled");
+            }
+            info!("Blockstore disabled - serving history from recent blocks only");
+            None
+        }
+    };
+    let history = History::new(block_storage, block_cache, NB_SLOTS_TRANSACTIONS_TO_CACHE);
+    let history_task = history.start_listening(blocks_notifier.resubscribe());
 
     let rpc_service = LiteBridge::new(
         rpc_client.clone(),
@@ -349,6 +594,12 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
         block_priofees_service.clone(),
         account_priofees_service.clone(),
         accounts_service.clone(),
+        AdmissionControl {
+            pressure_threshold: admission_queue_threshold
+                .unwrap_or(DEFAULT_MAX_NUMBER_OF_TXS_IN_QUEUE / 2),
+            queue_capacity: DEFAULT_MAX_NUMBER_OF_TXS_IN_QUEUE,
+            priority_percentile: admission_priority_percentile,
+        },
     );
 
     let pubsub_service = LitePubSubBridge::new(
@@ -398,6 +649,18 @@ pub async fn start_lite_rpc(args: Config, rpc_client: Arc<RpcClient>) -> anyhow:
         res = account_priofees_task => {
             anyhow::bail!("account prioritization fees task failed {res:?}")
         }
+        res = history_task => {
+            anyhow::bail!("History service failed {res:?}")
+        }
+        res = block_persistence_task => {
+            anyhow::bail!("Block persistence service failed {res:?}")
+        }
+        res = epoch_retention_task => {
+            anyhow::bail!("Blockstore epoch retention failed {res:?}")
+        }
+        res = journal_task => {
+            anyhow::bail!("Transaction journal failed {res:?}")
+        }
     }
 }
 
@@ -461,6 +724,29 @@ fn configure_tpu_connection_path(quic_proxy_addr: Option<String>) -> TpuConnecti
     }
 }
 
+fn parse_static_tpu_targets(
+    static_tpu_targets: Option<String>,
+) -> anyhow::Result<Vec<StaticTpuTarget>> {
+    let Some(static_tpu_targets) = static_tpu_targets else {
+        return Ok(vec![]);
+    };
+    serde_json::from_str::<Vec<StaticTpuTargetConfig>>(&static_tpu_targets)
+        .context("Static tpu targets should be valid")?
+        .into_iter()
+        .map(|target| {
+            let identity = Pubkey::from_str(&target.identity)
+                .with_context(|| format!("invalid static tpu target {}", target.identity))?;
+            let tpu_address = parse_host_port(&target.tpu_address).map_err(anyhow::Error::msg)?;
+            info!("Forwarding transactions to static tpu target {identity} at {tpu_address}");
+            Ok(StaticTpuTarget {
+                identity,
+                tpu_address,
+                stake: target.stake,
+            })
+        })
+        .collect()
+}
+
 fn parse_host_port(host_port: &str) -> Result<SocketAddr, String> {
     let addrs: Vec<_> = host_port
         .to_socket_addrs()
@@ -492,3 +778,50 @@ fn setup_tracing_subscriber() {
         tracing_subscriber::fmt::init();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_static_tpu_targets() {
+        assert!(parse_static_tpu_targets(None).unwrap().is_empty());
+
+        let identity = Pubkey::new_unique();
+        let targets = parse_static_tpu_targets(Some(format!(
+            r#"[{{"identity": "{identity}", "tpu_address": "127.0.0.1:8009", "stake": 1000}},
+                {{"identity": "{identity}", "tpu_address": "127.0.0.1:8010"}}]"#
+        )))
+        .unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                StaticTpuTarget {
+                    identity,
+                    tpu_address: SocketAddr::from(([127, 0, 0, 1], 8009)),
+                    stake: Some(1000),
+                },
+                StaticTpuTarget {
+                    identity,
+                    tpu_address: SocketAddr::from(([127, 0, 0, 1], 8010)),
+                    stake: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_static_tpu_targets() {
+        // not a list
+        assert!(parse_static_tpu_targets(Some("{}".to_string())).is_err());
+        assert!(parse_static_tpu_targets(Some(
+            r#"[{"identity": "not a pubkey", "tpu_address": "127.0.0.1:8009"}]"#.to_string()
+        ))
+        .is_err());
+        assert!(parse_static_tpu_targets(Some(format!(
+            r#"[{{"identity": "{}", "tpu_address": "127.0.0.1"}}]"#,
+            Pubkey::new_unique()
+        )))
+        .is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use jsonrpsee::types::ErrorObjectOwned;
+use solana_sdk::borsh1::try_from_slice_unchecked;
+use solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+
+use crate::rpc_errors::RpcErrors;
+
+// lamports per signature of mainnet; the cluster fee rate is not tracked
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+// same as solana_program_runtime::compute_budget_processing
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// reason for rejecting a transaction before it is sent to the leaders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreflightError {
+    SignatureVerificationFailure,
+    TransactionError(TransactionError),
+}
+
+impl From<PreflightError> for ErrorObjectOwned {
+    // same codes and messages as solana rpc so that clients can handle them
+    fn from(err: PreflightError) -> Self {
+        match err {
+            PreflightError::SignatureVerificationFailure => ErrorObjectOwned::owned(
+                RpcErrors::TransactionSignatureVerificationFailure as i32,
+                "Transaction signature verification failure",
+                None::<()>,
+            ),
+            PreflightError::TransactionError(err) => ErrorObjectOwned::owned(
+                RpcErrors::SendTransactionPreflightFailure as i32,
+                format!("Transaction simulation failed: {}", err),
+                // shape of RpcSimulateTransactionResult
+                Some(serde_json::json!({
+                    "err": err,
+                    "logs": [],
+                    "accounts": null,
+                    "unitsConsumed": 0,
+                    "returnData": null,
+                })),
+            ),
+        }
+    }
+}
+
+pub fn verify_signatures(tx: &VersionedTransaction) -> Result<(), PreflightError> {
+    if tx.verify_with_results().iter().all(|verified| *verified) {
+        Ok(())
+    } else {
+        Err(PreflightError::SignatureVerificationFailure)
+    }
+}
+
+// compute unit limit if set, compute unit price and number of other instructions
+fn parse_compute_budget(tx: &VersionedTransaction) -> (Option<u64>, u64, u64) {
+    let mut compute_unit_limit = None;
+    let mut compute_unit_price = 0;
+    let mut nb_instructions = 0;
+    for ix in tx.message.instructions() {
+        if ix
+            .program_id(tx.message.static_account_keys())
+            .eq(&compute_budget::id())
+        {
+            match try_from_slice_unchecked::<ComputeBudgetInstruction>(ix.data.as_slice()) {
+                Ok(ComputeBudgetInstruction::SetComputeUnitLimit(limit)) => {
+                    compute_unit_limit = Some(limit as u64)
+                }
+                Ok(ComputeBudgetInstruction::SetComputeUnitPrice(price)) => {
+                    compute_unit_price = price
+                }
+                _ => {}
+            }
+        } else {
+            nb_instructions += 1;
+        }
+    }
+    (compute_unit_limit, compute_unit_price, nb_instructions)
+}
+
+// in micro lamports
+pub fn compute_unit_price(tx: &VersionedTransaction) -> u64 {
+    parse_compute_budget(tx).1
+}
+
+// signature fees plus prioritization fees; the fee payer must hold at least that much
+pub fn estimate_fee(tx: &VersionedTransaction) -> u64 {
+    let (compute_unit_limit, compute_unit_price, nb_instructions) = parse_compute_budget(tx);
+    let compute_unit_limit = compute_unit_limit
+        .unwrap_or(nb_instructions * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    let prioritization_fee = (compute_unit_price as u128 * compute_unit_limit as u128)
+        .div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64;
+    let signature_fee = tx.message.header().num_required_signatures as u64 * LAMPORTS_PER_SIGNATURE;
+
+    signature_fee + prioritization_fee
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::{Message, VersionedMessage};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signature, Signer};
+    use solana_sdk::system_instruction;
+
+    fn create_transfer(payer: &Keypair, compute_unit_price: Option<u64>) -> VersionedTransaction {
+        let mut instructions = vec![];
+        if let Some(price) = compute_unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(10_000));
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions.push(system_instruction::transfer(
+            &payer.pubkey(),
+            &Pubkey::new_unique(),
+            1_000,
+        ));
+        let message =
+            Message::new_with_blockhash(&instructions, Some(&payer.pubkey()), &Hash::new_unique());
+        VersionedTransaction::try_new(VersionedMessage::Legacy(message), &[payer]).unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_signature() {
+        let payer = Keypair::new();
+        let tx = create_transfer(&payer, None);
+        assert_eq!(verify_signatures(&tx), Ok(()));
+
+        let mut forged = tx;
+        forged.signatures[0] = Signature::new_unique();
+        assert_eq!(
+            verify_signatures(&forged),
+            Err(PreflightError::SignatureVerificationFailure)
+        );
+    }
+
+    #[test]
+    fn estimates_signature_and_prioritization_fees() {
+        let payer = Keypair::new();
+        assert_eq!(estimate_fee(&create_transfer(&payer, None)), 5000);
+        // 10_000 CU at 250_000 micro lamports
+        assert_eq!(
+            estimate_fee(&create_transfer(&payer, Some(250_000))),
+            5000 + 2500
+        );
+        assert_eq!(
+            compute_unit_price(&create_transfer(&payer, Some(250_000))),
+            250_000
+        );
+    }
+
+    #[test]
+    fn maps_to_solana_error_codes() {
+        let err: ErrorObjectOwned =
+            PreflightError::TransactionError(TransactionError::AlreadyProcessed).into();
+        assert_eq!(err.code(), -32002);
+        assert!(err.data().unwrap().get().contains("AlreadyProcessed"));
+
+        let err: ErrorObjectOwned = PreflightError::SignatureVerificationFailure.into();
+        assert_eq!(err.code(), -32003);
+    }
+}
@@ -1,13 +1,16 @@
-use crate::configs::IsBlockHashValidConfig;
+use crate::configs::{IsBlockHashValidConfig, LiteRpcSendTransactionConfig};
+use crate::rpc_types::{EncodedConfirmedTransaction, SendTransactionResult};
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 use solana_account_decoder::UiAccount;
+use solana_lite_rpc_core::stores::tx_lifecycle_store::TransactionLifecycle;
 use solana_lite_rpc_prioritization_fees::prioritization_fee_calculation_method::PrioritizationFeeCalculationMethod;
 use solana_lite_rpc_prioritization_fees::rpc_data::{AccountPrioFeesStats, PrioFeesStats};
 use solana_rpc_client_api::config::{
-    RpcAccountInfoConfig, RpcBlocksConfigWrapper, RpcContextConfig, RpcGetVoteAccountsConfig,
-    RpcLeaderScheduleConfig, RpcProgramAccountsConfig, RpcRequestAirdropConfig,
-    RpcSendTransactionConfig, RpcSignatureStatusConfig, RpcSignaturesForAddressConfig,
+    RpcAccountInfoConfig, RpcBlockConfig, RpcBlocksConfigWrapper, RpcContextConfig,
+    RpcEncodingConfigWrapper, RpcGetVoteAccountsConfig, RpcLeaderScheduleConfig,
+    RpcProgramAccountsConfig, RpcRequestAirdropConfig, RpcSignatureStatusConfig,
+    RpcSignaturesForAddressConfig, RpcTransactionConfig,
 };
 use solana_rpc_client_api::response::{
     OptionalContext, Response as RpcResponse, RpcBlockhash,
@@ -28,7 +31,11 @@ pub trait LiteRpc {
     // ***********************
 
     #[method(name = "getBlock")]
-    async fn get_block(&self, slot: u64) -> RpcResult<Option<UiConfirmedBlock>>;
+    async fn get_block(
+        &self,
+        slot: u64,
+        config: Option<RpcEncodingConfigWrapper<RpcBlockConfig>>,
+    ) -> RpcResult<Option<UiConfirmedBlock>>;
 
     #[method(name = "getBlocks")]
     async fn get_blocks(
@@ -38,6 +45,14 @@ pub trait LiteRpc {
         commitment: Option<CommitmentConfig>,
     ) -> RpcResult<Vec<Slot>>;
 
+    #[method(name = "getBlocksWithLimit")]
+    async fn get_blocks_with_limit(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+        commitment: Option<CommitmentConfig>,
+    ) -> RpcResult<Vec<Slot>>;
+
     #[method(name = "getSignaturesForAddress")]
     async fn get_signatures_for_address(
         &self,
@@ -45,14 +60,12 @@ pub trait LiteRpc {
         config: Option<RpcSignaturesForAddressConfig>,
     ) -> RpcResult<Vec<RpcConfirmedTransactionStatusWithSignature>>;
 
-    // issue:  solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta does not implement Clone
-    //
-    //#[method(name = "getTransaction")]
-    //async fn get_transaction(
-    //    &self,
-    //    signature_str: String,
-    //    config: Option<RpcEncodingConfigWrapper<RpcTransactionConfig>>,
-    //) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>>;
+    #[method(name = "getTransaction")]
+    async fn get_transaction(
+        &self,
+        signature_str: String,
+        config: Option<RpcEncodingConfigWrapper<RpcTransactionConfig>>,
+    ) -> RpcResult<Option<EncodedConfirmedTransaction>>;
 
     // ***********************
     // Cluster Domain
@@ -133,9 +146,26 @@ pub trait LiteRpc {
     async fn send_transaction(
         &self,
         tx: String,
-        send_transaction_config: Option<RpcSendTransactionConfig>,
+        send_transaction_config: Option<LiteRpcSendTransactionConfig>,
     ) -> RpcResult<String>;
 
+    // sends a batch of transactions with one config
+    // (this is special method not available in solana rpc)
+    #[method(name = "sendTransactions")]
+    async fn send_transactions(
+        &self,
+        txs: Vec<String>,
+        send_transaction_config: Option<LiteRpcSendTransactionConfig>,
+    ) -> RpcResult<Vec<SendTransactionResult>>;
+
+    // what lite-rpc did with a transaction it received
+    // (this is special method not available in solana rpc)
+    #[method(name = "getTransactionLifecycle")]
+    async fn get_transaction_lifecycle(
+        &self,
+        signature: String,
+    ) -> RpcResult<Option<TransactionLifecycle>>;
+
     // ***********************
     // Deprecated
     // ***********************
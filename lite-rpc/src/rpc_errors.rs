@@ -3,4 +3,12 @@
 pub enum RpcErrors {
     // Account does not satisfy any account filters or account does not exists.
     AccountNotFound = 0,
+    // same code as solana rpc JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION
+    UnsupportedTransactionVersion = -32015,
+    // same code as solana rpc JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE
+    SendTransactionPreflightFailure = -32002,
+    // same code as solana rpc JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_VERIFICATION_FAILURE
+    TransactionSignatureVerificationFailure = -32003,
+    // lite-rpc specific, outside of the codes used by solana rpc
+    TransactionNotAdmitted = -32090,
 }
@@ -35,7 +35,6 @@ pub trait LiteRpcPubSub {
         config: Option<RpcTransactionLogsConfig>,
     ) -> SubscriptionResult;
 
-    // WARN: enable_received_notification: bool is ignored
     #[subscription(name = "signatureSubscribe" => "signatureNotification", unsubscribe="signatureUnsubscribe", item=String)]
     async fn signature_subscribe(
         &self,
@@ -43,6 +42,10 @@ pub trait LiteRpcPubSub {
         config: RpcSignatureSubscribeConfig,
     ) -> SubscriptionResult;
 
+    /// streams every lifecycle event of a transaction sent through lite-rpc until it is finalized or expired
+    #[subscription(name = "transactionLifecycleSubscribe" => "transactionLifecycleNotification", unsubscribe="transactionLifecycleUnsubscribe", item=String)]
+    async fn transaction_lifecycle_subscribe(&self, signature: String) -> SubscriptionResult;
+
     #[subscription(name = "slotUpdatesSubscribe" => "slotUpdatesNotification", unsubscribe="slotUpdatesUnsubscribe", item=String)]
     async fn slot_updates_subscribe(&self) -> SubscriptionResult;
 
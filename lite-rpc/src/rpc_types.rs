@@ -0,0 +1,36 @@
+use jsonrpsee::types::ErrorObjectOwned;
+use serde::{Deserialize, Serialize};
+use solana_sdk::clock::UnixTimestamp;
+use solana_sdk::slot_history::Slot;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransactionWithStatusMeta,
+};
+
+// same json format as solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta
+// which cannot be used as rpc response because it does not implement Clone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodedConfirmedTransaction {
+    pub slot: Slot,
+    #[serde(flatten)]
+    pub transaction: EncodedTransactionWithStatusMeta,
+    pub block_time: Option<UnixTimestamp>,
+}
+
+impl From<EncodedConfirmedTransactionWithStatusMeta> for EncodedConfirmedTransaction {
+    fn from(value: EncodedConfirmedTransactionWithStatusMeta) -> Self {
+        Self {
+            slot: value.slot,
+            transaction: value.transaction,
+            block_time: value.block_time,
+        }
+    }
+}
+
+/// outcome of one transaction of a sendTransactions batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SendTransactionResult {
+    Signature(String),
+    Error(ErrorObjectOwned),
+}
@@ -10,6 +10,7 @@ use solana_lite_rpc_services::{
     metrics_capture::MetricsCapture,
     prometheus_sync::PrometheusSync,
     tpu_utils::tpu_service::TpuService,
+    transaction_journal::TransactionJournal,
     transaction_replayer::TransactionReplayer,
     transaction_service::{TransactionService, TransactionServiceBuilder},
     tx_sender::TxSender,
@@ -71,6 +72,8 @@ impl ServiceSpawner {
         max_nb_txs_in_queue: usize,
         notifier: Option<NotificationSender>,
         max_retries: usize,
+        durable_nonce_time_budget: Option<Duration>,
+        journal: Option<TransactionJournal>,
         slot_notifications: SlotStream,
     ) -> (TransactionService, AnyhowJoinHandle) {
         let service_builder = TransactionServiceBuilder::new(
@@ -83,6 +86,8 @@ impl ServiceSpawner {
             notifier,
             self.data_cache.block_information_store.clone(),
             max_retries,
+            durable_nonce_time_budget,
+            journal,
             slot_notifications,
         )
     }
@@ -633,6 +633,7 @@ async fn start_literpc_client_proxy_mode(
         .update_connection(
             transaction_receiver,
             connections_to_keep,
+            DataCache::new_for_tests(),
             QUIC_CONNECTION_PARAMS,
         )
         .await;
@@ -758,6 +759,7 @@ pub fn build_raw_sample_tx(i: u32) -> SentTransactionInfo {
         transaction,
         last_valid_block_height: 300,
         prioritization_fee: 0,
+        leader_filter: None,
     }
 }
 
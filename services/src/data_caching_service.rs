@@ -112,12 +112,18 @@ impl DataCachingService {
                             }
                         }
                     }
+                    data_cache.tx_lifecycles.record_landed(
+                        &tx.signature,
+                        block.slot,
+                        block.commitment_config.commitment,
+                    );
                     // notify
                     data_cache
                         .tx_subs
                         .notify(block.slot, tx, block.commitment_config)
                         .await;
                 }
+                data_cache.expire_transactions().await;
             }
         });
 
@@ -4,6 +4,7 @@ pub mod prometheus_sync;
 pub mod quic_connection;
 pub mod quic_connection_utils;
 pub mod tpu_utils;
+pub mod transaction_journal;
 pub mod transaction_replayer;
 pub mod transaction_service;
 pub mod tx_sender;
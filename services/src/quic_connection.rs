@@ -5,6 +5,7 @@ use futures::FutureExt;
 use log::warn;
 use prometheus::{core::GenericGauge, opts, register_int_gauge};
 use quinn::{Connection, Endpoint, VarInt};
+use solana_lite_rpc_core::stores::tx_lifecycle_store::ForwardOutcome;
 use solana_lite_rpc_core::structures::rotating_queue::RotatingQueue;
 use solana_sdk::pubkey::Pubkey;
 use std::{
@@ -125,8 +126,13 @@ impl QuicConnection {
         }
     }
 
-    pub async fn send_transaction(&self, tx: &Vec<u8>, mut exit_notify: broadcast::Receiver<()>) {
+    pub async fn send_transaction(
+        &self,
+        tx: &Vec<u8>,
+        mut exit_notify: broadcast::Receiver<()>,
+    ) -> ForwardOutcome {
         let connection_retry_count = self.connection_params.connection_retry_count;
+        let mut outcome = ForwardOutcome::Cancelled;
         for _ in 0..connection_retry_count {
             let mut do_retry = false;
 
@@ -171,20 +177,25 @@ impl QuicConnection {
                         match write_add_result {
                             Ok(()) => {
                                 SEND_TRANSCTION_SUCESSFUL.inc();
+                                outcome = ForwardOutcome::Sent;
                             }
                             Err(QuicConnectionError::ConnectionError { retry }) => {
                                 do_retry = retry;
+                                outcome = ForwardOutcome::ConnectionError;
                             }
                             Err(QuicConnectionError::TimeOut) => {
                                 self.timeout_counters.fetch_add(1, Ordering::Relaxed);
+                                outcome = ForwardOutcome::Timeout;
                             }
                         }
                     }
                     Err(QuicConnectionError::ConnectionError { retry }) => {
                         do_retry = retry;
+                        outcome = ForwardOutcome::ConnectionError;
                     }
                     Err(QuicConnectionError::TimeOut) => {
                         self.timeout_counters.fetch_add(1, Ordering::Relaxed);
+                        outcome = ForwardOutcome::Timeout;
                     }
                 }
                 if do_retry {
@@ -194,6 +205,7 @@ impl QuicConnection {
                 }
             } else {
                 NB_QUIC_COULDNOT_ESTABLISH_CONNECTION.inc();
+                outcome = ForwardOutcome::NoConnection;
                 log::debug!(
                     "Could not establish connection with {}",
                     self.identity.to_string()
@@ -204,6 +216,7 @@ impl QuicConnection {
                 break;
             }
         }
+        outcome
     }
 
     pub fn get_timeout_count(&self) -> u64 {
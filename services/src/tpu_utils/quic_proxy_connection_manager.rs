@@ -5,6 +5,8 @@ use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
 
 use anyhow::bail;
+use solana_lite_rpc_core::stores::data_cache::DataCache;
+use solana_lite_rpc_core::stores::tx_lifecycle_store::ForwardOutcome;
 use solana_lite_rpc_core::structures::transaction_sent_info::SentTransactionInfo;
 use std::time::Duration;
 
@@ -21,6 +23,7 @@ use solana_lite_rpc_core::network_utils::apply_gso_workaround;
 use solana_lite_rpc_core::structures::proxy_request_format::{TpuForwardingRequest, TxData};
 
 use crate::tpu_utils::quinn_auto_reconnect::AutoReconnect;
+use crate::tpu_utils::tpu_service::NB_TXS_SKIPPED_FOR_LEADER;
 
 #[derive(Clone, Copy, Debug)]
 pub struct TpuNode {
@@ -65,6 +68,7 @@ impl QuicProxyConnectionManager {
         broadcast_receiver: Receiver<SentTransactionInfo>,
         // for duration of this slot these tpu nodes will receive the transactions
         connections_to_keep: HashMap<Pubkey, SocketAddr>,
+        data_cache: DataCache,
         connection_parameters: QuicConnectionParameters,
     ) {
         debug!(
@@ -108,6 +112,7 @@ impl QuicProxyConnectionManager {
             self.proxy_addr,
             self.endpoint.clone(),
             exit_signal,
+            data_cache,
             connection_parameters,
         ));
     }
@@ -159,6 +164,7 @@ impl QuicProxyConnectionManager {
         proxy_addr: SocketAddr,
         endpoint: Endpoint,
         exit_signal: Arc<AtomicBool>,
+        data_cache: DataCache,
         connection_parameters: QuicConnectionParameters,
     ) {
         let auto_connection = AutoReconnect::new(endpoint, proxy_addr);
@@ -173,31 +179,19 @@ impl QuicProxyConnectionManager {
             tokio::select! {
                 tx = transaction_receiver.recv() => {
 
-                    let first_tx: TxData = match tx {
-                        Ok(SentTransactionInfo{
-                            signature,
-                            transaction,
-                            ..
-                        }) => {
-                            let transaction = transaction.as_ref().clone();
-                            TxData::new(signature, transaction)
-                        },
+                    let first_tx = match tx {
+                        Ok(transaction_sent_info) => transaction_sent_info,
                         Err(e) => {
                             warn!("Broadcast channel error (close) on recv: {} - aborting", e);
                             return;
                         }
                     };
 
-                    let mut txs: Vec<TxData> = vec![first_tx];
+                    let mut txs: Vec<SentTransactionInfo> = vec![first_tx];
                     for _ in 1..connection_parameters.number_of_transactions_per_unistream {
                         match transaction_receiver.try_recv() {
-                            Ok(SentTransactionInfo{
-                                signature,
-                                transaction,
-                                ..
-                            }) => {
-                                let transaction = transaction.as_ref().clone();
-                                txs.push(TxData::new(signature, transaction));
+                            Ok(transaction_sent_info) => {
+                                txs.push(transaction_sent_info);
                             },
                             Err(TryRecvError::Empty) => {
                                 break;
@@ -217,17 +211,20 @@ impl QuicProxyConnectionManager {
                         continue;
                     }
 
-                    trace!("Sending copy of transaction batch of {} txs to {} tpu nodes via quic proxy",
-                            txs.len(), tpu_fanout_nodes.len());
-
-                    let send_result =
-                        Self::send_copy_of_txs_to_quicproxy(
-                            &txs, &auto_connection,
-                            proxy_addr,
-                            tpu_fanout_nodes)
-                        .await;
-                    if let Err(e) = send_result {
-                        warn!("Failed to send copy of txs to quic proxy - skip (error {})", e);
+                    for (txs, tpu_nodes) in Self::group_by_tpu_nodes(txs, tpu_fanout_nodes) {
+                        trace!("Sending copy of transaction batch of {} txs to {} tpu nodes via quic proxy",
+                                txs.len(), tpu_nodes.len());
+
+                        let send_result =
+                            Self::send_copy_of_txs_to_quicproxy(
+                                &txs, &auto_connection,
+                                proxy_addr,
+                                tpu_nodes,
+                                &data_cache)
+                            .await;
+                        if let Err(e) = send_result {
+                            warn!("Failed to send copy of txs to quic proxy - skip (error {})", e);
+                        }
                     }
 
                 },
@@ -235,18 +232,50 @@ impl QuicProxyConnectionManager {
         } // -- loop
     }
 
+    // transactions with their own leader lists are forwarded only to the tpu nodes they allow
+    fn group_by_tpu_nodes(
+        txs: Vec<SentTransactionInfo>,
+        tpu_fanout_nodes: Vec<TpuNode>,
+    ) -> Vec<(Vec<TxData>, Vec<TpuNode>)> {
+        let to_tx_data =
+            |tx: SentTransactionInfo| TxData::new(tx.signature, tx.transaction.as_ref().clone());
+        let (unrestricted, restricted): (Vec<_>, Vec<_>) =
+            txs.into_iter().partition(|tx| tx.leader_filter.is_none());
+
+        let mut groups = vec![];
+        for tx in restricted {
+            let tpu_nodes = tpu_fanout_nodes
+                .iter()
+                .filter(|tpu| tx.allows_leader(&tpu.tpu_identity))
+                .copied()
+                .collect_vec();
+            NB_TXS_SKIPPED_FOR_LEADER.inc_by((tpu_fanout_nodes.len() - tpu_nodes.len()) as u64);
+            if !tpu_nodes.is_empty() {
+                groups.push((vec![to_tx_data(tx)], tpu_nodes));
+            }
+        }
+        if !unrestricted.is_empty() {
+            groups.push((
+                unrestricted.into_iter().map(to_tx_data).collect_vec(),
+                tpu_fanout_nodes,
+            ));
+        }
+        groups
+    }
+
     async fn send_copy_of_txs_to_quicproxy(
         txs: &[TxData],
         auto_connection: &AutoReconnect,
         _proxy_address: SocketAddr,
         tpu_fanout_nodes: Vec<TpuNode>,
+        data_cache: &DataCache,
     ) -> anyhow::Result<()> {
         let tpu_data = tpu_fanout_nodes
             .iter()
             .map(|tpu| (tpu.tpu_address, tpu.tpu_identity))
             .collect_vec();
 
-        for chunk in txs.chunks(CHUNK_SIZE_PER_STREAM) {
+        for (chunk_index, chunk) in txs.chunks(CHUNK_SIZE_PER_STREAM).enumerate() {
             let forwarding_request = TpuForwardingRequest::new(&tpu_data, chunk);
             debug!("forwarding_request: {}", forwarding_request);
 
@@ -258,8 +287,21 @@ impl QuicProxyConnectionManager {
             match send_result {
                 Ok(()) => {
                     debug!("Successfully sent {} txs to quic proxy", txs.len());
+                    Self::record_forwards(
+                        data_cache,
+                        chunk,
+                        &tpu_fanout_nodes,
+                        ForwardOutcome::Sent,
+                    );
                 }
                 Err(e) => {
+                    // the remaining chunks are not sent either
+                    Self::record_forwards(
+                        data_cache,
+                        &txs[chunk_index * CHUNK_SIZE_PER_STREAM..],
+                        &tpu_fanout_nodes,
+                        ForwardOutcome::ConnectionError,
+                    );
                     bail!("Failed to send data to quic proxy: {:?}", e);
                 }
             }
@@ -267,4 +309,79 @@ impl QuicProxyConnectionManager {
 
         Ok(())
     }
+
+    // the proxy sends to every tpu node of the request, sent means it was handed to the proxy
+    fn record_forwards(
+        data_cache: &DataCache,
+        txs: &[TxData],
+        tpu_nodes: &[TpuNode],
+        outcome: ForwardOutcome,
+    ) {
+        let slot = data_cache.slot_cache.get_current_slot();
+        for tx in txs {
+            for tpu in tpu_nodes {
+                data_cache.tx_lifecycles.record_forward(
+                    tx.signature(),
+                    &tpu.tpu_identity,
+                    slot,
+                    outcome,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Signature;
+
+    #[test]
+    fn records_forward_per_tpu_node() {
+        let data_cache = DataCache::new_for_tests();
+        let signatures = (0..3).map(|_| Signature::new_unique()).collect_vec();
+        for signature in &signatures {
+            data_cache.tx_lifecycles.record_received(*signature, 0, 300);
+        }
+        let txs = signatures
+            .iter()
+            .map(|signature| TxData::new(*signature, vec![1, 2, 3]))
+            .collect_vec();
+        let tpu_nodes = (0..2)
+            .map(|port| TpuNode {
+                tpu_identity: Pubkey::new_unique(),
+                tpu_address: SocketAddr::from(([127, 0, 0, 1], 1000 + port)),
+            })
+            .collect_vec();
+
+        QuicProxyConnectionManager::record_forwards(
+            &data_cache,
+            &txs[1..],
+            &tpu_nodes,
+            ForwardOutcome::ConnectionError,
+        );
+
+        assert!(data_cache
+            .tx_lifecycles
+            .get(&signatures[0])
+            .unwrap()
+            .forwards
+            .is_empty());
+        for signature in &signatures[1..] {
+            let forwards = data_cache.tx_lifecycles.get(signature).unwrap().forwards;
+            assert_eq!(
+                forwards
+                    .iter()
+                    .map(|forward| forward.leader_identity.clone())
+                    .collect_vec(),
+                tpu_nodes
+                    .iter()
+                    .map(|tpu| tpu.tpu_identity.to_string())
+                    .collect_vec()
+            );
+            assert!(forwards
+                .iter()
+                .all(|forward| forward.outcome == ForwardOutcome::ConnectionError));
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use dashmap::DashMap;
+use itertools::Itertools;
 use log::{error, trace};
 use prometheus::{
     core::GenericGauge, histogram_opts, opts, register_histogram, register_int_gauge, Histogram,
@@ -22,6 +23,7 @@ use tokio::sync::{
 use crate::{
     quic_connection::{PooledConnection, QuicConnectionPool},
     quic_connection_utils::{QuicConnectionParameters, QuicConnectionUtils},
+    tpu_utils::tpu_service::{StaticTpuTarget, NB_TXS_SKIPPED_FOR_LEADER},
 };
 
 lazy_static::lazy_static! {
@@ -29,6 +31,8 @@ lazy_static::lazy_static! {
         register_int_gauge!(opts!("literpc_nb_active_connections", "Number quic tasks that are running")).unwrap();
     static ref NB_CONNECTIONS_TO_KEEP: GenericGauge<prometheus::core::AtomicI64> =
         register_int_gauge!(opts!("literpc_connections_to_keep", "Number of connections to keep asked by tpu service")).unwrap();
+    static ref NB_STATIC_CONNECTIONS: GenericGauge<prometheus::core::AtomicI64> =
+        register_int_gauge!(opts!("literpc_static_tpu_connections", "Number of connections to static tpu targets")).unwrap();
     static ref NB_QUIC_TASKS: GenericGauge<prometheus::core::AtomicI64> =
         register_int_gauge!(opts!("literpc_quic_tasks", "Number of connections to keep asked by tpu service")).unwrap();
     static ref TT_SENT_TIMER: Histogram = register_histogram!(histogram_opts!(
@@ -122,6 +126,10 @@ impl ActiveConnection {
                     };
                     match tx {
                         Ok(transaction_sent_info) => {
+                            if !transaction_sent_info.allows_leader(&identity) {
+                                NB_TXS_SKIPPED_FOR_LEADER.inc();
+                                continue;
+                            }
                             if data_cache
                                 .check_if_confirmed_or_expired_blockheight(&transaction_sent_info)
                             {
@@ -198,6 +206,7 @@ impl ActiveConnection {
                             },
                         };
                         let exit_notifier = self.exit_notifier.subscribe();
+                        let data_cache = self.data_cache.clone();
 
                         tokio::spawn(async move {
                             // permit will be used to send all the transaction and then destroyed
@@ -206,9 +215,16 @@ impl ActiveConnection {
 
                             NB_QUIC_TASKS.inc();
 
-                            connection.send_transaction(tx.transaction.as_ref(), exit_notifier).await;
+                            let outcome = connection.send_transaction(tx.transaction.as_ref(), exit_notifier).await;
                             timer.observe_duration();
                             NB_QUIC_TASKS.dec();
+
+                            data_cache.tx_lifecycles.record_forward(
+                                &tx.signature,
+                                &identity,
+                                data_cache.slot_cache.get_current_slot(),
+                                outcome,
+                            );
                         });
                     }
                 },
@@ -263,11 +279,40 @@ impl TpuConnectionManager {
         &self,
         broadcast_sender: Arc<Sender<SentTransactionInfo>>,
         connections_to_keep: HashMap<Pubkey, SocketAddr>,
+        static_tpu_targets: &[StaticTpuTarget],
         identity_stakes: IdentityStakesData,
         data_cache: DataCache,
         connection_parameters: QuicConnectionParameters,
     ) {
         NB_CONNECTIONS_TO_KEEP.set(connections_to_keep.len() as i64);
+        // static targets keep their connection whatever the leader schedule is
+        for target in static_tpu_targets {
+            if self
+                .identity_to_active_connection
+                .get(&target.identity)
+                .is_none()
+            {
+                trace!(
+                    "added a connection for static target {}, {}",
+                    target.identity,
+                    target.tpu_address
+                );
+                let active_connection = ActiveConnection::new(
+                    self.endpoints.clone(),
+                    target.tpu_address,
+                    target.identity,
+                    data_cache.clone(),
+                    connection_parameters,
+                );
+                active_connection.start_listening(
+                    broadcast_sender.subscribe(),
+                    target.identity_stakes(identity_stakes),
+                );
+                self.identity_to_active_connection
+                    .insert(target.identity, active_connection);
+            }
+        }
+
         for (identity, socket_addr) in &connections_to_keep {
             if self.identity_to_active_connection.get(identity).is_none() {
                 trace!("added a connection for {}, {}", identity, socket_addr);
@@ -288,7 +333,11 @@ impl TpuConnectionManager {
 
         // remove connections which are no longer needed
         self.identity_to_active_connection.retain(|key, value| {
-            if !connections_to_keep.contains_key(key) {
+            if !connections_to_keep.contains_key(key)
+                && !static_tpu_targets
+                    .iter()
+                    .any(|target| target.identity == *key)
+            {
                 trace!("removing a connection for {}", key.to_string());
                 // ignore error for exit channel
                 let _ = value.exit_notifier.send(());
@@ -297,5 +346,117 @@ impl TpuConnectionManager {
                 true
             }
         });
+        NB_STATIC_CONNECTIONS.set(
+            static_tpu_targets
+                .iter()
+                .map(|target| target.identity)
+                .unique()
+                .filter(|identity| self.identity_to_active_connection.contains_key(identity))
+                .count() as i64,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use solana_streamer::tls_certificates::new_self_signed_tls_certificate;
+    use std::collections::HashSet;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn create_static_target(port: u16) -> StaticTpuTarget {
+        StaticTpuTarget {
+            identity: Pubkey::new_unique(),
+            tpu_address: SocketAddr::from(([127, 0, 0, 1], port)),
+            stake: None,
+        }
+    }
+
+    async fn update_connections(
+        connection_manager: &TpuConnectionManager,
+        connections_to_keep: HashMap<Pubkey, SocketAddr>,
+        static_tpu_targets: &[StaticTpuTarget],
+    ) -> HashSet<Pubkey> {
+        let (sender, _) = broadcast::channel(10);
+        connection_manager
+            .update_connections(
+                Arc::new(sender),
+                connections_to_keep,
+                static_tpu_targets,
+                IdentityStakesData::default(),
+                DataCache::new_for_tests(),
+                QuicConnectionParameters::default(),
+            )
+            .await;
+        connection_manager
+            .identity_to_active_connection
+            .iter()
+            .map(|entry| *entry.key())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn keeps_static_targets_across_leader_rotation() {
+        let (certificate, key) =
+            new_self_signed_tls_certificate(&Keypair::new(), IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)))
+                .unwrap();
+        let connection_manager = TpuConnectionManager::new(certificate, key, 1).await;
+        let static_tpu_targets = vec![create_static_target(1000), create_static_target(1001)];
+        let first_leader = Pubkey::new_unique();
+        let second_leader = Pubkey::new_unique();
+
+        let identities = update_connections(
+            &connection_manager,
+            HashMap::from([(first_leader, SocketAddr::from(([127, 0, 0, 1], 1002)))]),
+            &static_tpu_targets,
+        )
+        .await;
+        assert_eq!(
+            identities,
+            HashSet::from([
+                static_tpu_targets[0].identity,
+                static_tpu_targets[1].identity,
+                first_leader
+            ])
+        );
+
+        // the previous leader is dropped, the static targets stay
+        let identities = update_connections(
+            &connection_manager,
+            HashMap::from([(second_leader, SocketAddr::from(([127, 0, 0, 1], 1003)))]),
+            &static_tpu_targets,
+        )
+        .await;
+        assert_eq!(
+            identities,
+            HashSet::from([
+                static_tpu_targets[0].identity,
+                static_tpu_targets[1].identity,
+                second_leader
+            ])
+        );
+        assert_eq!(NB_STATIC_CONNECTIONS.get(), 2);
+
+        // static targets survive an empty leader schedule
+        let identities =
+            update_connections(&connection_manager, HashMap::new(), &static_tpu_targets).await;
+        assert_eq!(
+            identities,
+            HashSet::from([
+                static_tpu_targets[0].identity,
+                static_tpu_targets[1].identity
+            ])
+        );
+
+        // a target removed from the configuration is dropped
+        let identities = update_connections(
+            &connection_manager,
+            HashMap::new(),
+            &static_tpu_targets[1..],
+        )
+        .await;
+        assert_eq!(identities, HashSet::from([static_tpu_targets[1].identity]));
+        assert_eq!(NB_STATIC_CONNECTIONS.get(), 1);
     }
 }
@@ -1,5 +1,5 @@
 use anyhow::Context;
-use prometheus::{core::GenericGauge, opts, register_int_gauge};
+use prometheus::{core::GenericGauge, opts, register_int_counter, register_int_gauge, IntCounter};
 
 use super::tpu_connection_manager::TpuConnectionManager;
 use crate::quic_connection_utils::QuicConnectionParameters;
@@ -9,17 +9,21 @@ use crate::tpu_utils::tpu_service::ConnectionManager::{DirectTpu, QuicProxy};
 
 use solana_lite_rpc_core::network_utils::log_gso_workaround;
 use solana_lite_rpc_core::stores::data_cache::DataCache;
+use solana_lite_rpc_core::structures::identity_stakes::IdentityStakesData;
+use solana_lite_rpc_core::structures::leader_filter::LeaderFilter;
 use solana_lite_rpc_core::structures::transaction_sent_info::SentTransactionInfo;
 use solana_lite_rpc_core::traits::leaders_fetcher_interface::LeaderFetcherInterface;
 use solana_lite_rpc_core::types::SlotStream;
 use solana_lite_rpc_core::AnyhowJoinHandle;
-use solana_sdk::{quic::QUIC_PORT_OFFSET, signature::Keypair, slot_history::Slot};
+use solana_sdk::{pubkey::Pubkey, quic::QUIC_PORT_OFFSET, signature::Keypair, slot_history::Slot};
+use solana_streamer::nonblocking::quic::ConnectionPeerType;
 use solana_streamer::tls_certificates::new_self_signed_tls_certificate;
 use std::collections::HashMap;
 use std::{
-    net::{IpAddr, Ipv4Addr},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
     sync::Arc,
 };
+use tokio::sync::watch;
 
 lazy_static::lazy_static! {
     static ref NB_CLUSTER_NODES: GenericGauge<prometheus::core::AtomicI64> =
@@ -30,14 +34,47 @@ lazy_static::lazy_static! {
 
     static ref ESTIMATED_SLOT: GenericGauge<prometheus::core::AtomicI64> =
     register_int_gauge!(opts!("literpc_estimated_slot", "Estimated slot seen by last rpc")).unwrap();
+
+    static ref NB_SKIPPED_LEADERS: GenericGauge<prometheus::core::AtomicI64> =
+    register_int_gauge!(opts!("literpc_skipped_leaders", "Number of leaders in the fanout window skipped by the leader allow and deny lists")).unwrap();
+
+    pub(crate) static ref NB_TXS_SKIPPED_FOR_LEADER: IntCounter =
+    register_int_counter!(opts!("literpc_txs_skipped_for_leader", "Transactions not forwarded to a leader excluded by their leader lists")).unwrap();
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct TpuServiceConfig {
     pub fanout_slots: u64,
     pub maximum_transaction_in_queue: usize,
     pub quic_connection_params: QuicConnectionParameters,
     pub tpu_connection_path: TpuConnectionPath,
+    // leaders excluded by the filter never get a connection
+    pub leader_filter: LeaderFilter,
+    // get every transaction in addition to the upcoming leaders
+    pub static_tpu_targets: Vec<StaticTpuTarget>,
+}
+
+/// TPU endpoint which is not derived from the leader schedule, like a relayer or an own validator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaticTpuTarget {
+    pub identity: Pubkey,
+    // quic port of the tpu
+    pub tpu_address: SocketAddr,
+    // stake the target grants to our identity; the stream limits follow our stake in the cluster if not set
+    pub stake: Option<u64>,
+}
+
+impl StaticTpuTarget {
+    pub fn identity_stakes(&self, identity_stakes: IdentityStakesData) -> IdentityStakesData {
+        match self.stake {
+            Some(stake) => IdentityStakesData {
+                peer_type: ConnectionPeerType::Staked(stake),
+                stakes: stake,
+                ..identity_stakes
+            },
+            None => identity_stakes,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -47,6 +84,8 @@ pub struct TpuService {
     leader_schedule: Arc<dyn LeaderFetcherInterface>,
     config: TpuServiceConfig,
     data_cache: DataCache,
+    // leader of the estimated slot
+    current_leader: Arc<watch::Sender<Option<Pubkey>>>,
 }
 
 #[derive(Clone)]
@@ -95,12 +134,14 @@ impl TpuService {
             }
         };
 
+        let (current_leader, _) = watch::channel(None);
         Ok(Self {
             leader_schedule,
             broadcast_sender: Arc::new(sender),
             connection_manager,
             config,
             data_cache,
+            current_leader: Arc::new(current_leader),
         })
     }
 
@@ -109,6 +150,10 @@ impl TpuService {
         Ok(())
     }
 
+    pub fn subscribe_current_leader(&self) -> watch::Receiver<Option<Pubkey>> {
+        self.current_leader.subscribe()
+    }
+
     // update/reconfigure connections on slot change
     async fn update_quic_connections(
         &self,
@@ -125,6 +170,15 @@ impl TpuService {
             .leader_schedule
             .get_slot_leaders(current_slot, last_slot)
             .await?;
+        let leader = next_leaders
+            .iter()
+            .find(|x| x.leader_slot == estimated_slot)
+            .map(|x| x.pubkey);
+        self.current_leader.send_if_modified(|current_leader| {
+            let changed = *current_leader != leader;
+            *current_leader = leader;
+            changed
+        });
         // get next leader with its tpu port
         let connections_to_keep: HashMap<_, _> = next_leaders
             .iter()
@@ -144,6 +198,12 @@ impl TpuService {
                 (x.0, addr)
             })
             .collect();
+        let nb_leaders = connections_to_keep.len();
+        let connections_to_keep: HashMap<_, _> = connections_to_keep
+            .into_iter()
+            .filter(|(identity, _)| self.config.leader_filter.allows(identity))
+            .collect();
+        NB_SKIPPED_LEADERS.set((nb_leaders - connections_to_keep.len()) as i64);
 
         match &self.connection_manager {
             DirectTpu {
@@ -153,6 +213,7 @@ impl TpuService {
                     .update_connections(
                         self.broadcast_sender.clone(),
                         connections_to_keep,
+                        &self.config.static_tpu_targets,
                         self.data_cache.identity_stakes.get_stakes().await,
                         self.data_cache.clone(),
                         self.config.quic_connection_params,
@@ -162,11 +223,20 @@ impl TpuService {
             QuicProxy {
                 quic_proxy_connection_manager,
             } => {
+                // the proxy manages the connections, so the static targets are just more tpu nodes
+                let mut connections_to_keep = connections_to_keep;
+                connections_to_keep.extend(
+                    self.config
+                        .static_tpu_targets
+                        .iter()
+                        .map(|target| (target.identity, target.tpu_address)),
+                );
                 let transaction_receiver = self.broadcast_sender.subscribe();
                 quic_proxy_connection_manager
                     .update_connection(
                         transaction_receiver,
                         connections_to_keep,
+                        self.data_cache.clone(),
                         self.config.quic_connection_params,
                     )
                     .await;
@@ -0,0 +1,382 @@
+use crate::transaction_replayer::ReplayStrategy;
+use anyhow::{bail, Context};
+use log::{error, info};
+use prometheus::{core::GenericGauge, opts, register_int_gauge};
+use serde::{Deserialize, Serialize};
+use solana_lite_rpc_core::{
+    stores::data_cache::DataCache,
+    structures::{
+        leader_filter::LeaderFilter,
+        transaction_sent_info::{SentTransactionInfo, WireTransaction},
+    },
+    AnyhowJoinHandle,
+};
+use solana_sdk::signature::Signature;
+use solana_sdk::slot_history::Slot;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+lazy_static::lazy_static! {
+    static ref TXS_IN_JOURNAL: GenericGauge<prometheus::core::AtomicI64> =
+        register_int_gauge!(opts!("literpc_txs_in_journal", "Number of pending transactions in the on-disk journal")).unwrap();
+}
+
+// at most this much of the replay state is lost on a crash
+const JOURNAL_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+// journal file layout: magic, format version (u16 little endian), bincode entries
+// files without the magic were written before versioning and use JournalEntryV1
+const JOURNAL_MAGIC: &[u8; 4] = b"LRJN";
+// bump on any change of JournalEntry and keep parsing the older versions
+const JOURNAL_VERSION: u16 = 2;
+
+/// A transaction which was sent but has neither landed nor expired, with its replay state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub signature: Signature,
+    pub slot: Slot,
+    pub transaction: WireTransaction,
+    pub last_valid_block_height: u64,
+    pub prioritization_fee: u64,
+    pub replay_count: usize,
+    pub max_replay: usize,
+    pub strategy: ReplayStrategy,
+    pub leader_filter: Option<LeaderFilter>,
+}
+
+// journal entry before leader filters were added, written without version header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntryV1 {
+    signature: Signature,
+    slot: Slot,
+    transaction: WireTransaction,
+    last_valid_block_height: u64,
+    prioritization_fee: u64,
+    replay_count: usize,
+    max_replay: usize,
+    strategy: ReplayStrategy,
+}
+
+impl From<JournalEntryV1> for JournalEntry {
+    fn from(entry: JournalEntryV1) -> Self {
+        JournalEntry {
+            signature: entry.signature,
+            slot: entry.slot,
+            transaction: entry.transaction,
+            last_valid_block_height: entry.last_valid_block_height,
+            prioritization_fee: entry.prioritization_fee,
+            replay_count: entry.replay_count,
+            max_replay: entry.max_replay,
+            strategy: entry.strategy,
+            leader_filter: None,
+        }
+    }
+}
+
+impl JournalEntry {
+    pub fn transaction_info(&self) -> SentTransactionInfo {
+        SentTransactionInfo {
+            signature: self.signature,
+            slot: self.slot,
+            transaction: Arc::new(self.transaction.clone()),
+            last_valid_block_height: self.last_valid_block_height,
+            prioritization_fee: self.prioritization_fee,
+            leader_filter: self.leader_filter.clone().map(Arc::new),
+        }
+    }
+}
+
+/// On-disk journal of the transactions in flight so that they survive a restart
+/// The pending transactions are kept in memory and written as a whole to the journal file periodically
+#[derive(Clone)]
+pub struct TransactionJournal {
+    path: PathBuf,
+    entries: Arc<Mutex<HashMap<Signature, JournalEntry>>>,
+    dirty: Arc<AtomicBool>,
+}
+
+impl TransactionJournal {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            dirty: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// entries written by a previous run, empty if there is no journal file yet
+    /// a journal which cannot be parsed is moved aside so that it does not prevent the startup
+    pub fn load(&self) -> anyhow::Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        let content = std::fs::read(&self.path)
+            .with_context(|| format!("read transaction journal {}", self.path.display()))?;
+        match Self::decode(&content) {
+            Ok(entries) => Ok(entries),
+            Err(e) => {
+                let corrupt_path = self.path.with_extension("corrupt");
+                error!(
+                    "Cannot parse transaction journal {}, moving it to {} and starting empty: {e:?}",
+                    self.path.display(),
+                    corrupt_path.display()
+                );
+                std::fs::rename(&self.path, &corrupt_path).with_context(|| {
+                    format!("move transaction journal to {}", corrupt_path.display())
+                })?;
+                Ok(vec![])
+            }
+        }
+    }
+
+    fn encode(entries: &[JournalEntry]) -> anyhow::Result<Vec<u8>> {
+        let mut content = JOURNAL_MAGIC.to_vec();
+        content.extend_from_slice(&JOURNAL_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut content, entries)?;
+        Ok(content)
+    }
+
+    fn decode(content: &[u8]) -> anyhow::Result<Vec<JournalEntry>> {
+        let Some(versioned) = content.strip_prefix(JOURNAL_MAGIC) else {
+            let entries = bincode::deserialize::<Vec<JournalEntryV1>>(content)?;
+            return Ok(entries.into_iter().map(JournalEntry::from).collect());
+        };
+        let (Some(version), Some(entries)) = (versioned.get(..2), versioned.get(2..)) else {
+            bail!("transaction journal without format version");
+        };
+        match u16::from_le_bytes([version[0], version[1]]) {
+            JOURNAL_VERSION => Ok(bincode::deserialize::<Vec<JournalEntry>>(entries)?),
+            version => bail!("unsupported transaction journal format version {version}"),
+        }
+    }
+
+    pub fn record(
+        &self,
+        transaction_info: &SentTransactionInfo,
+        replay_count: usize,
+        max_replay: usize,
+        strategy: ReplayStrategy,
+    ) {
+        let entry = JournalEntry {
+            signature: transaction_info.signature,
+            slot: transaction_info.slot,
+            transaction: transaction_info.transaction.as_ref().clone(),
+            last_valid_block_height: transaction_info.last_valid_block_height,
+            prioritization_fee: transaction_info.prioritization_fee,
+            replay_count,
+            max_replay,
+            strategy,
+            leader_filter: transaction_info.leader_filter.as_deref().cloned(),
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(transaction_info.signature, entry);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub fn record_replay(&self, signature: &Signature, replay_count: usize) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(signature) {
+            entry.replay_count = replay_count;
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// removes the transactions for which keep returns false
+    pub fn retain(&self, keep: impl Fn(&JournalEntry) -> bool) {
+        let mut entries = self.entries.lock().unwrap();
+        let length_before = entries.len();
+        entries.retain(|_, entry| keep(entry));
+        if entries.len() != length_before {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().unwrap().is_empty()
+    }
+
+    // write to temp file first to survive crashes while writing
+    // note: blocking, run on the blocking pool from async code
+    pub fn flush(&self) -> anyhow::Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        // snapshot so that senders are not blocked while serializing
+        let snapshot = self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        TXS_IN_JOURNAL.set(snapshot.len() as i64);
+        let content = match Self::encode(&snapshot) {
+            Ok(content) => content,
+            Err(e) => {
+                self.dirty.store(true, Ordering::Relaxed);
+                return Err(e.into());
+            }
+        };
+        let tmp_path = self.path.with_extension("tmp");
+        let written = Self::write_synced(&tmp_path, &content)
+            .with_context(|| format!("write transaction journal {}", tmp_path.display()))
+            .and_then(|_| {
+                std::fs::rename(&tmp_path, &self.path)
+                    .with_context(|| format!("move transaction journal to {}", self.path.display()))
+            });
+        if written.is_err() {
+            // retry with the next flush
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        written
+    }
+
+    // the rename must not become visible before the content is on disk
+    fn write_synced(path: &Path, content: &[u8]) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(content)?;
+        file.sync_all()
+    }
+
+    /// drops landed and expired transactions and flushes the journal periodically
+    pub fn start_service(self, data_cache: DataCache) -> AnyhowJoinHandle {
+        info!("Journaling pending transactions to {}", self.path.display());
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(JOURNAL_FLUSH_INTERVAL);
+            loop {
+                interval.tick().await;
+                let current_blockheight = data_cache.block_information_store.get_last_blockheight();
+                self.retain(|entry| {
+                    entry.last_valid_block_height >= current_blockheight
+                        && !data_cache.txs.is_transaction_confirmed(&entry.signature)
+                });
+                let journal = self.clone();
+                match tokio::task::spawn_blocking(move || journal.flush()).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => error!("Error flushing transaction journal: {e:?}"),
+                    Err(e) => error!("Transaction journal flush task failed: {e:?}"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_transaction_info(last_valid_block_height: u64) -> SentTransactionInfo {
+        SentTransactionInfo {
+            signature: Signature::new_unique(),
+            slot: 10,
+            transaction: Arc::new(vec![1, 2, 3]),
+            last_valid_block_height,
+            prioritization_fee: 500,
+            leader_filter: None,
+        }
+    }
+
+    #[test]
+    fn survives_restart() {
+        let path =
+            std::env::temp_dir().join(format!("transaction-journal-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let journal = TransactionJournal::new(&path);
+        assert!(journal.load().unwrap().is_empty());
+
+        let pending = create_transaction_info(300);
+        let expired = create_transaction_info(100);
+        journal.record(&pending, 0, 5, ReplayStrategy::Exponential);
+        journal.record(&expired, 0, 5, ReplayStrategy::Linear);
+        journal.record_replay(&pending.signature, 2);
+        journal.retain(|entry| entry.last_valid_block_height >= 200);
+        journal.flush().unwrap();
+        assert_eq!(journal.len(), 1);
+
+        let entries = TransactionJournal::new(&path).load().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].transaction_info(), pending);
+        assert_eq!(entries[0].replay_count, 2);
+        assert_eq!(entries[0].max_replay, 5);
+        assert_eq!(entries[0].strategy, ReplayStrategy::Exponential);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn upgrades_journal_without_version() {
+        let path =
+            std::env::temp_dir().join(format!("transaction-journal-v1-{}.bin", std::process::id()));
+        let pending = create_transaction_info(300);
+        let legacy = JournalEntryV1 {
+            signature: pending.signature,
+            slot: pending.slot,
+            transaction: pending.transaction.as_ref().clone(),
+            last_valid_block_height: pending.last_valid_block_height,
+            prioritization_fee: pending.prioritization_fee,
+            replay_count: 3,
+            max_replay: 5,
+            strategy: ReplayStrategy::Linear,
+        };
+        std::fs::write(&path, bincode::serialize(&vec![legacy]).unwrap()).unwrap();
+
+        let journal = TransactionJournal::new(&path);
+        let entries = journal.load().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].transaction_info(), pending);
+        assert_eq!(entries[0].replay_count, 3);
+        assert_eq!(entries[0].leader_filter, None);
+
+        // rewritten in the current format
+        journal.record(&pending, 3, 5, ReplayStrategy::Linear);
+        journal.flush().unwrap();
+        let content = std::fs::read(&path).unwrap();
+        assert!(content.starts_with(JOURNAL_MAGIC));
+        assert_eq!(TransactionJournal::new(&path).load().unwrap().len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_journal_version() {
+        let mut content = JOURNAL_MAGIC.to_vec();
+        content.extend_from_slice(&(JOURNAL_VERSION + 1).to_le_bytes());
+        content.extend_from_slice(&bincode::serialize(&Vec::<JournalEntry>::new()).unwrap());
+        assert!(TransactionJournal::decode(&content).is_err());
+    }
+
+    #[test]
+    fn moves_corrupt_journal_aside() {
+        let path = std::env::temp_dir().join(format!(
+            "transaction-journal-corrupt-{}.bin",
+            std::process::id()
+        ));
+        let corrupt_path = path.with_extension("corrupt");
+        let _ = std::fs::remove_file(&corrupt_path);
+        std::fs::write(&path, [0xff; 7]).unwrap();
+
+        let journal = TransactionJournal::new(&path);
+        assert!(journal.load().unwrap().is_empty());
+        assert!(!path.exists());
+        assert_eq!(std::fs::read(&corrupt_path).unwrap(), vec![0xff; 7]);
+
+        // the next flush starts a fresh journal
+        journal.record(&create_transaction_info(300), 0, 5, ReplayStrategy::Linear);
+        journal.flush().unwrap();
+        assert_eq!(TransactionJournal::new(&path).load().unwrap().len(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&corrupt_path).unwrap();
+    }
+}
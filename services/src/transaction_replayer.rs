@@ -1,11 +1,17 @@
 use crate::tpu_utils::tpu_service::TpuService;
+use crate::transaction_journal::TransactionJournal;
 use anyhow::{bail, Context};
 use log::error;
 use prometheus::{core::GenericGauge, opts, register_int_gauge};
+use serde::{Deserialize, Serialize};
 use solana_lite_rpc_core::{
     stores::data_cache::DataCache, structures::transaction_sent_info::SentTransactionInfo,
     AnyhowJoinHandle,
 };
+use solana_sdk::clock::DEFAULT_MS_PER_SLOT;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::time::Duration;
 use tokio::{
     sync::mpsc::{UnboundedReceiver, UnboundedSender},
@@ -17,18 +23,90 @@ lazy_static::lazy_static! {
         register_int_gauge!(opts!("literpc_messages_in_replay_queue", "Number of transactions waiting for replay")).unwrap();
 }
 
+const MAX_EXPONENTIAL_REPLAY_DELAY: Duration = Duration::from_secs(30);
+const BURST_REPLAY_INTERVAL: Duration = Duration::from_millis(100);
+const LEADER_CHANGE_POLL_INTERVAL: Duration = Duration::from_millis(DEFAULT_MS_PER_SLOT);
+
+/// When transactions which have not landed yet are sent again
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayStrategy {
+    /// after retry_offset, retry_offset*2, retry_offset*3 ...
+    #[default]
+    Linear,
+    /// after retry_offset, retry_offset*2, retry_offset*4 ... up to MAX_EXPONENTIAL_REPLAY_DELAY
+    Exponential,
+    /// each time the leader changes
+    LeaderChange,
+    /// max_replay times in quick succession, then stop
+    Burst,
+}
+
+impl ReplayStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReplayStrategy::Linear => "linear",
+            ReplayStrategy::Exponential => "exponential",
+            ReplayStrategy::LeaderChange => "leaderChange",
+            ReplayStrategy::Burst => "burst",
+        }
+    }
+
+    // delay until the next replay after replay_count replays
+    pub fn replay_delay(&self, retry_offset: Duration, replay_count: usize) -> Duration {
+        let replay_count = replay_count.max(1);
+        match self {
+            ReplayStrategy::Linear => retry_offset.mul_f32(replay_count as f32),
+            ReplayStrategy::Exponential => {
+                let factor = 2u32.saturating_pow(replay_count as u32 - 1);
+                retry_offset
+                    .saturating_mul(factor)
+                    .min(MAX_EXPONENTIAL_REPLAY_DELAY.max(retry_offset))
+            }
+            ReplayStrategy::LeaderChange => LEADER_CHANGE_POLL_INTERVAL,
+            ReplayStrategy::Burst => BURST_REPLAY_INTERVAL,
+        }
+    }
+}
+
+impl FromStr for ReplayStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "").as_str() {
+            "linear" => Ok(ReplayStrategy::Linear),
+            "exponential" => Ok(ReplayStrategy::Exponential),
+            "leaderchange" => Ok(ReplayStrategy::LeaderChange),
+            "burst" => Ok(ReplayStrategy::Burst),
+            _ => bail!(
+                "Unknown replay strategy '{}' - use 'linear', 'exponential', 'leaderChange' or 'burst'",
+                s
+            ),
+        }
+    }
+}
+
+impl Display for ReplayStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TransactionReplay {
     pub transaction: SentTransactionInfo,
     pub replay_count: usize,
     pub max_replay: usize,
     pub replay_at: Instant,
+    pub strategy: ReplayStrategy,
+    // leader when the transaction was last sent, for ReplayStrategy::LeaderChange
+    pub last_leader: Option<Pubkey>,
 }
 
 /// Transaction Replayer
 /// It will replay transaction sent to the cluster if they are not confirmed
 /// They will be replayed max_replay times
-/// The replay time will be linearly increasing by after count * replay after
+/// The replay times depend on the ReplayStrategy of the transaction, by default linearly increasing
 /// So the transasctions will be replayed like retry_after, retry_after*2, retry_after*3 ...
 
 #[derive(Clone)]
@@ -36,14 +114,21 @@ pub struct TransactionReplayer {
     pub tpu_service: TpuService,
     pub data_cache: DataCache,
     pub retry_offset: Duration,
+    pub replay_strategy: ReplayStrategy,
 }
 
 impl TransactionReplayer {
-    pub fn new(tpu_service: TpuService, data_cache: DataCache, retry_offset: Duration) -> Self {
+    pub fn new(
+        tpu_service: TpuService,
+        data_cache: DataCache,
+        retry_offset: Duration,
+        replay_strategy: ReplayStrategy,
+    ) -> Self {
         Self {
             tpu_service,
             data_cache,
             retry_offset,
+            replay_strategy,
         }
     }
 
@@ -51,17 +136,19 @@ impl TransactionReplayer {
         &self,
         sender: UnboundedSender<TransactionReplay>,
         mut reciever: UnboundedReceiver<TransactionReplay>,
+        journal: Option<TransactionJournal>,
     ) -> AnyhowJoinHandle {
         let tpu_service = self.tpu_service.clone();
         let data_cache = self.data_cache.clone();
         let retry_offset = self.retry_offset;
+        let current_leader = self.tpu_service.subscribe_current_leader();
 
         tokio::spawn(async move {
             while let Some(mut tx_replay) = reciever.recv().await {
                 MESSAGES_IN_REPLAY_QUEUE.dec();
                 let now = Instant::now();
                 if now < tx_replay.replay_at {
-                    if tx_replay.replay_at > now + retry_offset {
+                    if tx_replay.replay_at > now + retry_offset.min(LEADER_CHANGE_POLL_INTERVAL) {
                         // requeue the transactions will be replayed after retry_after duration
                         sender.send(tx_replay).context("replay channel closed")?;
                         MESSAGES_IN_REPLAY_QUEUE.inc();
@@ -73,13 +160,36 @@ impl TransactionReplayer {
                     // transaction has already expired or confirmed
                     continue;
                 }
+                if tx_replay.strategy == ReplayStrategy::LeaderChange {
+                    let leader = *current_leader.borrow();
+                    if tx_replay.last_leader.is_none() || tx_replay.last_leader == leader {
+                        // the current leader already got the transaction, check again next slot
+                        tx_replay.last_leader = tx_replay.last_leader.or(leader);
+                        tx_replay.replay_at = Instant::now() + LEADER_CHANGE_POLL_INTERVAL;
+                        sender.send(tx_replay).context("replay channel closed")?;
+                        MESSAGES_IN_REPLAY_QUEUE.inc();
+                        continue;
+                    }
+                    tx_replay.last_leader = leader;
+                }
                 // ignore reset error
                 let _ = tpu_service.send_transaction(&tx_replay.transaction);
+                data_cache
+                    .tx_lifecycles
+                    .record_replay(&tx_replay.transaction.signature, tx_replay.replay_count + 1);
 
                 if tx_replay.replay_count < tx_replay.max_replay {
                     tx_replay.replay_count += 1;
-                    tx_replay.replay_at =
-                        Instant::now() + retry_offset.mul_f32(tx_replay.replay_count as f32);
+                    if let Some(journal) = &journal {
+                        journal.record_replay(
+                            &tx_replay.transaction.signature,
+                            tx_replay.replay_count,
+                        );
+                    }
+                    tx_replay.replay_at = Instant::now()
+                        + tx_replay
+                            .strategy
+                            .replay_delay(retry_offset, tx_replay.replay_count);
                     sender.send(tx_replay).context("replay channel closed")?;
                     MESSAGES_IN_REPLAY_QUEUE.inc();
                 }
@@ -89,3 +199,56 @@ impl TransactionReplayer {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_delays() {
+        let offset = Duration::from_secs(3);
+        let delays = |strategy: ReplayStrategy| {
+            (0..6)
+                .map(|count| strategy.replay_delay(offset, count).as_secs())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(delays(ReplayStrategy::Linear), vec![3, 3, 6, 9, 12, 15]);
+        assert_eq!(
+            delays(ReplayStrategy::Exponential),
+            vec![3, 3, 6, 12, 24, 30]
+        );
+        assert_eq!(
+            ReplayStrategy::Burst.replay_delay(offset, 5),
+            BURST_REPLAY_INTERVAL
+        );
+        assert_eq!(
+            ReplayStrategy::Exponential.replay_delay(offset, 1000),
+            MAX_EXPONENTIAL_REPLAY_DELAY
+        );
+    }
+
+    #[test]
+    fn parse_replay_strategy() {
+        for strategy in [
+            ReplayStrategy::Linear,
+            ReplayStrategy::Exponential,
+            ReplayStrategy::LeaderChange,
+            ReplayStrategy::Burst,
+        ] {
+            assert_eq!(
+                strategy.to_string().parse::<ReplayStrategy>().unwrap(),
+                strategy
+            );
+            assert_eq!(
+                serde_json::from_value::<ReplayStrategy>(serde_json::json!(strategy.as_str()))
+                    .unwrap(),
+                strategy
+            );
+        }
+        assert_eq!(
+            "leader_change".parse::<ReplayStrategy>().unwrap(),
+            ReplayStrategy::LeaderChange
+        );
+        assert!("fibonacci".parse::<ReplayStrategy>().is_err());
+    }
+}
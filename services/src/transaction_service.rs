@@ -5,13 +5,17 @@ use std::{sync::Arc, time::Duration};
 
 use crate::{
     tpu_utils::tpu_service::TpuService,
-    transaction_replayer::{TransactionReplay, TransactionReplayer, MESSAGES_IN_REPLAY_QUEUE},
+    transaction_journal::{JournalEntry, TransactionJournal},
+    transaction_replayer::{
+        ReplayStrategy, TransactionReplay, TransactionReplayer, MESSAGES_IN_REPLAY_QUEUE,
+    },
     tx_sender::TxSender,
 };
 use anyhow::bail;
 use prometheus::{histogram_opts, register_histogram, Histogram};
 use solana_lite_rpc_core::{
-    solana_utils::SerializableTransaction, structures::transaction_sent_info::SentTransactionInfo,
+    solana_utils::SerializableTransaction,
+    structures::{leader_filter::LeaderFilter, transaction_sent_info::SentTransactionInfo},
     types::SlotStream,
 };
 use solana_lite_rpc_core::{
@@ -20,11 +24,13 @@ use solana_lite_rpc_core::{
     AnyhowJoinHandle,
 };
 use solana_sdk::{
+    clock::DEFAULT_MS_PER_SLOT,
+    commitment_config::CommitmentConfig,
     compute_budget::{self, ComputeBudgetInstruction},
     transaction::VersionedTransaction,
 };
 use tokio::{
-    sync::mpsc::{self, Sender, UnboundedSender},
+    sync::mpsc::{self, error::TrySendError, Sender, UnboundedSender},
     time::Instant,
 };
 
@@ -64,6 +70,8 @@ impl TransactionServiceBuilder {
         notifier: Option<NotificationSender>,
         block_information_store: BlockInformationStore,
         max_retries: usize,
+        durable_nonce_time_budget: Option<Duration>,
+        journal: Option<TransactionJournal>,
         slot_notifications: SlotStream,
     ) -> (TransactionService, AnyhowJoinHandle) {
         let (transaction_channel, tx_recv) = mpsc::channel(self.max_nb_txs_in_queue);
@@ -74,6 +82,7 @@ impl TransactionServiceBuilder {
             let tx_replayer = self.tx_replayer.clone();
             let tpu_service = self.tpu_service.clone();
             let replay_channel_task = replay_channel.clone();
+            let journal = journal.clone();
 
             tokio::spawn(async move {
                 let tpu_service_fx = tpu_service.start(slot_notifications);
@@ -81,7 +90,7 @@ impl TransactionServiceBuilder {
                 let tx_sender_jh = tx_sender.clone().execute(tx_recv, notifier.clone());
 
                 let replay_service =
-                    tx_replayer.start_service(replay_channel_task, replay_reciever);
+                    tx_replayer.start_service(replay_channel_task, replay_reciever, journal);
 
                 tokio::select! {
                     res = tpu_service_fx => {
@@ -104,12 +113,26 @@ impl TransactionServiceBuilder {
                 block_information_store,
                 max_retries,
                 replay_offset: self.tx_replayer.retry_offset,
+                replay_strategy: self.tx_replayer.replay_strategy,
+                durable_nonce_time_budget,
+                journal,
             },
             jh_services,
         )
     }
 }
 
+/// the transaction was not queued because the queue has no room, sending does not wait
+#[derive(Debug, thiserror::Error)]
+#[error("Transaction queue is full")]
+pub struct TransactionQueueFull;
+
+impl TransactionQueueFull {
+    pub fn is_queue_full(err: &anyhow::Error) -> bool {
+        err.downcast_ref::<TransactionQueueFull>().is_some()
+    }
+}
+
 #[derive(Clone)]
 pub struct TransactionService {
     pub transaction_channel: Sender<SentTransactionInfo>,
@@ -117,23 +140,143 @@ pub struct TransactionService {
     pub block_information_store: BlockInformationStore,
     pub max_retries: usize,
     pub replay_offset: Duration,
+    // used if the transaction does not ask for another strategy
+    pub replay_strategy: ReplayStrategy,
+    // how long durable nonce transactions are retried; derived from max retries if not set
+    pub durable_nonce_time_budget: Option<Duration>,
+    // pending transactions are journaled to disk if set
+    pub journal: Option<TransactionJournal>,
 }
 
 impl TransactionService {
+    // transactions waiting to be sent to the leaders
+    pub fn queue_depth(&self) -> usize {
+        self.transaction_channel.max_capacity() - self.transaction_channel.capacity()
+    }
+
     pub async fn send_transaction(
         &self,
         tx: VersionedTransaction,
         max_retries: Option<u16>,
+        replay_strategy: Option<ReplayStrategy>,
+        leader_filter: Option<LeaderFilter>,
     ) -> anyhow::Result<String> {
         let raw_tx = bincode::serialize(&tx)?;
-        self.send_wire_transaction(raw_tx, max_retries).await
+        self.send_wire_transaction(raw_tx, max_retries, replay_strategy, leader_filter)
+            .await
     }
 
     pub async fn send_wire_transaction(
         &self,
         raw_tx: Vec<u8>,
         max_retries: Option<u16>,
+        replay_strategy: Option<ReplayStrategy>,
+        leader_filter: Option<LeaderFilter>,
     ) -> anyhow::Result<String> {
+        let max_replay = max_retries.map_or(self.max_retries, |x| x as usize);
+        let leader_filter = leader_filter
+            .filter(|leader_filter| !leader_filter.is_empty())
+            .map(Arc::new);
+        let transaction_info = self
+            .prepare_transaction(raw_tx, max_replay, leader_filter)
+            .await?;
+        let signature = transaction_info.signature;
+
+        match self.transaction_channel.try_send(transaction_info.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => return Err(TransactionQueueFull.into()),
+            Err(e @ TrySendError::Closed(_)) => {
+                bail!(
+                    "Internal error sending transaction on send channel error {}",
+                    e
+                );
+            }
+        }
+        self.schedule_replay(transaction_info, 0, max_replay, replay_strategy);
+        Ok(signature.to_string())
+    }
+
+    /// sends the valid transactions of the batch together, results are in the order of the batch
+    pub async fn send_wire_transactions(
+        &self,
+        raw_txs: Vec<Vec<u8>>,
+        max_retries: Option<u16>,
+        replay_strategy: Option<ReplayStrategy>,
+        leader_filter: Option<LeaderFilter>,
+    ) -> Vec<anyhow::Result<String>> {
+        let max_replay = max_retries.map_or(self.max_retries, |x| x as usize);
+        let leader_filter = leader_filter
+            .filter(|leader_filter| !leader_filter.is_empty())
+            .map(Arc::new);
+        let mut results = Vec::with_capacity(raw_txs.len());
+        for raw_tx in raw_txs {
+            results.push(
+                self.prepare_transaction(raw_tx, max_replay, leader_filter.clone())
+                    .await,
+            );
+        }
+
+        let nb_valid = results.iter().filter(|result| result.is_ok()).count();
+        if nb_valid > self.transaction_channel.max_capacity() {
+            return results
+                .into_iter()
+                .map(|_| {
+                    Err(anyhow::anyhow!(
+                        "Batch exceeds the transaction queue capacity"
+                    ))
+                })
+                .collect();
+        }
+
+        // reserve the whole batch first so that it is queued without other transactions in between
+        // the batch is not queued at all if there is no room for all of it
+        let mut permits = Vec::with_capacity(nb_valid);
+        for _ in 0..nb_valid {
+            match self.transaction_channel.try_reserve() {
+                Ok(permit) => permits.push(permit),
+                Err(e) => {
+                    drop(permits);
+                    let queue_full = matches!(e, TrySendError::Full(()));
+                    return results
+                        .into_iter()
+                        .map(|result| {
+                            result.and_then(|_| {
+                                if queue_full {
+                                    return Err(TransactionQueueFull.into());
+                                }
+                                Err(anyhow::anyhow!(
+                                    "Internal error sending transaction on send channel error {}",
+                                    e
+                                ))
+                            })
+                        })
+                        .collect();
+                }
+            }
+        }
+
+        let mut permits = permits.into_iter();
+        results
+            .into_iter()
+            .map(|result| {
+                let transaction_info = result?;
+                let signature = transaction_info.signature;
+                permits
+                    .next()
+                    .expect("a permit per valid transaction")
+                    .send(transaction_info.clone());
+                self.schedule_replay(transaction_info, 0, max_replay, replay_strategy);
+                Ok(signature.to_string())
+            })
+            .collect()
+    }
+
+    async fn prepare_transaction(
+        &self,
+        raw_tx: Vec<u8>,
+        max_replay: usize,
+        leader_filter: Option<Arc<LeaderFilter>>,
+    ) -> anyhow::Result<SentTransactionInfo> {
         let tx = match bincode::deserialize::<VersionedTransaction>(&raw_tx) {
             Ok(tx) => tx,
             Err(err) => {
@@ -142,20 +285,37 @@ impl TransactionService {
         };
         let signature = tx.signatures[0];
 
-        let Some(BlockInformation {
-            slot,
-            last_valid_blockheight,
-            ..
-        }) = self
-            .block_information_store
-            .get_block_info(tx.get_recent_blockhash())
-        else {
-            bail!("Blockhash not found in block store".to_string());
-        };
+        // the recent blockhash of a durable nonce transaction is the nonce value which never lands in a block
+        let (slot, last_valid_blockheight) = if tx.uses_durable_nonce() {
+            let BlockInformation {
+                slot, block_height, ..
+            } = self
+                .block_information_store
+                .get_latest_block_information(CommitmentConfig::confirmed())
+                .await;
+            let horizon = durable_nonce_retry_horizon(
+                max_replay,
+                self.replay_offset,
+                self.durable_nonce_time_budget,
+            );
+            (slot, block_height + blocks_in_duration(horizon))
+        } else {
+            let Some(BlockInformation {
+                slot,
+                last_valid_blockheight,
+                ..
+            }) = self
+                .block_information_store
+                .get_block_info(tx.get_recent_blockhash())
+            else {
+                bail!("Blockhash not found in block store".to_string());
+            };
 
-        if self.block_information_store.get_last_blockheight() > last_valid_blockheight {
-            bail!("Blockhash is expired");
-        }
+            if self.block_information_store.get_last_blockheight() > last_valid_blockheight {
+                bail!("Blockhash is expired");
+            }
+            (slot, last_valid_blockheight)
+        };
 
         let prioritization_fee = {
             let mut prioritization_fee = 0;
@@ -177,40 +337,155 @@ impl TransactionService {
 
         PRIORITY_FEES_HISTOGRAM.observe(prioritization_fee as f64);
 
-        let max_replay = max_retries.map_or(self.max_retries, |x| x as usize);
-        let transaction_info = SentTransactionInfo {
+        Ok(SentTransactionInfo {
             signature,
             last_valid_block_height: last_valid_blockheight,
             slot,
             transaction: Arc::new(raw_tx),
             prioritization_fee,
-        };
-        if let Err(e) = self
-            .transaction_channel
-            .send(transaction_info.clone())
-            .await
-        {
-            bail!(
-                "Internal error sending transaction on send channel error {}",
-                e
+            leader_filter,
+        })
+    }
+
+    /// resends the transactions journaled by a previous run which can still land
+    /// returns the number of restored and dropped transactions
+    pub async fn restore_journal_entries(
+        &self,
+        entries: Vec<JournalEntry>,
+    ) -> anyhow::Result<(usize, usize)> {
+        let current_blockheight = self.block_information_store.get_last_blockheight();
+        let mut restored = 0;
+        let mut dropped = 0;
+        for entry in entries {
+            if entry.last_valid_block_height < current_blockheight {
+                dropped += 1;
+                continue;
+            }
+            let transaction_info = entry.transaction_info();
+            if let Err(e) = self
+                .transaction_channel
+                .send(transaction_info.clone())
+                .await
+            {
+                bail!(
+                    "Internal error sending transaction on send channel error {}",
+                    e
+                );
+            }
+            self.schedule_replay(
+                transaction_info,
+                entry.replay_count,
+                entry.max_replay,
+                Some(entry.strategy),
             );
+            restored += 1;
+        }
+        Ok((restored, dropped))
+    }
+
+    fn schedule_replay(
+        &self,
+        transaction_info: SentTransactionInfo,
+        replay_count: usize,
+        max_replay: usize,
+        replay_strategy: Option<ReplayStrategy>,
+    ) {
+        let strategy = replay_strategy.unwrap_or(self.replay_strategy);
+        if let Some(journal) = &self.journal {
+            journal.record(&transaction_info, replay_count, max_replay, strategy);
         }
-        let replay_at = Instant::now() + self.replay_offset;
+        let replay_at = Instant::now() + strategy.replay_delay(self.replay_offset, replay_count);
         // ignore error for replay service
         if self
             .replay_channel
             .send(TransactionReplay {
                 transaction: transaction_info,
-                replay_count: 0,
+                replay_count,
                 max_replay,
                 replay_at,
+                strategy,
+                last_leader: None,
             })
             .is_ok()
         {
             MESSAGES_IN_REPLAY_QUEUE.inc();
         }
-        Ok(signature.to_string())
     }
 }
 
-mod test {}
+// time budget if configured, otherwise long enough for all replays
+fn durable_nonce_retry_horizon(
+    max_replay: usize,
+    replay_offset: Duration,
+    time_budget: Option<Duration>,
+) -> Duration {
+    time_budget.unwrap_or_else(|| replay_offset * (max_replay as u32 + 1))
+}
+
+fn blocks_in_duration(duration: Duration) -> u64 {
+    (duration.as_millis() as u64).div_ceil(DEFAULT_MS_PER_SLOT)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::{Message, VersionedMessage};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use solana_sdk::system_instruction;
+
+    #[test]
+    fn detects_queue_full() {
+        use anyhow::Context;
+        let err: anyhow::Result<()> = Err(TransactionQueueFull.into());
+        assert!(TransactionQueueFull::is_queue_full(
+            &err.context("send transaction").unwrap_err()
+        ));
+        assert!(!TransactionQueueFull::is_queue_full(&anyhow::anyhow!(
+            "Blockhash is expired"
+        )));
+    }
+
+    #[test]
+    fn detects_durable_nonce_transactions() {
+        let payer = Keypair::new();
+        let nonce_account = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        let nonce_message = Message::new_with_nonce(
+            vec![transfer.clone()],
+            Some(&payer.pubkey()),
+            &nonce_account,
+            &payer.pubkey(),
+        );
+        let nonce_tx = VersionedTransaction::try_new(
+            VersionedMessage::Legacy(Message {
+                recent_blockhash: Hash::new_unique(),
+                ..nonce_message
+            }),
+            &[&payer],
+        )
+        .unwrap();
+        assert!(nonce_tx.uses_durable_nonce());
+
+        let blockhash_message =
+            Message::new_with_blockhash(&[transfer], Some(&payer.pubkey()), &Hash::new_unique());
+        let blockhash_tx =
+            VersionedTransaction::try_new(VersionedMessage::Legacy(blockhash_message), &[&payer])
+                .unwrap();
+        assert!(!blockhash_tx.uses_durable_nonce());
+    }
+
+    #[test]
+    fn durable_nonce_horizon() {
+        // 40 retries every 3 seconds plus the initial send
+        let horizon = durable_nonce_retry_horizon(40, Duration::from_secs(3), None);
+        assert_eq!(horizon, Duration::from_secs(123));
+        assert_eq!(blocks_in_duration(horizon), 308);
+
+        let horizon =
+            durable_nonce_retry_horizon(40, Duration::from_secs(3), Some(Duration::from_secs(600)));
+        assert_eq!(blocks_in_duration(horizon), 1500);
+    }
+}
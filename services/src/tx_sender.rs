@@ -55,8 +55,19 @@ impl TxSender {
                 status: None,
                 last_valid_blockheight: transaction_info.last_valid_block_height,
                 sent_by_lite_rpc: true,
+                expired_slot: None,
             },
         );
+        let received_slot = self.data_cache.slot_cache.get_current_slot();
+        self.data_cache.tx_lifecycles.record_received(
+            transaction_info.signature,
+            received_slot,
+            transaction_info.last_valid_block_height,
+        );
+        self.data_cache
+            .tx_subs
+            .notify_received(&transaction_info.signature, received_slot)
+            .await;
 
         match self.tpu_service.send_transaction(transaction_info) {
             Ok(_) => {